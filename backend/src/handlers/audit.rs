@@ -0,0 +1,7 @@
+use crate::AppState;
+use axum::{extract::State, response::IntoResponse, Json};
+use std::sync::Arc;
+
+pub async fn list_audit_entries(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.audit_log.list_entries()).into_response()
+}
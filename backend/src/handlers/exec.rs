@@ -0,0 +1,353 @@
+use crate::managers::audit::{AuditEvent, AuditSession};
+use crate::models::DebugContainerSpec;
+use crate::AppState;
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    response::Response,
+};
+use futures::{SinkExt, StreamExt};
+use kube::api::{AttachedProcess, TerminalSize};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+// Frames follow the Kubernetes channel protocol: the first byte of a binary
+// message names the stream, the rest is the payload.
+pub const STDIN_CHANNEL: u8 = 0;
+pub const STDOUT_CHANNEL: u8 = 1;
+pub const STDERR_CHANNEL: u8 = 2;
+pub const STATUS_CHANNEL: u8 = 3;
+pub const RESIZE_CHANNEL: u8 = 4;
+
+const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 15 * 60;
+const DEFAULT_SHELL: &str = "/bin/sh";
+const DEFAULT_DEBUG_IMAGE: &str = "busybox:1.36";
+
+#[derive(Deserialize)]
+pub struct ExecQuery {
+    namespace: Option<String>,
+    container: Option<String>,
+    /// Whitespace-separated command line; defaults to `/bin/sh`
+    command: Option<String>,
+    #[serde(default = "default_tty")]
+    tty: bool,
+    /// Seconds without client input before the session is closed
+    idle_timeout: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct DebugQuery {
+    namespace: Option<String>,
+    image: Option<String>,
+    /// Container whose process namespace the debugger joins
+    target: Option<String>,
+    /// Whitespace-separated command line; defaults to the image entrypoint
+    command: Option<String>,
+    /// Seconds without client input before the session is closed
+    idle_timeout: Option<u64>,
+}
+
+fn default_tty() -> bool {
+    true
+}
+
+/// What the browser sent us, decoded from a WebSocket message
+#[derive(Debug)]
+pub enum ClientFrame {
+    Stdin(Vec<u8>),
+    Resize(TerminalSize),
+    Close,
+    Ignore,
+}
+
+pub async fn exec_pod(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<ExecQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    let mut command = parse_command(query.command.as_deref());
+    if command.is_empty() {
+        command.push(DEFAULT_SHELL.to_string());
+    }
+    let idle_timeout = Duration::from_secs(query.idle_timeout.unwrap_or(DEFAULT_IDLE_TIMEOUT_SECS));
+
+    ws.on_upgrade(move |socket| async move {
+        let session = AuditSession::new(
+            &context,
+            &namespace,
+            &name,
+            query.container.clone(),
+            command.clone(),
+        );
+        state
+            .audit_log
+            .record(session.entry(AuditEvent::SessionStart));
+
+        let reason = match state
+            .k8s_service
+            .exec_pod(
+                &context,
+                &name,
+                &namespace,
+                query.container,
+                command,
+                query.tty,
+            )
+            .await
+        {
+            Ok(process) => bridge_session(socket, process, idle_timeout).await,
+            Err(e) => {
+                let reason = format!("failed to start: {}", e);
+                close_with_error(socket, &reason).await;
+                reason
+            }
+        };
+
+        state
+            .audit_log
+            .record(session.entry(AuditEvent::SessionEnd { reason }));
+    })
+}
+
+/// Injects an ephemeral container (for images without a shell) and attaches to it
+/// once it is running, reusing the exec bridge for the session.
+pub async fn debug_pod(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<DebugQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    let spec = DebugContainerSpec {
+        name: format!("debugger-{}", &Uuid::new_v4().simple().to_string()[..5]),
+        image: query
+            .image
+            .unwrap_or_else(|| DEFAULT_DEBUG_IMAGE.to_string()),
+        target_container: query.target,
+        command: parse_command(query.command.as_deref()),
+    };
+    let idle_timeout = Duration::from_secs(query.idle_timeout.unwrap_or(DEFAULT_IDLE_TIMEOUT_SECS));
+
+    // Recorded like the equivalent `kubectl debug` invocation
+    let mut audit_command = vec!["debug".to_string(), format!("--image={}", spec.image)];
+    if let Some(target) = &spec.target_container {
+        audit_command.push(format!("--target={}", target));
+    }
+    audit_command.extend(spec.command.iter().cloned());
+
+    ws.on_upgrade(move |mut socket| async move {
+        let container = spec.name.clone();
+        let session = AuditSession::new(
+            &context,
+            &namespace,
+            &name,
+            Some(container.clone()),
+            audit_command,
+        );
+        state
+            .audit_log
+            .record(session.entry(AuditEvent::SessionStart));
+
+        let notice = format!("Waiting for debug container {} to start...\r\n", container);
+        let _ = socket
+            .send(Message::Binary(framed(STDOUT_CHANNEL, notice.as_bytes())))
+            .await;
+
+        let attached = match state
+            .k8s_service
+            .create_debug_container(&context, &name, &namespace, spec)
+            .await
+        {
+            Ok(()) => {
+                state
+                    .k8s_service
+                    .attach_pod(&context, &name, &namespace, container, true)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        let reason = match attached {
+            Ok(process) => bridge_session(socket, process, idle_timeout).await,
+            Err(e) => {
+                let reason = format!("failed to start: {}", e);
+                close_with_error(socket, &reason).await;
+                reason
+            }
+        };
+
+        state
+            .audit_log
+            .record(session.entry(AuditEvent::SessionEnd { reason }));
+    })
+}
+
+fn parse_command(command: Option<&str>) -> Vec<String> {
+    command
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+pub fn decode_client_message(message: Message) -> ClientFrame {
+    match message {
+        Message::Binary(data) => match data.split_first() {
+            Some((&STDIN_CHANNEL, payload)) => ClientFrame::Stdin(payload.to_vec()),
+            Some((&RESIZE_CHANNEL, payload)) => serde_json::from_slice(payload)
+                .map(ClientFrame::Resize)
+                .unwrap_or(ClientFrame::Ignore),
+            _ => ClientFrame::Ignore,
+        },
+        // Plain text is treated as keyboard input so simple clients don't need framing
+        Message::Text(text) => ClientFrame::Stdin(text.as_bytes().to_vec()),
+        Message::Close(_) => ClientFrame::Close,
+        _ => ClientFrame::Ignore,
+    }
+}
+
+/// Pumps a WebSocket to an attached process until either side goes away or the
+/// client stops typing for `idle_timeout`. Returns why the session ended.
+pub async fn bridge_session(
+    socket: WebSocket,
+    mut process: AttachedProcess,
+    idle_timeout: Duration,
+) -> String {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let mut stdin = process.stdin();
+    let mut resize = process.terminal_size();
+    let status = process.take_status();
+
+    let (out_tx, mut out_rx) = mpsc::channel::<Message>(32);
+    if let Some(stdout) = process.stdout() {
+        tokio::spawn(pump_output(stdout, STDOUT_CHANNEL, out_tx.clone()));
+    }
+    if let Some(stderr) = process.stderr() {
+        tokio::spawn(pump_output(stderr, STDERR_CHANNEL, out_tx.clone()));
+    }
+    drop(out_tx);
+
+    let status = async move {
+        match status {
+            Some(status) => status.await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(status);
+    let idle = tokio::time::sleep(idle_timeout);
+    tokio::pin!(idle);
+
+    let reason = loop {
+        tokio::select! {
+            incoming = ws_rx.next() => match incoming {
+                Some(Ok(message)) => match decode_client_message(message) {
+                    ClientFrame::Stdin(data) => {
+                        idle.as_mut().reset(tokio::time::Instant::now() + idle_timeout);
+                        if let Some(writer) = stdin.as_mut() {
+                            if writer.write_all(&data).await.is_err() {
+                                break "stdin closed".to_string();
+                            }
+                        }
+                    }
+                    ClientFrame::Resize(size) => {
+                        if let Some(tx) = resize.as_mut() {
+                            let _ = tx.send(size).await;
+                        }
+                    }
+                    ClientFrame::Close => break "client closed".to_string(),
+                    ClientFrame::Ignore => {}
+                },
+                Some(Err(e)) => break format!("websocket error: {}", e),
+                None => break "client closed".to_string(),
+            },
+            Some(frame) = out_rx.recv() => {
+                if ws_tx.send(frame).await.is_err() {
+                    break "client disconnected".to_string();
+                }
+            }
+            exit = &mut status => {
+                // Flush output the process wrote just before exiting
+                while let Ok(Some(frame)) =
+                    tokio::time::timeout(Duration::from_secs(1), out_rx.recv()).await
+                {
+                    if ws_tx.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+                let payload = serde_json::to_vec(&exit).unwrap_or_default();
+                let _ = ws_tx
+                    .send(Message::Binary(framed(STATUS_CHANNEL, &payload)))
+                    .await;
+                break match exit.and_then(|s| s.status) {
+                    Some(status) => format!("exited: {}", status),
+                    None => "exited".to_string(),
+                };
+            }
+            _ = &mut idle => {
+                let _ = ws_tx
+                    .send(Message::Close(Some(CloseFrame {
+                        code: close_code::NORMAL,
+                        reason: "idle timeout".into(),
+                    })))
+                    .await;
+                break "idle timeout".to_string();
+            }
+        }
+    };
+
+    process.abort();
+    let _ = ws_tx.close().await;
+    reason
+}
+
+pub async fn close_with_error(mut socket: WebSocket, reason: &str) {
+    // Close frame reasons are capped at 123 bytes by the WebSocket spec
+    let mut end = reason.len().min(120);
+    while !reason.is_char_boundary(end) {
+        end -= 1;
+    }
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: close_code::ERROR,
+            reason: reason[..end].into(),
+        })))
+        .await;
+}
+
+async fn pump_output(
+    mut reader: impl AsyncRead + Unpin + Send + 'static,
+    channel: u8,
+    tx: mpsc::Sender<Message>,
+) {
+    let mut buf = vec![0u8; 8192];
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if tx
+                    .send(Message::Binary(framed(channel, &buf[..n])))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn framed(channel: u8, payload: &[u8]) -> Bytes {
+    let mut frame = Vec::with_capacity(payload.len() + 1);
+    frame.push(channel);
+    frame.extend_from_slice(payload);
+    frame.into()
+}
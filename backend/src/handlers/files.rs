@@ -0,0 +1,257 @@
+use crate::managers::audit::{AuditEvent, AuditSession};
+use crate::AppState;
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+
+const TAR_CONTENT_TYPE: &str = "application/x-tar";
+
+#[derive(Deserialize)]
+pub struct FileCopyQuery {
+    namespace: Option<String>,
+    container: Option<String>,
+    /// Remote file or directory to download, or the upload destination
+    path: String,
+}
+
+/// Splits a remote path into the directory tar should `-C` into and the entry to archive
+pub fn split_remote_path(path: &str) -> (String, String) {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return ("/".to_string(), ".".to_string());
+    }
+    match trimmed.rsplit_once('/') {
+        Some(("", name)) => ("/".to_string(), name.to_string()),
+        Some((parent, name)) => (parent.to_string(), name.to_string()),
+        None => (".".to_string(), trimmed.to_string()),
+    }
+}
+
+/// Streams a file or directory out of a container as a tar archive, like `kubectl cp pod:path .`
+pub async fn download_files(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<FileCopyQuery>,
+) -> Response {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    let (parent, entry) = split_remote_path(&query.path);
+    let command = vec![
+        "tar".to_string(),
+        "cf".to_string(),
+        "-".to_string(),
+        "-C".to_string(),
+        parent,
+        entry.clone(),
+    ];
+
+    let session = AuditSession::new(
+        &context,
+        &namespace,
+        &name,
+        query.container.clone(),
+        command.clone(),
+    );
+    state
+        .audit_log
+        .record(session.entry(AuditEvent::SessionStart));
+
+    let mut process = match state
+        .k8s_service
+        .exec_pod(&context, &name, &namespace, query.container, command, false)
+        .await
+    {
+        Ok(process) => process,
+        Err(e) => {
+            let reason = format!("Failed to start download: {}", e);
+            state
+                .audit_log
+                .record(session.entry(AuditEvent::SessionEnd {
+                    reason: reason.clone(),
+                }));
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": reason })),
+            )
+                .into_response();
+        }
+    };
+
+    let (Some(mut stdout), Some(stderr)) = (process.stdout(), process.stderr()) else {
+        process.abort();
+        state
+            .audit_log
+            .record(session.entry(AuditEvent::SessionEnd {
+                reason: "exec session has no output streams".to_string(),
+            }));
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": "Exec session has no output streams" })),
+        )
+            .into_response();
+    };
+    let stderr_task = tokio::spawn(read_to_string(stderr));
+
+    // Peek at the first chunk so a missing path is reported as an error instead of an empty archive
+    let mut first = vec![0u8; 64 * 1024];
+    let read = stdout.read(&mut first).await.unwrap_or(0);
+    if read == 0 {
+        let _ = process.join().await;
+        let stderr = stderr_task.await.unwrap_or_default();
+        let reason = format!("Failed to download {}: {}", query.path, stderr.trim());
+        state
+            .audit_log
+            .record(session.entry(AuditEvent::SessionEnd {
+                reason: reason.clone(),
+            }));
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": reason }))).into_response();
+    }
+    first.truncate(read);
+
+    let audit_log = state.audit_log.clone();
+    tokio::spawn(async move {
+        let reason = match process.join().await {
+            Ok(()) => "download finished".to_string(),
+            Err(e) => format!("download failed: {}", e),
+        };
+        let stderr = stderr_task.await.unwrap_or_default();
+        let reason = if stderr.trim().is_empty() {
+            reason
+        } else {
+            format!("{} ({})", reason, stderr.trim())
+        };
+        audit_log.record(session.entry(AuditEvent::SessionEnd { reason }));
+    });
+
+    let stream = futures::stream::once(async move { Ok(Bytes::from(first)) })
+        .chain(ReaderStream::new(stdout));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, TAR_CONTENT_TYPE.parse().unwrap());
+    if let Ok(disposition) = format!("attachment; filename=\"{}.tar\"", entry).parse() {
+        headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+    (StatusCode::OK, headers, Body::from_stream(stream)).into_response()
+}
+
+/// Streams the request body into a container. A tar archive (`Content-Type: application/x-tar`)
+/// is unpacked into `path` as a directory; any other body is written to `path` as a single file.
+pub async fn upload_files(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<FileCopyQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    let is_tar = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with(TAR_CONTENT_TYPE));
+
+    let command: Vec<String> = if is_tar {
+        vec!["tar", "xmf", "-", "-C", &query.path]
+    } else {
+        // The path is passed as an argument rather than spliced into the script
+        vec!["sh", "-c", "cat > \"$0\"", &query.path]
+    }
+    .into_iter()
+    .map(String::from)
+    .collect();
+
+    let session = AuditSession::new(
+        &context,
+        &namespace,
+        &name,
+        query.container.clone(),
+        command.clone(),
+    );
+    state
+        .audit_log
+        .record(session.entry(AuditEvent::SessionStart));
+
+    let result: Result<(), Box<dyn std::error::Error + Send + Sync>> = async {
+        let mut process = state
+            .k8s_service
+            .exec_pod(&context, &name, &namespace, query.container, command, false)
+            .await?;
+
+        let mut stdin = process.stdin().ok_or("Exec session has no stdin")?;
+        let stderr_task = process.stderr().map(|s| tokio::spawn(read_to_string(s)));
+        let stdout_task = process.stdout().map(|s| tokio::spawn(read_to_string(s)));
+        let status = process.take_status();
+
+        let mut body = body.into_data_stream();
+        while let Some(chunk) = body.next().await {
+            stdin.write_all(&chunk?).await?;
+        }
+        stdin.shutdown().await?;
+        drop(stdin);
+
+        let status = match status {
+            Some(status) => status.await,
+            None => None,
+        };
+        if let Some(task) = stdout_task {
+            let _ = task.await;
+        }
+        let stderr = match stderr_task {
+            Some(task) => task.await.unwrap_or_default(),
+            None => String::new(),
+        };
+
+        match status.as_ref().and_then(|s| s.status.as_deref()) {
+            Some("Success") => Ok(()),
+            _ => Err(format!(
+                "Upload to {} failed: {}",
+                query.path,
+                if stderr.trim().is_empty() {
+                    status.and_then(|s| s.message).unwrap_or_default()
+                } else {
+                    stderr.trim().to_string()
+                }
+            )
+            .into()),
+        }
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            state
+                .audit_log
+                .record(session.entry(AuditEvent::SessionEnd {
+                    reason: "upload finished".to_string(),
+                }));
+            (StatusCode::OK, Json(json!({ "path": query.path }))).into_response()
+        }
+        Err(e) => {
+            let reason = e.to_string();
+            state
+                .audit_log
+                .record(session.entry(AuditEvent::SessionEnd {
+                    reason: reason.clone(),
+                }));
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": reason })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn read_to_string(mut reader: impl AsyncRead + Unpin) -> String {
+    let mut buf = Vec::new();
+    let _ = reader.read_to_end(&mut buf).await;
+    String::from_utf8_lossy(&buf).into_owned()
+}
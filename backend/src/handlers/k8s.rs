@@ -1,17 +1,123 @@
-use crate::models::K8sResourceType;
+use crate::models::{
+    DrainEvent, DrainOptions, EventFilter, GraphData, GraphDetail, GraphFormat, GraphOptions,
+    HistoryRange, K8sResourceType, NamespaceGraphOptions, PatchType, ReplaceOutcome, ResourcePatch,
+    TableOptions, UnusedResourceOptions,
+};
+use crate::services::graph_export::render_graph;
 use crate::AppState;
 use axum::{
     extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Deserialize)]
 pub struct GetResourceQuery {
     namespace: Option<String>,
     container: Option<String>,
+    /// Adds the object's Events under `events`
+    #[serde(default)]
+    events: bool,
+}
+
+#[derive(Deserialize)]
+pub struct TableQuery {
+    /// Column to sort by, e.g. `Restarts` or `Age`
+    sort_by: Option<String>,
+    #[serde(default)]
+    descending: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ListResourcesQuery {
+    /// Adds live cpu and memory under `usage` to Pods and Nodes
+    #[serde(default)]
+    usage: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UsageQuery {
+    /// All namespaces when unset
+    namespace: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    namespace: Option<String>,
+    name: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    /// Bucket size in seconds; each bucket keeps its peak
+    step: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct OverviewQuery {
+    /// Skips the cache and rebuilds the overview from the cluster
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Deserialize)]
+pub struct GraphQuery {
+    namespace: Option<String>,
+    /// Ownership hops to follow in each direction; defaults to 1
+    depth: Option<usize>,
+    #[serde(default)]
+    detail: GraphDetail,
+    #[serde(default)]
+    format: GraphFormat,
+    /// Adds live cpu and memory under `usage` to Pod and Node nodes; json format only
+    #[serde(default)]
+    usage: bool,
+}
+
+#[derive(Deserialize)]
+pub struct NamespaceGraphQuery {
+    /// Comma-separated kinds to leave out, e.g. `ReplicaSet,Secret`
+    exclude: Option<String>,
+    /// Set to false to keep unrelated objects as individual nodes
+    collapse: Option<bool>,
+    #[serde(default)]
+    detail: GraphDetail,
+    #[serde(default)]
+    format: GraphFormat,
+    /// Adds live cpu and memory under `usage` to Pod and Node nodes; json format only
+    #[serde(default)]
+    usage: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UnusedQuery {
+    /// Report completed Jobs that finished at least this many days ago; defaults to 7
+    job_age_days: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct PatchResourceQuery {
+    namespace: Option<String>,
+    #[serde(default)]
+    patch_type: PatchType,
+    #[serde(default)]
+    dry_run: bool,
+    resource_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReplaceResourceQuery {
+    namespace: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
 }
 
 pub async fn list_contexts(State(state): State<Arc<AppState>>) -> Json<Value> {
@@ -24,44 +130,435 @@ pub async fn list_contexts(State(state): State<Arc<AppState>>) -> Json<Value> {
 pub async fn list_resources(
     State(state): State<Arc<AppState>>,
     Path((context, resource_type)): Path<(String, K8sResourceType)>,
+    Query(query): Query<ListResourcesQuery>,
+) -> Json<Value> {
+    let mut resources = match state
+        .k8s_service
+        .list_resources(&context, resource_type.clone())
+        .await
+    {
+        Ok(resources) => resources,
+        Err(e) => return Json(json!({ "error": format!("Failed to list resources: {}", e) })),
+    };
+    if query.usage {
+        attach_usage(&state, &context, &resource_type, &mut resources).await;
+    }
+    Json(json!(resources))
+}
+
+/// Adds `usage` to listed Pods or Nodes. The list is still worth showing without it, e.g.
+/// when metrics-server isn't installed.
+async fn attach_usage(
+    state: &AppState,
+    context: &str,
+    resource_type: &K8sResourceType,
+    resources: &mut [Value],
+) {
+    let key = |resource: &Value, field: &str| {
+        resource
+            .pointer(&format!("/metadata/{}", field))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let usage: HashMap<(String, String), Value> = match resource_type {
+        K8sResourceType::Pod => match state.k8s_service.list_pod_usage(context, None).await {
+            Ok(report) => report
+                .items
+                .into_iter()
+                .map(|p| ((p.namespace, p.name), json!(p.usage)))
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Failed to read pod usage: {}", e);
+                return;
+            }
+        },
+        K8sResourceType::Node => match state.k8s_service.list_node_usage(context).await {
+            Ok(report) => report
+                .items
+                .into_iter()
+                .map(|n| ((String::new(), n.name), json!(n.usage)))
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Failed to read node usage: {}", e);
+                return;
+            }
+        },
+        _ => return,
+    };
+    for resource in resources {
+        if let Some(usage) = usage.get(&(key(resource, "namespace"), key(resource, "name"))) {
+            resource["usage"] = usage.clone();
+        }
+    }
+}
+
+pub async fn list_pod_usage(
+    State(state): State<Arc<AppState>>,
+    Path(context): Path<String>,
+    Query(query): Query<UsageQuery>,
 ) -> Json<Value> {
     match state
         .k8s_service
-        .list_resources(&context, resource_type)
+        .list_pod_usage(&context, query.namespace)
         .await
     {
-        Ok(resources) => Json(json!(resources)),
+        Ok(report) => {
+            if report.metrics_available {
+                state.metrics_history.watch(&context);
+            }
+            Json(json!(report))
+        }
+        Err(e) => Json(json!({ "error": format!("Failed to read pod usage: {}", e) })),
+    }
+}
+
+pub async fn list_node_usage(
+    State(state): State<Arc<AppState>>,
+    Path(context): Path<String>,
+) -> Json<Value> {
+    match state.k8s_service.list_node_usage(&context).await {
+        Ok(report) => {
+            if report.metrics_available {
+                state.metrics_history.watch(&context);
+            }
+            Json(json!(report))
+        }
+        Err(e) => Json(json!({ "error": format!("Failed to read node usage: {}", e) })),
+    }
+}
+
+async fn usage_history(
+    state: &AppState,
+    context: &str,
+    kind: K8sResourceType,
+    query: HistoryQuery,
+) -> Json<Value> {
+    // A context not sampled yet is sampled once now, and from then on if that worked
+    let history = &state.metrics_history;
+    if !history.touch(context) && history.sample(state.k8s_service.as_ref(), context).await {
+        history.watch(context);
+    }
+    let range = HistoryRange {
+        since: query.since,
+        until: query.until,
+        step_seconds: query.step,
+    };
+    Json(json!(state.metrics_history.query(
+        context,
+        kind,
+        query.namespace.as_deref(),
+        query.name.as_deref(),
+        &range,
+    )))
+}
+
+pub async fn list_pod_usage_history(
+    State(state): State<Arc<AppState>>,
+    Path(context): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<Value> {
+    usage_history(&state, &context, K8sResourceType::Pod, query).await
+}
+
+pub async fn list_node_usage_history(
+    State(state): State<Arc<AppState>>,
+    Path(context): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<Value> {
+    usage_history(&state, &context, K8sResourceType::Node, query).await
+}
+
+pub async fn get_cluster_overview(
+    State(state): State<Arc<AppState>>,
+    Path(context): Path<String>,
+    Query(query): Query<OverviewQuery>,
+) -> Json<Value> {
+    let overview = state
+        .overview_cache
+        .get_or_build(&context, query.refresh, || {
+            state.k8s_service.get_cluster_overview(&context)
+        })
+        .await;
+    match overview {
+        Ok(overview) => Json(json!(overview)),
+        Err(e) => Json(json!({ "error": format!("Failed to build cluster overview: {}", e) })),
+    }
+}
+
+pub async fn list_resource_table(
+    State(state): State<Arc<AppState>>,
+    Path((context, resource_type)): Path<(String, K8sResourceType)>,
+    Query(query): Query<TableQuery>,
+) -> Json<Value> {
+    let options = TableOptions {
+        sort_by: query.sort_by,
+        descending: query.descending,
+    };
+    match state
+        .k8s_service
+        .list_resource_table(&context, resource_type, options)
+        .await
+    {
+        Ok(table) => Json(json!(table)),
         Err(e) => Json(json!({ "error": format!("Failed to list resources: {}", e) })),
     }
 }
 
+pub async fn list_custom_resource_table(
+    State(state): State<Arc<AppState>>,
+    Path((context, crd)): Path<(String, String)>,
+    Query(query): Query<TableQuery>,
+) -> Json<Value> {
+    let options = TableOptions {
+        sort_by: query.sort_by,
+        descending: query.descending,
+    };
+    match state
+        .k8s_service
+        .list_custom_resource_table(&context, &crd, options)
+        .await
+    {
+        Ok(table) => Json(json!(table)),
+        Err(e) => Json(json!({ "error": format!("Failed to list custom resources: {}", e) })),
+    }
+}
+
 pub async fn get_resource(
     State(state): State<Arc<AppState>>,
     Path((context, resource_type, name)): Path<(String, K8sResourceType, String)>,
     Query(query): Query<GetResourceQuery>,
 ) -> Json<Value> {
-    match state
+    let mut resource = match state
         .k8s_service
         .get_resource(&context, resource_type, &name, query.namespace)
         .await
+    {
+        Ok(resource) => resource,
+        Err(e) => return Json(json!({ "error": format!("Failed to get resource: {}", e) })),
+    };
+    if query.events {
+        let metadata_field = |field: &str| {
+            resource
+                .pointer(&format!("/metadata/{}", field))
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+        let filter = EventFilter {
+            namespace: metadata_field("namespace"),
+            involved_uid: metadata_field("uid"),
+            ..Default::default()
+        };
+        // The object is still worth showing when its Events can't be read
+        match state.k8s_service.list_events(&context, filter).await {
+            Ok(events) => resource["events"] = json!(events),
+            Err(e) => tracing::warn!("Failed to list events for {}: {}", name, e),
+        }
+    }
+    Json(resource)
+}
+
+pub async fn patch_resource(
+    State(state): State<Arc<AppState>>,
+    Path((context, resource_type, name)): Path<(String, K8sResourceType, String)>,
+    Query(query): Query<PatchResourceQuery>,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    let patch = ResourcePatch {
+        patch_type: query.patch_type,
+        body,
+        dry_run: query.dry_run,
+        resource_version: query.resource_version,
+    };
+    match state
+        .k8s_service
+        .patch_resource(&context, resource_type, &name, query.namespace, patch)
+        .await
     {
         Ok(resource) => Json(resource),
-        Err(e) => Json(json!({ "error": format!("Failed to get resource: {}", e) })),
+        Err(e) => Json(json!({ "error": format!("Failed to patch resource: {}", e) })),
+    }
+}
+
+pub async fn replace_resource(
+    State(state): State<Arc<AppState>>,
+    Path((context, resource_type, name)): Path<(String, K8sResourceType, String)>,
+    Query(query): Query<ReplaceResourceQuery>,
+    Json(object): Json<Value>,
+) -> impl IntoResponse {
+    match state
+        .k8s_service
+        .replace_resource(
+            &context,
+            resource_type,
+            &name,
+            query.namespace,
+            object,
+            query.dry_run,
+        )
+        .await
+    {
+        Ok(outcome @ ReplaceOutcome::Conflict { .. }) => {
+            (StatusCode::CONFLICT, Json(json!(outcome))).into_response()
+        }
+        Ok(outcome) => (StatusCode::OK, Json(json!(outcome))).into_response(),
+        Err(e) => {
+            Json(json!({ "error": format!("Failed to replace resource: {}", e) })).into_response()
+        }
     }
 }
 
 pub async fn get_resource_graph(
     State(state): State<Arc<AppState>>,
     Path((context, resource_type, name)): Path<(String, K8sResourceType, String)>,
-    Query(query): Query<GetResourceQuery>,
+    Query(query): Query<GraphQuery>,
+) -> Response {
+    let mut options = GraphOptions {
+        detail: query.detail,
+        usage: query.usage && query.format == GraphFormat::Json,
+        ..Default::default()
+    };
+    if let Some(depth) = query.depth {
+        options.depth = depth;
+    }
+    match state
+        .k8s_service
+        .get_resource_graph(&context, resource_type, &name, query.namespace, options)
+        .await
+    {
+        Ok(graph) => graph_response(&graph, &query.format),
+        Err(e) => {
+            Json(json!({ "error": format!("Failed to get resource graph: {}", e) })).into_response()
+        }
+    }
+}
+
+pub async fn get_namespace_graph(
+    State(state): State<Arc<AppState>>,
+    Path((context, namespace)): Path<(String, String)>,
+    Query(query): Query<NamespaceGraphQuery>,
+) -> Response {
+    let mut options = NamespaceGraphOptions {
+        detail: query.detail,
+        usage: query.usage && query.format == GraphFormat::Json,
+        ..Default::default()
+    };
+    for kind in query
+        .exclude
+        .iter()
+        .flat_map(|e| e.split(','))
+        .map(str::trim)
+    {
+        if kind.is_empty() {
+            continue;
+        }
+        match K8sResourceType::from_kind(kind) {
+            Some(resource_type) => options.exclude.push(resource_type),
+            None => {
+                return Json(json!({ "error": format!("Unknown resource kind: {}", kind) }))
+                    .into_response()
+            }
+        }
+    }
+    if let Some(collapse) = query.collapse {
+        options.collapse_unrelated = collapse;
+    }
+
+    match state
+        .k8s_service
+        .get_namespace_graph(&context, &namespace, options)
+        .await
+    {
+        Ok(graph) => graph_response(&graph, &query.format),
+        Err(e) => Json(json!({ "error": format!("Failed to get namespace graph: {}", e) }))
+            .into_response(),
+    }
+}
+
+pub async fn find_dangling_references(
+    State(state): State<Arc<AppState>>,
+    Path((context, namespace)): Path<(String, String)>,
 ) -> Json<Value> {
     match state
         .k8s_service
-        .get_resource_graph(&context, resource_type, &name, query.namespace)
+        .find_dangling_references(&context, &namespace)
         .await
     {
-        Ok(graph) => Json(json!(graph)),
-        Err(e) => Json(json!({ "error": format!("Failed to get resource graph: {}", e) })),
+        Ok(findings) => Json(json!(findings)),
+        Err(e) => Json(json!({ "error": format!("Failed to scan references: {}", e) })),
+    }
+}
+
+pub async fn find_unused_resources(
+    State(state): State<Arc<AppState>>,
+    Path((context, namespace)): Path<(String, String)>,
+    Query(query): Query<UnusedQuery>,
+) -> Json<Value> {
+    let mut options = UnusedResourceOptions::default();
+    if let Some(days) = query.job_age_days {
+        options.completed_job_days = days;
+    }
+    match state
+        .k8s_service
+        .find_unused_resources(&context, &namespace, options)
+        .await
+    {
+        Ok(findings) => Json(json!(findings)),
+        Err(e) => Json(json!({ "error": format!("Failed to scan for unused resources: {}", e) })),
+    }
+}
+
+pub async fn list_events(
+    State(state): State<Arc<AppState>>,
+    Path(context): Path<String>,
+    Query(filter): Query<EventFilter>,
+) -> Json<Value> {
+    match state.k8s_service.list_events(&context, filter).await {
+        Ok(events) => Json(json!(events)),
+        Err(e) => Json(json!({ "error": format!("Failed to list events: {}", e) })),
+    }
+}
+
+/// Streams Events as they are reported, as server-sent events. The watch stops when the
+/// client disconnects.
+pub async fn stream_events(
+    State(state): State<Arc<AppState>>,
+    Path(context): Path<String>,
+    Query(filter): Query<EventFilter>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let (tx, rx) = tokio::sync::mpsc::channel(32);
+    let (error_tx, error_rx) = tokio::sync::oneshot::channel();
+
+    tokio::spawn(async move {
+        if let Err(e) = state.k8s_service.watch_events(&context, filter, tx).await {
+            let _ = error_tx.send(format!("Failed to watch events: {}", e));
+        }
+    });
+
+    let events = futures::stream::unfold(rx, |mut rx| async move {
+        let event = rx.recv().await?;
+        Some((Event::default().json_data(event), rx))
+    });
+    // Sent once the watch has given up, after any events it produced
+    let error = futures::stream::once(error_rx).filter_map(|message| async move {
+        let message = message.ok()?;
+        Some(
+            Event::default()
+                .event("error")
+                .json_data(json!({ "error": message })),
+        )
+    });
+    Sse::new(events.chain(error)).keep_alive(KeepAlive::default())
+}
+
+/// Serves a graph as JSON for the UI, or as an export document for other tools
+fn graph_response(graph: &GraphData, format: &GraphFormat) -> Response {
+    match format {
+        GraphFormat::Json => Json(json!(graph)).into_response(),
+        _ => {
+            let (content_type, body) = render_graph(graph, format);
+            ([(header::CONTENT_TYPE, content_type)], body).into_response()
+        }
     }
 }
 
@@ -80,3 +577,160 @@ pub async fn get_pod_logs(
         Err(e) => Json(json!({ "error": format!("Failed to get pod logs: {}", e) })),
     }
 }
+
+pub async fn diagnose_pod(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<GetResourceQuery>,
+) -> Json<Value> {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    match state
+        .k8s_service
+        .diagnose_pod(&context, &name, &namespace)
+        .await
+    {
+        Ok(diagnosis) => Json(json!(diagnosis)),
+        Err(e) => Json(json!({ "error": format!("Failed to diagnose pod: {}", e) })),
+    }
+}
+
+pub async fn diagnose_service(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<GetResourceQuery>,
+) -> Json<Value> {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    match state
+        .k8s_service
+        .diagnose_service(&context, &name, &namespace)
+        .await
+    {
+        Ok(diagnosis) => Json(json!(diagnosis)),
+        Err(e) => Json(json!({ "error": format!("Failed to diagnose service: {}", e) })),
+    }
+}
+
+pub async fn trigger_cronjob(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<GetResourceQuery>,
+) -> Json<Value> {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    match state
+        .k8s_service
+        .trigger_cronjob(&context, &name, &namespace)
+        .await
+    {
+        Ok(job) => Json(job),
+        Err(e) => Json(json!({ "error": format!("Failed to trigger CronJob: {}", e) })),
+    }
+}
+
+pub async fn rerun_job(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<GetResourceQuery>,
+) -> Json<Value> {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    match state
+        .k8s_service
+        .rerun_job(&context, &name, &namespace)
+        .await
+    {
+        Ok(job) => Json(job),
+        Err(e) => Json(json!({ "error": format!("Failed to re-run Job: {}", e) })),
+    }
+}
+
+pub async fn suspend_cronjob(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<GetResourceQuery>,
+) -> Json<Value> {
+    set_cronjob_suspended(state, context, name, query, true).await
+}
+
+pub async fn resume_cronjob(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(query): Query<GetResourceQuery>,
+) -> Json<Value> {
+    set_cronjob_suspended(state, context, name, query, false).await
+}
+
+async fn set_cronjob_suspended(
+    state: Arc<AppState>,
+    context: String,
+    name: String,
+    query: GetResourceQuery,
+    suspend: bool,
+) -> Json<Value> {
+    let namespace = query.namespace.unwrap_or_else(|| "default".to_string());
+    match state
+        .k8s_service
+        .set_cronjob_suspended(&context, &name, &namespace, suspend)
+        .await
+    {
+        Ok(cronjob) => Json(cronjob),
+        Err(e) => Json(json!({ "error": format!("Failed to update CronJob: {}", e) })),
+    }
+}
+
+pub async fn cordon_node(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+) -> Json<Value> {
+    set_node_unschedulable(state, context, name, true).await
+}
+
+pub async fn uncordon_node(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+) -> Json<Value> {
+    set_node_unschedulable(state, context, name, false).await
+}
+
+async fn set_node_unschedulable(
+    state: Arc<AppState>,
+    context: String,
+    name: String,
+    unschedulable: bool,
+) -> Json<Value> {
+    match state
+        .k8s_service
+        .set_node_unschedulable(&context, &name, unschedulable)
+        .await
+    {
+        Ok(node) => Json(node),
+        Err(e) => Json(json!({ "error": format!("Failed to update node: {}", e) })),
+    }
+}
+
+/// Streams drain progress as server-sent events. The drain keeps going if the client disconnects.
+pub async fn drain_node(
+    State(state): State<Arc<AppState>>,
+    Path((context, name)): Path<(String, String)>,
+    Query(options): Query<DrainOptions>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let (tx, rx) = tokio::sync::mpsc::channel(32);
+
+    tokio::spawn(async move {
+        if let Err(e) = state
+            .k8s_service
+            .drain_node(&context, &name, options, tx.clone())
+            .await
+        {
+            let _ = tx
+                .send(DrainEvent::Error {
+                    message: format!("Failed to drain node: {}", e),
+                })
+                .await;
+        }
+    });
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        let event = rx.recv().await?;
+        Some((Event::default().json_data(event), rx))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -1,6 +1,10 @@
+pub mod audit;
+pub mod exec;
+pub mod files;
 pub mod health;
 pub mod k8s;
 pub mod port_forward;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
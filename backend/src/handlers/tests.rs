@@ -1,14 +1,30 @@
 #[cfg(test)]
 mod tests {
-    use super::super::k8s::{list_contexts, list_resources};
+    use super::super::exec::{decode_client_message, ClientFrame, RESIZE_CHANNEL, STDIN_CHANNEL};
+    use super::super::files::split_remote_path;
+    use super::super::k8s::{
+        get_cluster_overview, get_namespace_graph, get_resource, list_contexts,
+        list_pod_usage_history, list_resources, patch_resource, replace_resource, GetResourceQuery,
+        HistoryQuery, ListResourcesQuery, NamespaceGraphQuery, OverviewQuery, PatchResourceQuery,
+        ReplaceResourceQuery,
+    };
+    use crate::managers::audit::AuditLog;
+    use crate::managers::metrics_history::{MetricsHistory, SamplerConfig};
+    use crate::managers::overview::OverviewCache;
     use crate::managers::port_forward::PortForwardManager;
-    use crate::models::K8sResourceType;
+    use crate::models::{
+        ClusterEvent, ClusterOverview, GraphData, InvolvedObject, K8sResourceType, PatchType,
+        PodUsage, ReplaceOutcome, ResourceUsage, UsageMeasure, UsageReport,
+    };
     use crate::services::k8s::MockK8sService;
     use crate::AppState;
     use axum::{
-        extract::{Path, State},
+        extract::{ws::Message, Path, Query, State},
+        http::StatusCode,
+        response::IntoResponse,
         Json,
     };
+    use http_body_util::BodyExt;
     use std::sync::Arc;
 
     #[tokio::test]
@@ -22,6 +38,9 @@ mod tests {
         let state = State(Arc::new(AppState {
             k8s_service: Arc::new(mock_service),
             port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
         }));
         let Json(response) = list_contexts(state).await;
 
@@ -40,6 +59,9 @@ mod tests {
         let state = State(Arc::new(AppState {
             k8s_service: Arc::new(mock_service),
             port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
         }));
         let Json(response) = list_contexts(state).await;
 
@@ -64,14 +86,569 @@ mod tests {
                     }
                 })])
             });
+        mock_service
+            .expect_list_pod_usage()
+            .times(1)
+            .returning(|_, _| {
+                Ok(UsageReport {
+                    metrics_available: true,
+                    message: None,
+                    items: vec![PodUsage {
+                        name: "test-pod".to_string(),
+                        namespace: "default".to_string(),
+                        node: None,
+                        timestamp: None,
+                        window: None,
+                        usage: ResourceUsage {
+                            cpu: UsageMeasure {
+                                used: 120,
+                                request: Some(250),
+                                percent_of_request: Some(48.0),
+                                ..Default::default()
+                            },
+                            memory: UsageMeasure::default(),
+                        },
+                        containers: vec![],
+                    }],
+                })
+            });
 
         let state = State(Arc::new(AppState {
             k8s_service: Arc::new(mock_service),
             port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
         }));
         let path = Path(("minikube".to_string(), K8sResourceType::Pod));
-        let Json(response) = list_resources(state, path).await;
+        let query: Query<ListResourcesQuery> =
+            Query::try_from_uri(&"/?usage=true".parse().unwrap()).unwrap();
+        let Json(response) = list_resources(state, path, query).await;
 
         assert_eq!(response[0]["metadata"]["name"], "test-pod");
+        assert_eq!(response[0]["usage"]["cpu"]["used"], 120);
+        assert_eq!(response[0]["usage"]["cpu"]["percent_of_request"], 48.0);
+    }
+
+    #[tokio::test]
+    async fn test_patch_resource_passes_options() {
+        let mut mock_service = MockK8sService::new();
+        mock_service
+            .expect_patch_resource()
+            .withf(|context, resource_type, name, namespace, patch| {
+                context == "minikube"
+                    && *resource_type == K8sResourceType::Deployment
+                    && name == "web"
+                    && namespace.as_deref() == Some("default")
+                    && patch.patch_type == PatchType::Json
+                    && patch.dry_run
+                    && patch.resource_version.as_deref() == Some("42")
+            })
+            .times(1)
+            .returning(|_, _, _, _, _| {
+                Ok(serde_json::json!({ "metadata": { "name": "web", "resourceVersion": "43" } }))
+            });
+
+        let state = State(Arc::new(AppState {
+            k8s_service: Arc::new(mock_service),
+            port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
+        }));
+        let path = Path((
+            "minikube".to_string(),
+            K8sResourceType::Deployment,
+            "web".to_string(),
+        ));
+        let query: Query<PatchResourceQuery> = Query::try_from_uri(
+            &"/?namespace=default&patch_type=json&dry_run=true&resource_version=42"
+                .parse()
+                .unwrap(),
+        )
+        .unwrap();
+        let body = Json(serde_json::json!([
+            { "op": "replace", "path": "/spec/replicas", "value": 3 }
+        ]));
+        let Json(response) = patch_resource(state, path, query, body).await;
+
+        assert_eq!(response["metadata"]["resourceVersion"], "43");
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_attaches_events_by_uid() {
+        let mut mock_service = MockK8sService::new();
+        mock_service
+            .expect_get_resource()
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(serde_json::json!({
+                    "metadata": { "name": "web", "namespace": "shop", "uid": "pod-uid" }
+                }))
+            });
+        mock_service
+            .expect_list_events()
+            .withf(|context, filter| {
+                context == "minikube"
+                    && filter.namespace.as_deref() == Some("shop")
+                    && filter.involved_uid.as_deref() == Some("pod-uid")
+                    && filter.event_type.is_none()
+            })
+            .times(1)
+            .returning(|_, _| {
+                Ok(vec![ClusterEvent {
+                    name: "web.1".to_string(),
+                    namespace: "shop".to_string(),
+                    involved_object: InvolvedObject {
+                        kind: "Pod".to_string(),
+                        name: "web".to_string(),
+                        namespace: Some("shop".to_string()),
+                        uid: Some("pod-uid".to_string()),
+                        field_path: None,
+                    },
+                    event_type: "Warning".to_string(),
+                    reason: "BackOff".to_string(),
+                    message: "Back-off restarting failed container".to_string(),
+                    count: 3,
+                    source: Some("kubelet".to_string()),
+                    first_seen: None,
+                    last_seen: None,
+                }])
+            });
+
+        let state = State(Arc::new(AppState {
+            k8s_service: Arc::new(mock_service),
+            port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
+        }));
+        let path = Path((
+            "minikube".to_string(),
+            K8sResourceType::Pod,
+            "web".to_string(),
+        ));
+        let query: Query<GetResourceQuery> =
+            Query::try_from_uri(&"/?namespace=shop&events=true".parse().unwrap()).unwrap();
+        let Json(response) = get_resource(state, path, query).await;
+
+        assert_eq!(response["metadata"]["name"], "web");
+        assert_eq!(response["events"][0]["reason"], "BackOff");
+        assert_eq!(response["events"][0]["type"], "Warning");
+    }
+
+    #[tokio::test]
+    async fn test_namespace_graph_parses_excluded_kinds() {
+        let mut mock_service = MockK8sService::new();
+        mock_service
+            .expect_get_namespace_graph()
+            .withf(|context, namespace, options| {
+                context == "minikube"
+                    && namespace == "shop"
+                    && options.exclude == vec![K8sResourceType::ReplicaSet, K8sResourceType::Secret]
+                    && !options.collapse_unrelated
+            })
+            .times(1)
+            .returning(|_, _, _| {
+                Ok(GraphData {
+                    nodes: vec![],
+                    edges: vec![],
+                    stats: None,
+                })
+            });
+
+        let state = Arc::new(AppState {
+            k8s_service: Arc::new(mock_service),
+            port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
+        });
+        let path = || Path(("minikube".to_string(), "shop".to_string()));
+
+        let query: Query<NamespaceGraphQuery> = Query::try_from_uri(
+            &"/?exclude=ReplicaSet,%20Secret&collapse=false"
+                .parse()
+                .unwrap(),
+        )
+        .unwrap();
+        let response = get_namespace_graph(State(state.clone()), path(), query).await;
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["nodes"], serde_json::json!([]));
+
+        let query: Query<NamespaceGraphQuery> =
+            Query::try_from_uri(&"/?exclude=Widget".parse().unwrap()).unwrap();
+        let response = get_namespace_graph(State(state), path(), query).await;
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["error"], "Unknown resource kind: Widget");
+    }
+
+    #[tokio::test]
+    async fn test_replace_resource_conflict_returns_409() {
+        let mut mock_service = MockK8sService::new();
+        mock_service
+            .expect_replace_resource()
+            .times(1)
+            .returning(|_, _, _, _, _, _| {
+                Ok(ReplaceOutcome::Conflict {
+                    current: serde_json::json!({ "metadata": { "resourceVersion": "12" } }),
+                })
+            });
+
+        let state = State(Arc::new(AppState {
+            k8s_service: Arc::new(mock_service),
+            port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
+        }));
+        let path = Path((
+            "minikube".to_string(),
+            K8sResourceType::ConfigMap,
+            "settings".to_string(),
+        ));
+        let query: Query<ReplaceResourceQuery> =
+            Query::try_from_uri(&"/?namespace=default".parse().unwrap()).unwrap();
+        let body = Json(serde_json::json!({ "metadata": { "resourceVersion": "11" } }));
+        let response = replace_resource(state, path, query, body)
+            .await
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["outcome"], "conflict");
+        assert_eq!(body["current"]["metadata"]["resourceVersion"], "12");
+    }
+
+    #[tokio::test]
+    async fn test_cluster_overview_is_cached_until_refresh() {
+        let mut mock_service = MockK8sService::new();
+        mock_service
+            .expect_get_cluster_overview()
+            .times(2)
+            .returning(|_| {
+                Ok(ClusterOverview {
+                    server_version: "v1.31.0".to_string(),
+                    nodes: Default::default(),
+                    namespaces: vec!["default".to_string()],
+                    pods_by_phase: Default::default(),
+                    failing_workloads: vec![],
+                    warning_count: 0,
+                    recent_warnings: vec![],
+                    resources: Default::default(),
+                    generated_at: chrono::Utc::now(),
+                    unavailable: Default::default(),
+                })
+            });
+
+        let state = Arc::new(AppState {
+            k8s_service: Arc::new(mock_service),
+            port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: MetricsHistory::new(SamplerConfig::default()),
+        });
+        let request = |uri: &str| {
+            let query: Query<OverviewQuery> = Query::try_from_uri(&uri.parse().unwrap()).unwrap();
+            get_cluster_overview(State(state.clone()), Path("minikube".to_string()), query)
+        };
+
+        let Json(first) = request("/").await;
+        assert_eq!(first["server_version"], "v1.31.0");
+        let Json(cached) = request("/").await;
+        assert_eq!(cached["generated_at"], first["generated_at"]);
+        let Json(refreshed) = request("/?refresh=true").await;
+        assert_eq!(refreshed["namespaces"][0], "default");
+    }
+
+    #[tokio::test]
+    async fn test_cluster_overview_builds_once_for_concurrent_requests() {
+        let cache = OverviewCache::new();
+        let builds = std::sync::atomic::AtomicUsize::new(0);
+        let build = || async {
+            builds.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            Ok::<_, String>(ClusterOverview {
+                server_version: "v1.31.0".to_string(),
+                nodes: Default::default(),
+                namespaces: vec![],
+                pods_by_phase: Default::default(),
+                failing_workloads: vec![],
+                warning_count: 0,
+                recent_warnings: vec![],
+                resources: Default::default(),
+                generated_at: chrono::Utc::now(),
+                unavailable: Default::default(),
+            })
+        };
+
+        let (first, second, refreshed) = tokio::join!(
+            cache.get_or_build("minikube", false, build),
+            cache.get_or_build("minikube", false, build),
+            cache.get_or_build("minikube", true, build),
+        );
+        assert_eq!(builds.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let generated_at = first.unwrap().generated_at;
+        assert_eq!(second.unwrap().generated_at, generated_at);
+        assert_eq!(refreshed.unwrap().generated_at, generated_at);
+
+        cache.get_or_build("minikube", true, build).await.unwrap();
+        assert_eq!(builds.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_usage_history_keeps_a_bounded_ring_and_folds_steps() {
+        let pod = |cpu: u64, at: chrono::DateTime<chrono::Utc>| PodUsage {
+            name: "web".to_string(),
+            namespace: "shop".to_string(),
+            node: None,
+            timestamp: Some(at),
+            window: None,
+            usage: ResourceUsage {
+                cpu: UsageMeasure {
+                    used: cpu,
+                    ..Default::default()
+                },
+                memory: UsageMeasure {
+                    used: 64,
+                    limit: Some(128),
+                    ..Default::default()
+                },
+            },
+            containers: vec![],
+        };
+        let now = chrono::Utc::now();
+        // The start of the previous minute, so the samples below share step buckets
+        let base = chrono::DateTime::from_timestamp(now.timestamp() / 60 * 60 - 60, 0).unwrap();
+        let at = |seconds: i64| base + chrono::Duration::seconds(seconds);
+
+        let mut mock_service = MockK8sService::new();
+        let sampled = pod(50, at(60));
+        mock_service
+            .expect_list_pod_usage()
+            .times(1)
+            .returning(move |_, _| {
+                Ok(UsageReport {
+                    metrics_available: true,
+                    message: None,
+                    items: vec![sampled.clone()],
+                })
+            });
+        mock_service
+            .expect_list_node_usage()
+            .times(1)
+            .returning(|_| {
+                Ok(UsageReport {
+                    metrics_available: false,
+                    message: Some("metrics-server is not installed".to_string()),
+                    items: vec![],
+                })
+            });
+
+        // Room for three samples
+        let history = MetricsHistory::new(SamplerConfig {
+            interval: std::time::Duration::from_secs(60),
+            retention: std::time::Duration::from_secs(120),
+            ..Default::default()
+        });
+        history.record_pods("minikube", &[pod(100, at(0))], now);
+        history.record_pods("minikube", &[pod(300, at(10))], now);
+        history.record_pods("minikube", &[pod(999, at(10))], now);
+        history.record_pods("minikube", &[pod(200, at(20))], now);
+        let mock_service: Arc<dyn crate::services::k8s::K8sService> = Arc::new(mock_service);
+        assert!(history.sample(mock_service.as_ref(), "minikube").await);
+        history.watch("minikube");
+
+        let state = Arc::new(AppState {
+            k8s_service: mock_service,
+            port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: history,
+        });
+        let request = |uri: &str| {
+            let query: Query<HistoryQuery> = Query::try_from_uri(&uri.parse().unwrap()).unwrap();
+            list_pod_usage_history(State(state.clone()), Path("minikube".to_string()), query)
+        };
+
+        let Json(all) = request("/?namespace=shop").await;
+        let cpu: Vec<_> = all[0]["samples"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["cpu_millis"].as_u64().unwrap())
+            .collect();
+        assert_eq!(cpu, vec![300, 200, 50]);
+        assert_eq!(all[0]["samples"][0]["memory_limit_bytes"], 128);
+
+        let Json(stepped) = request("/?name=web&step=60").await;
+        let samples = stepped[0]["samples"].as_array().unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0]["cpu_millis"], 300);
+
+        let since = at(15).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let Json(recent) = request(&format!("/?name=web&since={}", since)).await;
+        assert_eq!(recent[0]["samples"].as_array().unwrap().len(), 2);
+
+        let Json(other) = request("/?name=api").await;
+        assert_eq!(other, serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_sampler_starts_with_current_or_configured_contexts() {
+        let sampler = |current: Option<&'static str>, contexts: Vec<String>| {
+            let mut mock_service = MockK8sService::new();
+            if let Some(current) = current {
+                mock_service
+                    .expect_get_current_context()
+                    .times(1)
+                    .returning(move || Ok(Some(current.to_string())));
+            }
+            mock_service
+                .expect_list_pod_usage()
+                .returning(|context, _| {
+                    Ok(UsageReport {
+                        metrics_available: true,
+                        message: None,
+                        items: vec![PodUsage {
+                            name: format!("{}-web", context),
+                            namespace: "shop".to_string(),
+                            node: None,
+                            timestamp: Some(chrono::Utc::now()),
+                            window: None,
+                            usage: ResourceUsage::default(),
+                            containers: vec![],
+                        }],
+                    })
+                });
+            mock_service.expect_list_node_usage().returning(|_| {
+                Ok(UsageReport {
+                    metrics_available: false,
+                    message: None,
+                    items: vec![],
+                })
+            });
+            let history = MetricsHistory::new(SamplerConfig {
+                interval: std::time::Duration::from_millis(10),
+                contexts,
+                ..Default::default()
+            });
+            history.spawn_sampler(Arc::new(mock_service));
+            history
+        };
+        let sampled = |history: &MetricsHistory, context: &str| {
+            history
+                .query(
+                    context,
+                    K8sResourceType::Pod,
+                    None,
+                    None,
+                    &Default::default(),
+                )
+                .into_iter()
+                .map(|h| h.name)
+                .collect::<Vec<_>>()
+        };
+
+        let current = sampler(Some("minikube"), vec![]);
+        let configured = sampler(None, vec!["prod".to_string(), "staging".to_string()]);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        assert_eq!(sampled(&current, "minikube"), vec!["minikube-web"]);
+        assert_eq!(sampled(&configured, "prod"), vec!["prod-web"]);
+        assert_eq!(sampled(&configured, "staging"), vec!["staging-web"]);
+        assert!(sampled(&configured, "minikube").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sampler_only_keeps_contexts_that_can_be_read() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut mock_service = MockK8sService::new();
+        let counted = calls.clone();
+        mock_service
+            .expect_list_pod_usage()
+            .returning(move |context, _| {
+                counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Err(format!("context {} not found", context).into())
+            });
+        mock_service
+            .expect_list_node_usage()
+            .returning(|context| Err(format!("context {} not found", context).into()));
+        let mock_service: Arc<dyn crate::services::k8s::K8sService> = Arc::new(mock_service);
+
+        let history = MetricsHistory::new(SamplerConfig {
+            interval: std::time::Duration::from_millis(5),
+            contexts: vec!["gone".to_string()],
+            ..Default::default()
+        });
+        let state = Arc::new(AppState {
+            k8s_service: mock_service.clone(),
+            port_forward_manager: PortForwardManager::new(),
+            audit_log: AuditLog::new(),
+            overview_cache: OverviewCache::new(),
+            metrics_history: history.clone(),
+        });
+
+        // A failed read doesn't register the context, so each history request tries again
+        for _ in 0..2 {
+            let query: Query<HistoryQuery> = Query::try_from_uri(&"/".parse().unwrap()).unwrap();
+            let Json(body) =
+                list_pod_usage_history(State(state.clone()), Path("typo".to_string()), query).await;
+            assert_eq!(body, serde_json::json!([]));
+        }
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert!(!history.touch("typo"));
+
+        // A configured context that keeps failing is dropped after a few ticks
+        history.spawn_sampler(mock_service);
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!history.touch("gone"));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2 + 5);
+    }
+
+    #[test]
+    fn test_decode_exec_client_frames() {
+        let stdin = decode_client_message(Message::Binary(vec![STDIN_CHANNEL, b'l', b's'].into()));
+        assert!(matches!(stdin, ClientFrame::Stdin(data) if data == b"ls"));
+
+        let mut resize = vec![RESIZE_CHANNEL];
+        resize.extend_from_slice(br#"{"Width":120,"Height":40}"#);
+        match decode_client_message(Message::Binary(resize.into())) {
+            ClientFrame::Resize(size) => {
+                assert_eq!(size.width, 120);
+                assert_eq!(size.height, 40);
+            }
+            other => panic!("expected resize, got {:?}", other),
+        }
+
+        let text = decode_client_message(Message::Text("pwd\n".into()));
+        assert!(matches!(text, ClientFrame::Stdin(data) if data == b"pwd\n"));
+
+        let unknown = decode_client_message(Message::Binary(vec![9, 1, 2].into()));
+        assert!(matches!(unknown, ClientFrame::Ignore));
+    }
+
+    #[test]
+    fn test_split_remote_path() {
+        assert_eq!(
+            split_remote_path("/tmp/heap.hprof"),
+            ("/tmp".to_string(), "heap.hprof".to_string())
+        );
+        assert_eq!(
+            split_remote_path("/var/log/app/"),
+            ("/var/log".to_string(), "app".to_string())
+        );
+        assert_eq!(
+            split_remote_path("/data"),
+            ("/".to_string(), "data".to_string())
+        );
+        assert_eq!(
+            split_remote_path("config.yaml"),
+            (".".to_string(), "config.yaml".to_string())
+        );
+        assert_eq!(split_remote_path("/"), ("/".to_string(), ".".to_string()));
     }
 }
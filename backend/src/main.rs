@@ -2,6 +2,9 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use crate::services::k8s::{K8sClient, K8sService};
+use crate::managers::audit::AuditLog;
+use crate::managers::metrics_history::{MetricsHistory, SamplerConfig};
+use crate::managers::overview::OverviewCache;
 use crate::managers::port_forward::PortForwardManager;
 
 mod handlers;
@@ -13,6 +16,9 @@ mod managers;
 pub struct AppState {
     pub k8s_service: Arc<dyn K8sService>,
     pub port_forward_manager: PortForwardManager,
+    pub audit_log: AuditLog,
+    pub overview_cache: OverviewCache,
+    pub metrics_history: MetricsHistory,
 }
 
 #[tokio::main]
@@ -29,10 +35,17 @@ async fn main() {
     // Initialize services
     let k8s_service = Arc::new(K8sClient::new());
     let port_forward_manager = PortForwardManager::new();
+    let audit_log = AuditLog::new();
+    let overview_cache = OverviewCache::new();
+    let metrics_history = MetricsHistory::new(SamplerConfig::from_env());
+    metrics_history.spawn_sampler(k8s_service.clone());
 
     let state = Arc::new(AppState {
         k8s_service,
         port_forward_manager,
+        audit_log,
+        overview_cache,
+        metrics_history,
     });
 
     // Build our application with a route
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Oldest entries are dropped once the in-memory log reaches this size
+const MAX_AUDIT_ENTRIES: usize = 1000;
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    SessionStart,
+    SessionEnd { reason: String },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditEntry {
+    pub id: String,
+    pub session_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub context: String,
+    pub namespace: String,
+    pub pod: String,
+    pub container: Option<String>,
+    pub command: Vec<String>,
+    pub event: AuditEvent,
+}
+
+#[derive(Clone)]
+pub struct AuditLog {
+    entries: Arc<Mutex<VecDeque<AuditEntry>>>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn record(&self, entry: AuditEntry) {
+        tracing::info!(
+            target: "audit",
+            session = %entry.session_id,
+            context = %entry.context,
+            namespace = %entry.namespace,
+            pod = %entry.pod,
+            container = ?entry.container,
+            command = ?entry.command,
+            event = ?entry.event,
+            "interactive session event"
+        );
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_AUDIT_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    pub fn list_entries(&self) -> Vec<AuditEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().cloned().collect()
+    }
+}
+
+/// Identifies one interactive session; start and end entries share the session id
+#[derive(Clone, Debug)]
+pub struct AuditSession {
+    pub session_id: String,
+    pub context: String,
+    pub namespace: String,
+    pub pod: String,
+    pub container: Option<String>,
+    pub command: Vec<String>,
+}
+
+impl AuditSession {
+    pub fn new(
+        context: &str,
+        namespace: &str,
+        pod: &str,
+        container: Option<String>,
+        command: Vec<String>,
+    ) -> Self {
+        Self {
+            session_id: Uuid::new_v4().to_string(),
+            context: context.to_string(),
+            namespace: namespace.to_string(),
+            pod: pod.to_string(),
+            container,
+            command,
+        }
+    }
+
+    pub fn entry(&self, event: AuditEvent) -> AuditEntry {
+        AuditEntry {
+            id: Uuid::new_v4().to_string(),
+            session_id: self.session_id.clone(),
+            timestamp: Utc::now(),
+            context: self.context.clone(),
+            namespace: self.namespace.clone(),
+            pod: self.pod.clone(),
+            container: self.container.clone(),
+            command: self.command.clone(),
+            event,
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use crate::models::{
+    HistoryRange, K8sResourceType, NodeUsage, PodUsage, UsageHistory, UsageSample,
+};
+use crate::services::k8s::K8sService;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_RETENTION: Duration = Duration::from_secs(60 * 60);
+/// Failed samples in a row after which a context is dropped until someone asks again
+const MAX_FAILED_SAMPLES: u32 = 5;
+
+/// How often metrics-server is sampled, how long samples are kept and where sampling starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamplerConfig {
+    /// Zero turns the sampler off
+    pub interval: Duration,
+    pub retention: Duration,
+    /// Contexts sampled from startup; the kubeconfig's current context when empty
+    pub contexts: Vec<String>,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            retention: DEFAULT_RETENTION,
+            contexts: Vec::new(),
+        }
+    }
+}
+
+impl SamplerConfig {
+    /// Reads `METRICS_SAMPLE_INTERVAL_SECS`, `METRICS_RETENTION_MINUTES` and the
+    /// comma-separated `METRICS_SAMPLE_CONTEXTS`, falling back to the defaults for unset or
+    /// unparsable values
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        let defaults = Self::default();
+        Self {
+            interval: var("METRICS_SAMPLE_INTERVAL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.interval),
+            retention: var("METRICS_RETENTION_MINUTES")
+                .map(|minutes| Duration::from_secs(minutes * 60))
+                .unwrap_or(defaults.retention),
+            contexts: std::env::var("METRICS_SAMPLE_CONTEXTS")
+                .map(|contexts| {
+                    contexts
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or(defaults.contexts),
+        }
+    }
+
+    /// Samples one series can hold before the oldest is overwritten
+    fn capacity(&self) -> usize {
+        (self.retention.as_secs() / self.interval.as_secs().max(1)) as usize + 1
+    }
+}
+
+/// A context being sampled
+struct Watched {
+    /// Configured at startup; kept however long nobody looks at it
+    pinned: bool,
+    last_queried: Instant,
+    failures: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SeriesKey {
+    context: String,
+    kind: K8sResourceType,
+    namespace: Option<String>,
+    name: String,
+}
+
+/// Short-term usage history for Pods and Nodes, one ring buffer per object. The configured
+/// contexts are sampled from startup and any other once its usage has been read, until
+/// nobody has asked about it for the retention period. Contexts that keep failing are
+/// dropped, so unreachable clusters in the kubeconfig cost nothing.
+#[derive(Clone)]
+pub struct MetricsHistory {
+    series: Arc<Mutex<HashMap<SeriesKey, VecDeque<UsageSample>>>>,
+    contexts: Arc<Mutex<BTreeMap<String, Watched>>>,
+    config: SamplerConfig,
+}
+
+impl MetricsHistory {
+    pub fn new(config: SamplerConfig) -> Self {
+        Self {
+            series: Arc::new(Mutex::new(HashMap::new())),
+            contexts: Arc::new(Mutex::new(BTreeMap::new())),
+            config,
+        }
+    }
+
+    /// Adds a context to those sampled on every tick, or marks it as asked about again.
+    /// Call it once usage of the context has been read, so typos and unreachable
+    /// clusters are never sampled.
+    pub fn watch(&self, context: &str) {
+        self.add(context, false);
+    }
+
+    fn add(&self, context: &str, pinned: bool) {
+        let mut contexts = self.contexts.lock().unwrap();
+        match contexts.get_mut(context) {
+            Some(watched) => {
+                watched.last_queried = Instant::now();
+                watched.failures = 0;
+                watched.pinned |= pinned;
+            }
+            None => {
+                tracing::info!("Sampling usage of context {}", context);
+                contexts.insert(
+                    context.to_string(),
+                    Watched {
+                        pinned,
+                        last_queried: Instant::now(),
+                        failures: 0,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Marks a sampled context as asked about; false if it isn't being sampled
+    pub fn touch(&self, context: &str) -> bool {
+        let mut contexts = self.contexts.lock().unwrap();
+        match contexts.get_mut(context) {
+            Some(watched) => {
+                watched.last_queried = Instant::now();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Counts one sample of a context, dropping it once it fails too often in a row
+    fn sampled(&self, context: &str, ok: bool) {
+        let mut contexts = self.contexts.lock().unwrap();
+        let Some(watched) = contexts.get_mut(context) else {
+            return;
+        };
+        watched.failures = if ok { 0 } else { watched.failures + 1 };
+        if watched.failures >= MAX_FAILED_SAMPLES {
+            tracing::warn!(
+                "Stopped sampling usage of context {} after {} failed samples",
+                context,
+                watched.failures
+            );
+            contexts.remove(context);
+        }
+    }
+
+    /// Contexts to sample on this tick, after dropping those nobody has asked about within
+    /// the retention period
+    fn due(&self) -> Vec<String> {
+        let mut contexts = self.contexts.lock().unwrap();
+        contexts.retain(|context, watched| {
+            let keep = watched.pinned || watched.last_queried.elapsed() < self.config.retention;
+            if !keep {
+                tracing::info!("Stopped sampling usage of idle context {}", context);
+            }
+            keep
+        });
+        contexts.keys().cloned().collect()
+    }
+
+    pub fn record_pods(&self, context: &str, pods: &[PodUsage], now: DateTime<Utc>) {
+        for pod in pods {
+            let key = SeriesKey {
+                context: context.to_string(),
+                kind: K8sResourceType::Pod,
+                namespace: Some(pod.namespace.clone()),
+                name: pod.name.clone(),
+            };
+            let sample = UsageSample {
+                timestamp: pod.timestamp.unwrap_or(now),
+                cpu_millis: pod.usage.cpu.used,
+                memory_bytes: pod.usage.memory.used,
+                cpu_limit_millis: pod.usage.cpu.limit,
+                memory_limit_bytes: pod.usage.memory.limit,
+            };
+            self.record(key, sample);
+        }
+        self.prune(now);
+    }
+
+    pub fn record_nodes(&self, context: &str, nodes: &[NodeUsage], now: DateTime<Utc>) {
+        for node in nodes {
+            let key = SeriesKey {
+                context: context.to_string(),
+                kind: K8sResourceType::Node,
+                namespace: None,
+                name: node.name.clone(),
+            };
+            let sample = UsageSample {
+                timestamp: node.timestamp.unwrap_or(now),
+                cpu_millis: node.usage.cpu.used,
+                memory_bytes: node.usage.memory.used,
+                cpu_limit_millis: node.usage.cpu.allocatable,
+                memory_limit_bytes: node.usage.memory.allocatable,
+            };
+            self.record(key, sample);
+        }
+        self.prune(now);
+    }
+
+    fn record(&self, key: SeriesKey, sample: UsageSample) {
+        let mut series = self.series.lock().unwrap();
+        let samples = series.entry(key).or_default();
+        // metrics-server refreshes less often than we may sample; keep each reading once
+        if samples
+            .back()
+            .is_some_and(|last| last.timestamp >= sample.timestamp)
+        {
+            return;
+        }
+        if samples.len() >= self.config.capacity() {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    /// Drops samples past the retention limit, and with them objects that have gone away
+    fn prune(&self, now: DateTime<Utc>) {
+        let retention = chrono::Duration::from_std(self.config.retention).unwrap_or_default();
+        let cutoff = now - retention;
+        let mut series = self.series.lock().unwrap();
+        series.retain(|_, samples| {
+            while samples.front().is_some_and(|s| s.timestamp < cutoff) {
+                samples.pop_front();
+            }
+            !samples.is_empty()
+        });
+    }
+
+    /// Histories of one kind in a context, optionally narrowed to a namespace or a name
+    pub fn query(
+        &self,
+        context: &str,
+        kind: K8sResourceType,
+        namespace: Option<&str>,
+        name: Option<&str>,
+        range: &HistoryRange,
+    ) -> Vec<UsageHistory> {
+        let series = self.series.lock().unwrap();
+        let mut histories: Vec<UsageHistory> = series
+            .iter()
+            .filter(|(key, _)| {
+                key.context == context
+                    && key.kind == kind
+                    && namespace.is_none_or(|ns| key.namespace.as_deref() == Some(ns))
+                    && name.is_none_or(|name| key.name == name)
+            })
+            .map(|(key, samples)| UsageHistory {
+                kind: format!("{:?}", key.kind),
+                name: key.name.clone(),
+                namespace: key.namespace.clone(),
+                samples: in_range(samples, range),
+            })
+            .collect();
+        histories.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+        histories
+    }
+
+    /// Takes one sample of a context's pods and nodes. False when neither could be read,
+    /// which includes a cluster without metrics-server.
+    pub async fn sample(&self, k8s_service: &dyn K8sService, context: &str) -> bool {
+        let (pods, nodes) = tokio::join!(
+            k8s_service.list_pod_usage(context, None),
+            k8s_service.list_node_usage(context)
+        );
+        let now = Utc::now();
+        let mut ok = false;
+        match pods {
+            Ok(report) => {
+                ok |= report.metrics_available;
+                self.record_pods(context, &report.items, now);
+            }
+            Err(e) => tracing::debug!("Failed to sample pod usage in {}: {}", context, e),
+        }
+        match nodes {
+            Ok(report) => {
+                ok |= report.metrics_available;
+                self.record_nodes(context, &report.items, now);
+            }
+            Err(e) => tracing::debug!("Failed to sample node usage in {}: {}", context, e),
+        }
+        ok
+    }
+
+    /// Watches the configured contexts, or the kubeconfig's current context if none are set,
+    /// so history is recorded before anyone opens a usage page
+    pub async fn watch_startup_contexts(&self, k8s_service: &dyn K8sService) {
+        if !self.config.contexts.is_empty() {
+            for context in &self.config.contexts {
+                self.add(context, true);
+            }
+            return;
+        }
+        match k8s_service.get_current_context().await {
+            Ok(Some(context)) => self.add(&context, true),
+            Ok(None) => tracing::info!("No current context to sample usage of"),
+            Err(e) => tracing::warn!("Failed to read the current context: {}", e),
+        }
+    }
+
+    /// Samples every watched context on the configured interval until the process exits
+    pub fn spawn_sampler(&self, k8s_service: Arc<dyn K8sService>) {
+        if self.config.interval.is_zero() {
+            tracing::info!("Usage sampling is turned off");
+            return;
+        }
+        let history = self.clone();
+        tokio::spawn(async move {
+            history.watch_startup_contexts(k8s_service.as_ref()).await;
+            let mut ticker = tokio::time::interval(history.config.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                let contexts = history.due();
+                let results = futures::future::join_all(
+                    contexts
+                        .iter()
+                        .map(|context| history.sample(k8s_service.as_ref(), context)),
+                )
+                .await;
+                for (context, ok) in contexts.iter().zip(results) {
+                    history.sampled(context, ok);
+                }
+            }
+        });
+    }
+}
+
+/// The samples inside the range, folded into `step_seconds` buckets when asked
+fn in_range(samples: &VecDeque<UsageSample>, range: &HistoryRange) -> Vec<UsageSample> {
+    let selected = samples.iter().filter(|s| {
+        range.since.is_none_or(|since| s.timestamp >= since)
+            && range.until.is_none_or(|until| s.timestamp <= until)
+    });
+    let Some(step) = range.step_seconds.filter(|step| *step > 0) else {
+        return selected.cloned().collect();
+    };
+
+    let mut buckets: Vec<(i64, UsageSample)> = Vec::new();
+    for sample in selected {
+        let bucket = sample.timestamp.timestamp().div_euclid(step as i64);
+        match buckets.last_mut() {
+            Some((current, peak)) if *current == bucket => {
+                peak.cpu_millis = peak.cpu_millis.max(sample.cpu_millis);
+                peak.memory_bytes = peak.memory_bytes.max(sample.memory_bytes);
+                peak.cpu_limit_millis = sample.cpu_limit_millis;
+                peak.memory_limit_bytes = sample.memory_limit_bytes;
+                peak.timestamp = sample.timestamp;
+            }
+            _ => buckets.push((bucket, sample.clone())),
+        }
+    }
+    buckets.into_iter().map(|(_, sample)| sample).collect()
+}
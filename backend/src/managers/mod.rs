@@ -1 +1,4 @@
+pub mod audit;
+pub mod metrics_history;
 pub mod port_forward;
+pub mod overview;
@@ -0,0 +1,86 @@
+use crate::models::ClusterOverview;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long an overview is served before the cluster is asked again
+const OVERVIEW_TTL: Duration = Duration::from_secs(30);
+
+/// The last overview built for each context, so landing page reloads and several open
+/// browser tabs do not each list the whole cluster
+#[derive(Clone)]
+pub struct OverviewCache {
+    entries: Arc<Mutex<HashMap<String, (Instant, ClusterOverview)>>>,
+    /// Held while an overview is built, so requests arriving meanwhile wait for it
+    builds: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    ttl: Duration,
+}
+
+impl OverviewCache {
+    pub fn new() -> Self {
+        Self::with_ttl(OVERVIEW_TTL)
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            builds: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// The cached overview for a context, if it is younger than the TTL
+    pub fn get(&self, context: &str) -> Option<ClusterOverview> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(context)
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, overview)| overview.clone())
+    }
+
+    pub fn insert(&self, context: &str, overview: ClusterOverview) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(context.to_string(), (Instant::now(), overview));
+    }
+
+    /// The cached overview, or a new one from `build`. Only one build runs per context at
+    /// a time; requests arriving during it get its result. `refresh` skips the cache but
+    /// still shares a build that started after the request came in.
+    pub async fn get_or_build<F, Fut, E>(
+        &self,
+        context: &str,
+        refresh: bool,
+        build: F,
+    ) -> Result<ClusterOverview, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<ClusterOverview, E>>,
+    {
+        if !refresh {
+            if let Some(overview) = self.get(context) {
+                return Ok(overview);
+            }
+        }
+        let requested = Instant::now();
+        let lock = {
+            let mut builds = self.builds.lock().unwrap();
+            builds.entry(context.to_string()).or_default().clone()
+        };
+        let _building = lock.lock().await;
+
+        let built_meanwhile = {
+            let entries = self.entries.lock().unwrap();
+            entries
+                .get(context)
+                .filter(|(stored, _)| *stored >= requested)
+                .map(|(_, overview)| overview.clone())
+        };
+        if let Some(overview) = built_meanwhile {
+            return Ok(overview);
+        }
+        let overview = build().await?;
+        self.insert(context, overview.clone());
+        Ok(overview)
+    }
+}
@@ -1,7 +1,8 @@
+use chrono::{DateTime, Utc};
 use kube::api::{ApiResource, GroupVersionKind};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum K8sResourceType {
     // Workload and Compute Objects
     Pod,
@@ -11,10 +12,12 @@ pub enum K8sResourceType {
     DaemonSet,
     Job,
     CronJob,
+    Node,
 
     // Service & Networking Objects
     Service,
     Ingress,
+    IngressClass,
 
     // Storage Objects
     PersistentVolume,
@@ -42,8 +45,10 @@ impl K8sResourceType {
             "DaemonSet" => Some(Self::DaemonSet),
             "Job" => Some(Self::Job),
             "CronJob" => Some(Self::CronJob),
+            "Node" => Some(Self::Node),
             "Service" => Some(Self::Service),
             "Ingress" => Some(Self::Ingress),
+            "IngressClass" => Some(Self::IngressClass),
             "PersistentVolume" => Some(Self::PersistentVolume),
             "PersistentVolumeClaim" => Some(Self::PersistentVolumeClaim),
             "StorageClass" => Some(Self::StorageClass),
@@ -59,6 +64,20 @@ impl K8sResourceType {
         }
     }
 
+    /// Whether objects of this kind live inside a namespace
+    pub fn is_namespaced(&self) -> bool {
+        !matches!(
+            self,
+            Self::Node
+                | Self::IngressClass
+                | Self::PersistentVolume
+                | Self::StorageClass
+                | Self::Namespace
+                | Self::ClusterRole
+                | Self::ClusterRoleBinding
+        )
+    }
+
     pub fn get_api_resource(&self) -> ApiResource {
         let gvk = match self {
             // Workload and Compute Objects
@@ -69,10 +88,12 @@ impl K8sResourceType {
             Self::DaemonSet => GroupVersionKind::gvk("apps", "v1", "DaemonSet"),
             Self::Job => GroupVersionKind::gvk("batch", "v1", "Job"),
             Self::CronJob => GroupVersionKind::gvk("batch", "v1", "CronJob"),
+            Self::Node => GroupVersionKind::gvk("", "v1", "Node"),
 
             // Service & Networking Objects
             Self::Service => GroupVersionKind::gvk("", "v1", "Service"),
             Self::Ingress => GroupVersionKind::gvk("networking.k8s.io", "v1", "Ingress"),
+            Self::IngressClass => GroupVersionKind::gvk("networking.k8s.io", "v1", "IngressClass"),
 
             // Storage Objects
             Self::PersistentVolume => GroupVersionKind::gvk("", "v1", "PersistentVolume"),
@@ -98,6 +119,7 @@ impl K8sResourceType {
 
         let plural = match self {
             Self::Ingress => "ingresses",
+            Self::IngressClass => "ingressclasses",
             Self::CronJob => "cronjobs", // kube-rs might handle this but let's be safe
             _ => "",                     // Let kube-rs infer or we can be explicit.
                                           // Actually ApiResource::from_gvk doesn't take plural.
@@ -123,6 +145,12 @@ pub struct GraphNode {
     pub label: String,
     pub resource_type: String,
     pub data: serde_json::Value,
+    /// Normalized status for kinds where health has a clear meaning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<NodeHealth>,
+    /// Live cpu and memory for Pods and Nodes, when metrics-server is installed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ResourceUsage>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,10 +159,666 @@ pub struct GraphEdge {
     pub source: String,
     pub target: String,
     pub label: String,
+    /// Set when the reference is broken, e.g. the target object does not exist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    /// Still converging, e.g. a pending Pod or a rollout in progress
+    Progressing,
+    /// Working, but not fully, e.g. some replicas unavailable
+    Degraded,
+    Error,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeHealth {
+    pub status: HealthStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl NodeHealth {
+    pub fn new(status: HealthStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: Some(message.into()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphData {
     pub nodes: Vec<GraphNode>,
     pub edges: Vec<GraphEdge>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<GraphStats>,
+}
+
+/// How building one graph went, to spot slow or chatty requests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphStats {
+    pub duration_ms: u64,
+    /// Requests sent to the API server
+    pub api_calls: usize,
+    /// Lookups answered by a list or object already fetched for this graph
+    pub cache_hits: usize,
+}
+
+/// Controls how far the resource graph is expanded from the requested object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphOptions {
+    /// Number of ownerReference hops followed both up to owners and down to owned objects
+    pub depth: usize,
+    pub detail: GraphDetail,
+    /// Fill in live usage on Pod and Node nodes from metrics-server
+    pub usage: bool,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        Self {
+            depth: 1,
+            detail: GraphDetail::default(),
+            usage: false,
+        }
+    }
+}
+
+/// How much of each object a graph node carries in `data`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphDetail {
+    /// The whole object as returned by the API server
+    #[default]
+    Full,
+    /// Only kind, name, namespace, uid, status phase and readiness; the object itself can
+    /// be fetched through the resource endpoint when needed
+    Summary,
+}
+
+/// Output format of the graph endpoints; everything but `json` drops the embedded objects
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Json,
+    /// Graphviz DOT
+    Dot,
+    Mermaid,
+    Graphml,
+    /// Cytoscape.js elements JSON
+    Cytoscape,
+}
+
+/// Controls which objects a namespace topology graph shows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceGraphOptions {
+    /// Kinds left out of the topology entirely
+    pub exclude: Vec<K8sResourceType>,
+    /// Fold objects with no edges into one grouping node per kind
+    pub collapse_unrelated: bool,
+    pub detail: GraphDetail,
+    /// Fill in live usage on Pod and Node nodes from metrics-server
+    pub usage: bool,
+}
+
+impl Default for NamespaceGraphOptions {
+    fn default() -> Self {
+        Self {
+            exclude: Vec::new(),
+            collapse_unrelated: true,
+            detail: GraphDetail::default(),
+            usage: false,
+        }
+    }
+}
+
+/// An object that nothing appears to use any more, a candidate for cleanup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnusedResource {
+    pub kind: String,
+    pub name: String,
+    /// Unset for cluster-scoped kinds such as PersistentVolumes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub reason: String,
+    /// Seconds since creation, or since completion for Jobs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_seconds: Option<i64>,
+    /// Payload bytes for ConfigMaps and Secrets, capacity for volumes and claims
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+}
+
+/// Options for the unused resource scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedResourceOptions {
+    /// Completed Jobs are reported once they finished this many days ago
+    pub completed_job_days: u32,
+}
+
+impl Default for UnusedResourceOptions {
+    fn default() -> Self {
+        Self {
+            completed_job_days: 7,
+        }
+    }
+}
+
+/// A reference from one object to another that does not resolve
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DanglingReference {
+    /// Kind of the object holding the reference
+    pub kind: String,
+    pub name: String,
+    /// Field holding the reference, e.g. `spec.volumes[0].configMap.name`
+    pub field_path: String,
+    pub target_kind: String,
+    /// Unset when the reference is a selector rather than a name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_name: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchType {
+    /// RFC 6902 JSON Patch (list of operations)
+    Json,
+    /// RFC 7386 JSON Merge Patch
+    Merge,
+    /// Kubernetes strategic merge patch (kubectl's default)
+    #[default]
+    Strategic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourcePatch {
+    pub patch_type: PatchType,
+    pub body: serde_json::Value,
+    pub dry_run: bool,
+    /// When set, the patch only applies if the live object still has this resourceVersion
+    pub resource_version: Option<String>,
+}
+
+/// Result of replacing a resource with a full edited object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ReplaceOutcome {
+    /// Dry-run result: live and would-be objects (without status/managedFields) plus the JSON patch between them
+    Preview {
+        before: serde_json::Value,
+        after: serde_json::Value,
+        diff: serde_json::Value,
+    },
+    Applied {
+        resource: serde_json::Value,
+    },
+    /// The object changed since it was loaded; carries the current live object
+    Conflict {
+        current: serde_json::Value,
+    },
+}
+
+/// Ephemeral container to inject into a running pod for debugging
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugContainerSpec {
+    pub name: String,
+    pub image: String,
+    /// Container whose process namespace the debugger joins
+    pub target_container: Option<String>,
+    /// Overrides the image entrypoint when not empty
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrainOptions {
+    /// Give up on pods that still can't be evicted after this long
+    #[serde(default = "DrainOptions::default_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Overrides each pod's terminationGracePeriodSeconds
+    pub grace_period_seconds: Option<u32>,
+    /// Evict pods that no controller will recreate
+    #[serde(default)]
+    pub force: bool,
+    /// Evict pods using emptyDir volumes, losing their data
+    #[serde(default)]
+    pub delete_emptydir_data: bool,
+}
+
+impl DrainOptions {
+    fn default_timeout_seconds() -> u64 {
+        300
+    }
+}
+
+impl Default for DrainOptions {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: Self::default_timeout_seconds(),
+            grace_period_seconds: None,
+            force: false,
+            delete_emptydir_data: false,
+        }
+    }
+}
+
+/// Progress reported while draining a node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DrainEvent {
+    Cordoned {
+        node: String,
+    },
+    Skipped {
+        namespace: String,
+        name: String,
+        reason: String,
+    },
+    /// Eviction accepted; the pod may still be shutting down
+    Evicted {
+        namespace: String,
+        name: String,
+    },
+    /// An evicted pod is gone from the node
+    Deleted {
+        namespace: String,
+        name: String,
+    },
+    /// Eviction refused, e.g. by a PodDisruptionBudget; the drain keeps retrying
+    Blocked {
+        namespace: String,
+        name: String,
+        reason: String,
+    },
+    Failed {
+        namespace: String,
+        name: String,
+        reason: String,
+    },
+    Completed {
+        evicted: usize,
+        /// `namespace/name` of pods still on the node when the drain gave up, including
+        /// evicted pods that had not finished terminating
+        blocking: Vec<String>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventType {
+    Normal,
+    Warning,
+}
+
+/// Which Events to list or stream. Every field narrows the result; none set means all.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Unset lists Events from every namespace
+    pub namespace: Option<String>,
+    pub involved_kind: Option<String>,
+    pub involved_name: Option<String>,
+    pub involved_uid: Option<String>,
+    #[serde(rename = "type")]
+    pub event_type: Option<EventType>,
+    /// Only Events last seen at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only Events last seen at or before this time
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// The object an Event is about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvolvedObject {
+    pub kind: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<String>,
+    /// Part of the object the Event is about, e.g. `spec.containers{web}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_path: Option<String>,
+}
+
+/// A Kubernetes Event, with the timestamps the different reporters fill in folded together
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterEvent {
+    pub name: String,
+    pub namespace: String,
+    pub involved_object: InvolvedObject,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub reason: String,
+    pub message: String,
+    pub count: i32,
+    /// Component that reported the Event, e.g. `kubelet` or `default-scheduler`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// A known way for a pod to fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PodProblem {
+    CrashLoopBackOff,
+    ImagePullBackOff,
+    Unschedulable,
+    OomKilled,
+    ProbeFailure,
+    ContainerConfigError,
+    MissingReference,
+    NodeCondition,
+}
+
+/// One problem found with a pod and what to do about it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosisFinding {
+    pub problem: PodProblem,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Last lines the crashed container logged before it exited
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_tail: Vec<String>,
+    pub remedy: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PodDiagnosis {
+    pub name: String,
+    pub namespace: String,
+    pub phase: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    pub health: NodeHealth,
+    /// Empty when nothing looks wrong
+    pub findings: Vec<DiagnosisFinding>,
+}
+
+/// A known reason for a Service to have no, or too few, endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceProblem {
+    NoSelector,
+    NoMatchingPods,
+    PodNotReady,
+    TargetPortNotFound,
+    PortNotDeclared,
+    NoReadyEndpoints,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceFinding {
+    pub problem: ServiceProblem,
+    pub message: String,
+    pub remedy: String,
+}
+
+/// A pod picked by the Service's selector
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectedPod {
+    pub name: String,
+    pub ready: bool,
+    pub phase: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    /// Why the pod isn't ready
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// One Service port and the container port it resolves to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServicePortCheck {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub port: i32,
+    pub protocol: String,
+    /// A container port name or number
+    pub target_port: String,
+    /// The number a named target port resolves to on the first pod that declares it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_port: Option<i32>,
+    /// Selected pods with a container declaring the target port
+    pub declared_by: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointSummary {
+    pub addresses: Vec<String>,
+    pub ready: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointSliceSummary {
+    pub name: String,
+    pub address_type: String,
+    /// Ports as `name:port/protocol`
+    pub ports: Vec<String>,
+    pub endpoints: Vec<EndpointSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceDiagnosis {
+    pub name: String,
+    pub namespace: String,
+    pub service_type: String,
+    /// `key=value` pairs; unset when the Service has no selector
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    pub pods: Vec<SelectedPod>,
+    pub ports: Vec<ServicePortCheck>,
+    pub endpoint_slices: Vec<EndpointSliceSummary>,
+    /// Empty when the Service's endpoints look right
+    pub findings: Vec<ServiceFinding>,
+}
+
+/// A column of a resource table, as in a server-side Table's `columnDefinitions`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableColumn {
+    pub name: String,
+    /// `string`, `integer`, `number`, `boolean` or `date`
+    #[serde(rename = "type")]
+    pub column_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Columns above 0 are only shown in wide output
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableRow {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// One cell per column
+    pub cells: Vec<serde_json::Value>,
+}
+
+/// A list of objects rendered as `kubectl get` would print them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceTable {
+    pub columns: Vec<TableColumn>,
+    pub rows: Vec<TableRow>,
+    /// Whether the API server rendered the table, rather than the backend
+    pub server_side: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableOptions {
+    /// Column to sort rows by, matched case-insensitively; rows keep the server's order if unset
+    pub sort_by: Option<String>,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeCounts {
+    pub total: usize,
+    pub ready: usize,
+    /// Cordoned nodes
+    pub unschedulable: usize,
+}
+
+/// A controller that isn't running as many pods as it should
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailingWorkload {
+    pub kind: String,
+    pub name: String,
+    pub namespace: String,
+    pub health: NodeHealth,
+}
+
+/// Requests of scheduled, unfinished pods against what the nodes can allocate
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceTotals {
+    pub cpu_requests_millis: u64,
+    pub cpu_allocatable_millis: u64,
+    pub memory_requests_bytes: u64,
+    pub memory_allocatable_bytes: u64,
+}
+
+/// Everything the landing page shows about one context
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterOverview {
+    pub server_version: String,
+    pub nodes: NodeCounts,
+    pub namespaces: Vec<String>,
+    pub pods_by_phase: std::collections::BTreeMap<String, usize>,
+    pub failing_workloads: Vec<FailingWorkload>,
+    /// Warning Events last seen in the past hour
+    pub warning_count: usize,
+    /// The newest of those Events
+    pub recent_warnings: Vec<ClusterEvent>,
+    pub resources: ResourceTotals,
+    pub generated_at: DateTime<Utc>,
+    /// Sections that could not be read, such as `nodes` or `warnings`, with the reason.
+    /// Their fields are left empty.
+    #[serde(default)]
+    pub unavailable: std::collections::BTreeMap<String, String>,
+}
+
+/// Live usage of one resource, cpu in millicores or memory in bytes, against what the
+/// object asked for and what its node offers. Percentages are rounded to one decimal.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageMeasure {
+    pub used: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocatable: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent_of_request: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent_of_limit: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent_of_allocatable: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Millicores
+    pub cpu: UsageMeasure,
+    /// Bytes
+    pub memory: UsageMeasure,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerUsage {
+    pub name: String,
+    pub usage: ResourceUsage,
+}
+
+/// A pod's `kubectl top` line. The pod's request or limit is only set when every
+/// container has one, since one unbounded container leaves the pod unbounded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PodUsage {
+    pub name: String,
+    pub namespace: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// When metrics-server sampled the pod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The period the sample is averaged over, e.g. `15s`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<String>,
+    pub usage: ResourceUsage,
+    pub containers: Vec<ContainerUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeUsage {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<String>,
+    pub usage: ResourceUsage,
+}
+
+/// Usage read from the `metrics.k8s.io` API. Clusters without metrics-server get an
+/// empty report with `metrics_available` unset and the reason in `message`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport<T> {
+    pub metrics_available: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub items: Vec<T>,
+}
+
+/// One point of a usage series, as sampled from metrics-server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageSample {
+    pub timestamp: DateTime<Utc>,
+    pub cpu_millis: u64,
+    pub memory_bytes: u64,
+    /// The limits in force when the sample was taken, allocatable for Nodes, so a chart can
+    /// draw the ceiling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_limit_millis: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_bytes: Option<u64>,
+}
+
+/// Recent usage of one Pod or Node, oldest sample first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageHistory {
+    pub kind: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub samples: Vec<UsageSample>,
+}
+
+/// Which part of the sampled history to return
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryRange {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Folds samples into buckets of this many seconds, keeping each bucket's peak, so a
+    /// sparkline gets a fixed number of points whatever the sampling interval
+    pub step_seconds: Option<u64>,
 }
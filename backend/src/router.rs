@@ -1,4 +1,4 @@
-use crate::handlers::{health, k8s, port_forward};
+use crate::handlers::{audit, exec, files, health, k8s, port_forward};
 use crate::AppState;
 use axum::{
     routing::{delete, get, post},
@@ -10,19 +10,88 @@ pub fn create_router(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/health", get(health::health_check))
         .route("/api/contexts", get(k8s::list_contexts))
+        .route("/api/{context}/overview", get(k8s::get_cluster_overview))
+        .route("/api/{context}/metrics/pods", get(k8s::list_pod_usage))
+        .route("/api/{context}/metrics/nodes", get(k8s::list_node_usage))
+        .route(
+            "/api/{context}/metrics/history/pods",
+            get(k8s::list_pod_usage_history),
+        )
+        .route(
+            "/api/{context}/metrics/history/nodes",
+            get(k8s::list_node_usage_history),
+        )
         .route(
             "/api/{context}/resources/{resource_type}",
             get(k8s::list_resources),
         )
+        .route(
+            "/api/{context}/tables/{resource_type}",
+            get(k8s::list_resource_table),
+        )
+        .route(
+            "/api/{context}/tables/crd/{crd}",
+            get(k8s::list_custom_resource_table),
+        )
         .route(
             "/api/{context}/resources/{resource_type}/{name}",
-            get(k8s::get_resource),
+            get(k8s::get_resource)
+                .patch(k8s::patch_resource)
+                .put(k8s::replace_resource),
         )
         .route(
             "/api/{context}/resources/{resource_type}/{name}/graph",
             get(k8s::get_resource_graph),
         )
+        .route(
+            "/api/{context}/namespaces/{namespace}/graph",
+            get(k8s::get_namespace_graph),
+        )
+        .route("/api/{context}/events", get(k8s::list_events))
+        .route("/api/{context}/events/stream", get(k8s::stream_events))
+        .route(
+            "/api/{context}/namespaces/{namespace}/dangling-references",
+            get(k8s::find_dangling_references),
+        )
+        .route(
+            "/api/{context}/namespaces/{namespace}/unused",
+            get(k8s::find_unused_resources),
+        )
         .route("/api/{context}/pods/{name}/logs", get(k8s::get_pod_logs))
+        .route(
+            "/api/{context}/pods/{name}/diagnose",
+            get(k8s::diagnose_pod),
+        )
+        .route(
+            "/api/{context}/services/{name}/diagnose",
+            get(k8s::diagnose_service),
+        )
+        .route("/api/{context}/pods/{name}/exec", get(exec::exec_pod))
+        .route("/api/{context}/pods/{name}/debug", get(exec::debug_pod))
+        .route(
+            "/api/{context}/pods/{name}/files",
+            get(files::download_files).put(files::upload_files),
+        )
+        .route("/api/audit", get(audit::list_audit_entries))
+        .route(
+            "/api/{context}/cronjobs/{name}/trigger",
+            post(k8s::trigger_cronjob),
+        )
+        .route(
+            "/api/{context}/cronjobs/{name}/suspend",
+            post(k8s::suspend_cronjob),
+        )
+        .route(
+            "/api/{context}/cronjobs/{name}/resume",
+            post(k8s::resume_cronjob),
+        )
+        .route("/api/{context}/jobs/{name}/rerun", post(k8s::rerun_job))
+        .route("/api/{context}/nodes/{name}/cordon", post(k8s::cordon_node))
+        .route(
+            "/api/{context}/nodes/{name}/uncordon",
+            post(k8s::uncordon_node),
+        )
+        .route("/api/{context}/nodes/{name}/drain", post(k8s::drain_node))
         .route(
             "/api/port-forward",
             post(port_forward::start_port_forward).get(port_forward::list_port_forwards),
@@ -0,0 +1,883 @@
+use super::diagnose::endpoint_slice_summary;
+use super::graph::{pod_spec_references, selector_matches, selector_string};
+use super::health::node_health;
+use super::k8s::K8sClient;
+use super::quantity::parse_bytes;
+use crate::models::{
+    DanglingReference, HealthStatus, K8sResourceType, UnusedResource, UnusedResourceOptions,
+};
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::discovery::v1::EndpointSlice;
+use kube::api::{DynamicObject, ListParams};
+use kube::{Api, Client};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+/// Kinds whose objects carry a pod spec, and where it sits in them
+const POD_SPEC_PATHS: [(K8sResourceType, &str); 6] = [
+    (K8sResourceType::Pod, "spec"),
+    (K8sResourceType::Deployment, "spec.template.spec"),
+    (K8sResourceType::StatefulSet, "spec.template.spec"),
+    (K8sResourceType::DaemonSet, "spec.template.spec"),
+    (K8sResourceType::Job, "spec.template.spec"),
+    (
+        K8sResourceType::CronJob,
+        "spec.jobTemplate.spec.template.spec",
+    ),
+];
+
+fn api_for(client: Client, resource_type: &K8sResourceType, namespace: &str) -> Api<DynamicObject> {
+    let api_resource = resource_type.get_api_resource();
+    if resource_type.is_namespaced() {
+        Api::namespaced_with(client, namespace, &api_resource)
+    } else {
+        Api::all_with(client, &api_resource)
+    }
+}
+
+/// Lists full objects of one kind. Unlike the graph, a failed list is an error here:
+/// treating it as empty would report every reference into it as dangling.
+pub(crate) async fn list_objects(
+    client: Client,
+    resource_type: K8sResourceType,
+    namespace: &str,
+) -> Result<Vec<Value>, Box<dyn Error + Send + Sync>> {
+    let list = api_for(client, &resource_type, namespace)
+        .list(&ListParams::default())
+        .await
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+    Ok(list
+        .items
+        .into_iter()
+        .map(|item| serde_json::to_value(item).unwrap_or_default())
+        .collect())
+}
+
+/// Lists every kind in `POD_SPEC_PATHS`, keyed by kind
+async fn list_workloads(
+    client: Client,
+    namespace: &str,
+) -> Result<HashMap<K8sResourceType, Vec<Value>>, Box<dyn Error + Send + Sync>> {
+    let lists = futures::future::try_join_all(
+        POD_SPEC_PATHS
+            .iter()
+            .map(|(kind, _)| list_objects(client.clone(), kind.clone(), namespace)),
+    )
+    .await?;
+    Ok(POD_SPEC_PATHS
+        .iter()
+        .map(|(kind, _)| kind.clone())
+        .zip(lists)
+        .collect())
+}
+
+/// Names of the cluster's PersistentVolumes, or `None` when they can't be listed. Users
+/// confined to one namespace usually may not list cluster-scoped kinds, and that shouldn't
+/// fail a scan of their namespace.
+async fn volume_names(client: Client, namespace: &str) -> Option<HashSet<String>> {
+    match list_names(client, K8sResourceType::PersistentVolume, namespace).await {
+        Ok(names) => Some(names),
+        Err(e) => {
+            tracing::debug!("Skipping checks of claimed volumes: {}", e);
+            None
+        }
+    }
+}
+
+/// Lists only the names of one kind, so Secret payloads never leave the API server
+pub(crate) async fn list_names(
+    client: Client,
+    resource_type: K8sResourceType,
+    namespace: &str,
+) -> Result<HashSet<String>, Box<dyn Error + Send + Sync>> {
+    let list = api_for(client, &resource_type, namespace)
+        .list_metadata(&ListParams::default())
+        .await
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+    Ok(list
+        .items
+        .into_iter()
+        .filter_map(|item| item.metadata.name)
+        .collect())
+}
+
+fn name_of(resource: &Value) -> String {
+    resource
+        .pointer("/metadata/name")
+        .and_then(|n| n.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Objects made by a controller live and die with it
+fn is_controlled(resource: &Value) -> bool {
+    resource
+        .pointer("/metadata/ownerReferences")
+        .and_then(|r| r.as_array())
+        .is_some_and(|refs| {
+            refs.iter()
+                .any(|r| r.get("controller").and_then(|c| c.as_bool()) == Some(true))
+        })
+}
+
+/// Kind and name of the object's controller, if it has one
+fn controller_of(resource: &Value) -> Option<(&str, &str)> {
+    let owner = resource
+        .pointer("/metadata/ownerReferences")
+        .and_then(|r| r.as_array())?
+        .iter()
+        .find(|r| r.get("controller").and_then(|c| c.as_bool()) == Some(true))?;
+    Some((
+        owner.get("kind").and_then(|k| k.as_str())?,
+        owner.get("name").and_then(|n| n.as_str())?,
+    ))
+}
+
+/// Looks up a JSON path written as `spec.jobTemplate.spec`
+fn at_path<'a>(resource: &'a Value, path: &str) -> Option<&'a Value> {
+    resource.pointer(&format!("/{}", path.replace('.', "/")))
+}
+
+/// Names of the objects a scan resolves references against
+struct Existing {
+    config_maps: HashSet<String>,
+    secrets: HashSet<String>,
+    claims: HashSet<String>,
+    service_accounts: HashSet<String>,
+    services: HashSet<String>,
+    /// `None` when PersistentVolumes can't be listed
+    volumes: Option<HashSet<String>>,
+}
+
+impl Existing {
+    fn contains(&self, resource_type: &K8sResourceType, name: &str) -> bool {
+        let names = match resource_type {
+            K8sResourceType::ConfigMap => &self.config_maps,
+            K8sResourceType::Secret => &self.secrets,
+            K8sResourceType::PersistentVolumeClaim => &self.claims,
+            K8sResourceType::ServiceAccount => &self.service_accounts,
+            K8sResourceType::Service => &self.services,
+            // Without the list a volume can't be shown to be missing
+            K8sResourceType::PersistentVolume => match &self.volumes {
+                Some(volumes) => volumes,
+                None => return true,
+            },
+            _ => return true,
+        };
+        names.contains(name)
+    }
+}
+
+fn missing(
+    kind: &K8sResourceType,
+    name: &str,
+    field_path: String,
+    target_kind: K8sResourceType,
+    target_name: &str,
+) -> DanglingReference {
+    DanglingReference {
+        kind: format!("{:?}", kind),
+        name: name.to_string(),
+        field_path,
+        target_kind: format!("{:?}", target_kind),
+        target_name: Some(target_name.to_string()),
+        message: format!("{:?} {} does not exist", target_kind, target_name),
+    }
+}
+
+/// ConfigMaps, Secrets, PVCs and the ServiceAccount named by one pod spec
+fn check_pod_spec(
+    kind: &K8sResourceType,
+    name: &str,
+    prefix: &str,
+    spec: &Value,
+    existing: &Existing,
+) -> Vec<DanglingReference> {
+    let mut findings: Vec<DanglingReference> = pod_spec_references(spec)
+        .into_iter()
+        .filter(|r| !r.optional && !existing.contains(&r.resource_type, &r.name))
+        .map(|r| {
+            let path = format!("{}.{}", prefix, r.path);
+            missing(kind, name, path, r.resource_type, &r.name)
+        })
+        .collect();
+
+    let account = spec
+        .get("serviceAccountName")
+        .and_then(|s| s.as_str())
+        .unwrap_or("default");
+    if !existing.contains(&K8sResourceType::ServiceAccount, account) {
+        let path = format!("{}.serviceAccountName", prefix);
+        findings.push(missing(
+            kind,
+            name,
+            path,
+            K8sResourceType::ServiceAccount,
+            account,
+        ));
+    }
+    findings
+}
+
+/// Backend Services and TLS Secrets of an Ingress
+fn check_ingress(ingress: &Value, existing: &Existing) -> Vec<DanglingReference> {
+    let name = name_of(ingress);
+    let mut refs: Vec<(String, K8sResourceType, &str)> = Vec::new();
+
+    if let Some(service) = ingress
+        .pointer("/spec/defaultBackend/service/name")
+        .and_then(|s| s.as_str())
+    {
+        let path = "spec.defaultBackend.service.name".to_string();
+        refs.push((path, K8sResourceType::Service, service));
+    }
+    let rules = ingress.pointer("/spec/rules").and_then(|r| r.as_array());
+    for (r, rule) in rules.into_iter().flatten().enumerate() {
+        let paths = rule.pointer("/http/paths").and_then(|p| p.as_array());
+        for (p, http_path) in paths.into_iter().flatten().enumerate() {
+            if let Some(service) = http_path
+                .pointer("/backend/service/name")
+                .and_then(|s| s.as_str())
+            {
+                let path = format!("spec.rules[{}].http.paths[{}].backend.service.name", r, p);
+                refs.push((path, K8sResourceType::Service, service));
+            }
+        }
+    }
+    let tls = ingress.pointer("/spec/tls").and_then(|t| t.as_array());
+    for (t, entry) in tls.into_iter().flatten().enumerate() {
+        if let Some(secret) = entry.get("secretName").and_then(|s| s.as_str()) {
+            let path = format!("spec.tls[{}].secretName", t);
+            refs.push((path, K8sResourceType::Secret, secret));
+        }
+    }
+
+    refs.into_iter()
+        .filter(|(_, target_type, target)| !existing.contains(target_type, target))
+        .map(|(path, target_type, target)| {
+            missing(&K8sResourceType::Ingress, &name, path, target_type, target)
+        })
+        .collect()
+}
+
+/// A selector that matches no pod leaves the Service without endpoints
+fn check_service(service: &Value, pods: &[Value]) -> Option<DanglingReference> {
+    let selector = service
+        .pointer("/spec/selector")
+        .and_then(|s| s.as_object())
+        .filter(|s| !s.is_empty())?;
+    let matched = pods.iter().any(|pod| {
+        pod.pointer("/metadata/labels")
+            .and_then(|l| l.as_object())
+            .is_some_and(|labels| selector_matches(selector, labels))
+    });
+    if matched {
+        return None;
+    }
+    let selector = selector_string(selector);
+    Some(DanglingReference {
+        kind: "Service".to_string(),
+        name: name_of(service),
+        field_path: "spec.selector".to_string(),
+        target_kind: "Pod".to_string(),
+        target_name: None,
+        message: format!("selector {} matches no pods", selector),
+    })
+}
+
+/// A claim should be bound to a volume that still exists
+fn check_claim(claim: &Value, existing: &Existing) -> Option<DanglingReference> {
+    let name = name_of(claim);
+    let kind = K8sResourceType::PersistentVolumeClaim;
+    match claim.pointer("/spec/volumeName").and_then(|v| v.as_str()) {
+        Some(volume) if !existing.contains(&K8sResourceType::PersistentVolume, volume) => {
+            Some(missing(
+                &kind,
+                &name,
+                "spec.volumeName".to_string(),
+                K8sResourceType::PersistentVolume,
+                volume,
+            ))
+        }
+        Some(_) => None,
+        None => {
+            let phase = claim
+                .pointer("/status/phase")
+                .and_then(|p| p.as_str())
+                .unwrap_or("Pending");
+            Some(DanglingReference {
+                kind: format!("{:?}", kind),
+                name,
+                field_path: "spec.volumeName".to_string(),
+                target_kind: "PersistentVolume".to_string(),
+                target_name: None,
+                message: format!("claim is {} and not bound to any volume", phase),
+            })
+        }
+    }
+}
+
+/// References in one pod's spec that don't resolve, and claims it mounts that aren't bound
+pub(crate) async fn pod_reference_problems(
+    client: Client,
+    namespace: &str,
+    pod: &Value,
+) -> Result<Vec<DanglingReference>, Box<dyn Error + Send + Sync>> {
+    let Some(spec) = pod.get("spec") else {
+        return Ok(Vec::new());
+    };
+    let (lists, volumes) = tokio::join!(
+        async {
+            tokio::try_join!(
+                list_names(client.clone(), K8sResourceType::ConfigMap, namespace),
+                list_names(client.clone(), K8sResourceType::Secret, namespace),
+                list_names(client.clone(), K8sResourceType::ServiceAccount, namespace),
+                list_objects(
+                    client.clone(),
+                    K8sResourceType::PersistentVolumeClaim,
+                    namespace
+                ),
+            )
+        },
+        volume_names(client.clone(), namespace),
+    );
+    let (config_maps, secrets, service_accounts, claims) = lists?;
+    let existing = Existing {
+        config_maps,
+        secrets,
+        claims: claims.iter().map(name_of).collect(),
+        service_accounts,
+        services: HashSet::new(),
+        volumes,
+    };
+
+    let kind = K8sResourceType::Pod;
+    let mut findings = check_pod_spec(&kind, &name_of(pod), "spec", spec, &existing);
+    let mounted: HashSet<String> = pod_spec_references(spec)
+        .into_iter()
+        .filter(|r| r.resource_type == K8sResourceType::PersistentVolumeClaim)
+        .map(|r| r.name)
+        .collect();
+    findings.extend(
+        claims
+            .iter()
+            .filter(|c| mounted.contains(&name_of(c)))
+            .filter_map(|c| check_claim(c, &existing)),
+    );
+    Ok(findings)
+}
+
+impl K8sClient {
+    /// Helper to scan a namespace for references that don't resolve using a provided client,
+    /// exposed for testing
+    pub(crate) async fn find_dangling_references_with_client(
+        client: Client,
+        namespace: &str,
+    ) -> Result<Vec<DanglingReference>, Box<dyn Error + Send + Sync>> {
+        let (workloads, replica_sets, services, ingresses, claims) = tokio::try_join!(
+            list_workloads(client.clone(), namespace),
+            list_objects(client.clone(), K8sResourceType::ReplicaSet, namespace),
+            list_objects(client.clone(), K8sResourceType::Service, namespace),
+            list_objects(client.clone(), K8sResourceType::Ingress, namespace),
+            list_objects(
+                client.clone(),
+                K8sResourceType::PersistentVolumeClaim,
+                namespace
+            ),
+        )?;
+        let (names, volumes) = tokio::join!(
+            async {
+                tokio::try_join!(
+                    list_names(client.clone(), K8sResourceType::ConfigMap, namespace),
+                    list_names(client.clone(), K8sResourceType::Secret, namespace),
+                    list_names(client.clone(), K8sResourceType::ServiceAccount, namespace),
+                )
+            },
+            volume_names(client.clone(), namespace),
+        );
+        let (config_maps, secrets, service_accounts) = names?;
+        let existing = Existing {
+            config_maps,
+            secrets,
+            claims: claims.iter().map(name_of).collect(),
+            service_accounts,
+            services: services.iter().map(name_of).collect(),
+            volumes,
+        };
+
+        // Objects made by a controller whose template is scanned are covered through it.
+        // Pods of a standalone ReplicaSet or of an operator are checked themselves.
+        let deployment_replica_sets: HashSet<String> = replica_sets
+            .iter()
+            .filter(|rs| controller_of(rs).is_some_and(|(kind, _)| kind == "Deployment"))
+            .map(name_of)
+            .collect();
+        let covered = |object: &Value| match controller_of(object) {
+            Some(("ReplicaSet", name)) => deployment_replica_sets.contains(name),
+            Some((kind, _)) => K8sResourceType::from_kind(kind)
+                .is_some_and(|kind| POD_SPEC_PATHS.iter().any(|(scanned, _)| *scanned == kind)),
+            None => false,
+        };
+
+        let mut findings = Vec::new();
+        for (kind, prefix) in &POD_SPEC_PATHS {
+            for object in &workloads[kind] {
+                let Some(spec) = at_path(object, prefix) else {
+                    continue;
+                };
+                if covered(object) {
+                    continue;
+                }
+                findings.extend(check_pod_spec(
+                    kind,
+                    &name_of(object),
+                    prefix,
+                    spec,
+                    &existing,
+                ));
+            }
+        }
+        let pods = &workloads[&K8sResourceType::Pod];
+        findings.extend(services.iter().filter_map(|s| check_service(s, pods)));
+        findings.extend(ingresses.iter().flat_map(|i| check_ingress(i, &existing)));
+        findings.extend(claims.iter().filter_map(|c| check_claim(c, &existing)));
+        Ok(findings)
+    }
+}
+
+/// ConfigMaps every namespace gets from the control plane
+const SYSTEM_CONFIG_MAPS: [&str; 1] = ["kube-root-ca.crt"];
+
+/// Secret types that are used without any pod naming them
+const IMPLICIT_SECRET_TYPES: [&str; 3] = [
+    "kubernetes.io/service-account-token",
+    "bootstrap.kubernetes.io/token",
+    "helm.sh/release.v1",
+];
+
+/// Ready endpoints of each Service in a namespace, counted over its EndpointSlices
+async fn ready_endpoints(
+    client: Client,
+    namespace: &str,
+) -> Result<HashMap<String, usize>, Box<dyn Error + Send + Sync>> {
+    let slices: Api<EndpointSlice> = Api::namespaced(client, namespace);
+    let slices = slices
+        .list(&ListParams::default())
+        .await
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+    let mut ready = HashMap::new();
+    for slice in slices.items {
+        let slice = serde_json::to_value(slice).unwrap_or_default();
+        let Some(service) = slice
+            .pointer("/metadata/labels/kubernetes.io~1service-name")
+            .and_then(|s| s.as_str())
+        else {
+            continue;
+        };
+        let summary = endpoint_slice_summary(&slice);
+        *ready.entry(service.to_string()).or_insert(0) +=
+            summary.endpoints.iter().filter(|e| e.ready).count();
+    }
+    Ok(ready)
+}
+
+/// Revisions a Deployment keeps when it doesn't set `revisionHistoryLimit`
+const DEFAULT_REVISION_HISTORY: usize = 10;
+
+fn seconds_since(timestamp: Option<&str>, now: DateTime<Utc>) -> Option<i64> {
+    let time = DateTime::parse_from_rfc3339(timestamp?).ok()?;
+    Some((now - time.with_timezone(&Utc)).num_seconds())
+}
+
+fn age_of(resource: &Value, now: DateTime<Utc>) -> Option<i64> {
+    seconds_since(
+        resource
+            .pointer("/metadata/creationTimestamp")
+            .and_then(|t| t.as_str()),
+        now,
+    )
+}
+
+/// Length of a base64 payload once decoded
+fn decoded_len(encoded: &str) -> u64 {
+    let padding = encoded.bytes().rev().take_while(|b| *b == b'=').count();
+    (encoded.len() / 4 * 3).saturating_sub(padding) as u64
+}
+
+/// Bytes held under `data` and `binaryData`; Secret data and binaryData are base64 encoded
+fn payload_size(resource: &Value, data_is_encoded: bool) -> u64 {
+    let values = |key: &str| {
+        resource
+            .get(key)
+            .and_then(|d| d.as_object())
+            .into_iter()
+            .flat_map(|d| d.values().filter_map(|v| v.as_str()))
+    };
+    let data: u64 = values("data")
+        .map(|v| {
+            if data_is_encoded {
+                decoded_len(v)
+            } else {
+                v.len() as u64
+            }
+        })
+        .sum();
+    data + values("binaryData").map(decoded_len).sum::<u64>()
+}
+
+fn storage_at(resource: &Value, path: &str) -> Option<u64> {
+    at_path(resource, path)
+        .and_then(|q| q.as_str())
+        .and_then(parse_bytes)
+}
+
+fn unused(
+    kind: K8sResourceType,
+    resource: &Value,
+    reason: String,
+    age_seconds: Option<i64>,
+    size_bytes: Option<u64>,
+) -> UnusedResource {
+    UnusedResource {
+        kind: format!("{:?}", kind),
+        name: name_of(resource),
+        namespace: resource
+            .pointer("/metadata/namespace")
+            .and_then(|n| n.as_str())
+            .map(String::from),
+        reason,
+        age_seconds,
+        size_bytes,
+    }
+}
+
+/// Names of the ConfigMaps and Secrets that something in the namespace still uses
+#[derive(Default)]
+struct ConfigUsers {
+    config_maps: HashSet<String>,
+    secrets: HashSet<String>,
+}
+
+impl ConfigUsers {
+    fn add(&mut self, resource_type: &K8sResourceType, name: Option<&str>) {
+        let names = match resource_type {
+            K8sResourceType::ConfigMap => &mut self.config_maps,
+            K8sResourceType::Secret => &mut self.secrets,
+            _ => return,
+        };
+        if let Some(name) = name {
+            names.insert(name.to_string());
+        }
+    }
+
+    fn add_names(&mut self, resource_type: &K8sResourceType, list: Option<&Value>) {
+        for item in list.and_then(|l| l.as_array()).into_iter().flatten() {
+            self.add(resource_type, item.get("name").and_then(|n| n.as_str()));
+        }
+    }
+
+    /// Optional references still count: the pod reads the object whenever it exists
+    fn add_pod_spec(&mut self, spec: &Value) {
+        for reference in pod_spec_references(spec) {
+            self.add(&reference.resource_type, Some(&reference.name));
+        }
+        self.add_names(&K8sResourceType::Secret, spec.get("imagePullSecrets"));
+        let volumes = spec.get("volumes").and_then(|v| v.as_array());
+        for volume in volumes.into_iter().flatten() {
+            let sources = volume
+                .pointer("/projected/sources")
+                .and_then(|s| s.as_array());
+            for source in sources.into_iter().flatten() {
+                let config_map = source.pointer("/configMap/name");
+                self.add(
+                    &K8sResourceType::ConfigMap,
+                    config_map.and_then(|n| n.as_str()),
+                );
+                let secret = source.pointer("/secret/name");
+                self.add(&K8sResourceType::Secret, secret.and_then(|n| n.as_str()));
+            }
+        }
+    }
+}
+
+fn revision_of(replica_set: &Value) -> i64 {
+    replica_set
+        .pointer("/metadata/annotations/deployment.kubernetes.io~1revision")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(0)
+}
+
+fn controller_uid(resource: &Value) -> Option<&str> {
+    resource
+        .pointer("/metadata/ownerReferences")
+        .and_then(|r| r.as_array())?
+        .iter()
+        .find(|r| r.get("controller").and_then(|c| c.as_bool()) == Some(true))?
+        .get("uid")
+        .and_then(|u| u.as_str())
+}
+
+/// Scaled-down ReplicaSets past their Deployment's revision history, or without a Deployment
+fn stale_replica_sets(
+    replica_sets: &[Value],
+    deployments: &[Value],
+    now: DateTime<Utc>,
+) -> Vec<UnusedResource> {
+    let scaled_down: Vec<&Value> = replica_sets
+        .iter()
+        .filter(|rs| rs.pointer("/spec/replicas").and_then(|r| r.as_i64()) == Some(0))
+        .collect();
+    let mut findings = Vec::new();
+
+    for replica_set in &scaled_down {
+        let owned = controller_uid(replica_set).is_some_and(|uid| {
+            deployments
+                .iter()
+                .any(|d| d.pointer("/metadata/uid").and_then(|u| u.as_str()) == Some(uid))
+        });
+        if !owned {
+            findings.push(unused(
+                K8sResourceType::ReplicaSet,
+                replica_set,
+                "Scaled to zero and not owned by any Deployment".to_string(),
+                age_of(replica_set, now),
+                None,
+            ));
+        }
+    }
+
+    for deployment in deployments {
+        let uid = deployment.pointer("/metadata/uid").and_then(|u| u.as_str());
+        let current = revision_of(deployment);
+        let limit = deployment
+            .pointer("/spec/revisionHistoryLimit")
+            .and_then(|l| l.as_u64())
+            .map_or(DEFAULT_REVISION_HISTORY, |l| l as usize);
+        let mut history: Vec<&Value> = scaled_down
+            .iter()
+            .copied()
+            .filter(|rs| uid.is_some() && controller_uid(rs) == uid)
+            .filter(|rs| revision_of(rs) != current)
+            .collect();
+        history.sort_by_key(|rs| std::cmp::Reverse(revision_of(rs)));
+        for replica_set in history.into_iter().skip(limit) {
+            let reason = format!(
+                "Revision {} of Deployment {} is beyond its history limit of {}",
+                revision_of(replica_set),
+                name_of(deployment),
+                limit
+            );
+            findings.push(unused(
+                K8sResourceType::ReplicaSet,
+                replica_set,
+                reason,
+                age_of(replica_set, now),
+                None,
+            ));
+        }
+    }
+    findings
+}
+
+impl K8sClient {
+    /// Helper to list objects in a namespace that nothing uses any more using a provided
+    /// client, exposed for testing. ConfigMaps and Secrets are listed in full to size them.
+    pub(crate) async fn find_unused_resources_with_client(
+        client: Client,
+        namespace: &str,
+        options: UnusedResourceOptions,
+    ) -> Result<Vec<UnusedResource>, Box<dyn Error + Send + Sync>> {
+        let list = |resource_type| list_objects(client.clone(), resource_type, namespace);
+        let (workloads, ingresses, service_accounts, config_maps, secrets) = tokio::try_join!(
+            list_workloads(client.clone(), namespace),
+            list(K8sResourceType::Ingress),
+            list(K8sResourceType::ServiceAccount),
+            list(K8sResourceType::ConfigMap),
+            list(K8sResourceType::Secret),
+        )?;
+        let (lists, volumes, endpoints) = tokio::join!(
+            async {
+                tokio::try_join!(
+                    list(K8sResourceType::PersistentVolumeClaim),
+                    list(K8sResourceType::ReplicaSet),
+                    list(K8sResourceType::Service),
+                )
+            },
+            list(K8sResourceType::PersistentVolume),
+            ready_endpoints(client.clone(), namespace),
+        );
+        let (claims, replica_sets, services) = lists?;
+        // Users confined to the namespace usually may not list volumes or slices; skip the
+        // checks that need them rather than the whole scan
+        let volumes = volumes.unwrap_or_else(|e| {
+            tracing::debug!("Skipping released volumes: {}", e);
+            Vec::new()
+        });
+        let endpoints = endpoints
+            .map_err(|e| tracing::debug!("Judging Services by their pods alone: {}", e))
+            .ok();
+        let now = Utc::now();
+
+        let mut users = ConfigUsers::default();
+        for (kind, prefix) in &POD_SPEC_PATHS {
+            for spec in workloads[kind].iter().filter_map(|o| at_path(o, prefix)) {
+                users.add_pod_spec(spec);
+            }
+        }
+        for ingress in &ingresses {
+            let tls = ingress.pointer("/spec/tls").and_then(|t| t.as_array());
+            for entry in tls.into_iter().flatten() {
+                let secret = entry.get("secretName").and_then(|s| s.as_str());
+                users.add(&K8sResourceType::Secret, secret);
+            }
+        }
+        for account in &service_accounts {
+            users.add_names(&K8sResourceType::Secret, account.get("secrets"));
+            users.add_names(&K8sResourceType::Secret, account.get("imagePullSecrets"));
+        }
+
+        let mut findings = Vec::new();
+        for config_map in &config_maps {
+            let name = name_of(config_map);
+            if SYSTEM_CONFIG_MAPS.contains(&name.as_str())
+                || is_controlled(config_map)
+                || users.config_maps.contains(&name)
+            {
+                continue;
+            }
+            findings.push(unused(
+                K8sResourceType::ConfigMap,
+                config_map,
+                "Not mounted or referenced by any pod".to_string(),
+                age_of(config_map, now),
+                Some(payload_size(config_map, false)),
+            ));
+        }
+        for secret in &secrets {
+            let secret_type = secret.get("type").and_then(|t| t.as_str()).unwrap_or("");
+            if IMPLICIT_SECRET_TYPES.contains(&secret_type)
+                || is_controlled(secret)
+                || users.secrets.contains(&name_of(secret))
+            {
+                continue;
+            }
+            findings.push(unused(
+                K8sResourceType::Secret,
+                secret,
+                "Not mounted or referenced by any pod, Ingress or ServiceAccount".to_string(),
+                age_of(secret, now),
+                Some(payload_size(secret, true)),
+            ));
+        }
+
+        for volume in &volumes {
+            let phase = volume.pointer("/status/phase").and_then(|p| p.as_str());
+            let claim_namespace = volume
+                .pointer("/spec/claimRef/namespace")
+                .and_then(|n| n.as_str());
+            if phase != Some("Released") || claim_namespace != Some(namespace) {
+                continue;
+            }
+            let claim = volume
+                .pointer("/spec/claimRef/name")
+                .and_then(|n| n.as_str())
+                .unwrap_or_default();
+            let policy = volume
+                .pointer("/spec/persistentVolumeReclaimPolicy")
+                .and_then(|p| p.as_str())
+                .unwrap_or("Retain");
+            findings.push(unused(
+                K8sResourceType::PersistentVolume,
+                volume,
+                format!("Released by claim {}, reclaim policy {}", claim, policy),
+                age_of(volume, now),
+                storage_at(volume, "spec.capacity.storage"),
+            ));
+        }
+        for claim in &claims {
+            let phase = claim
+                .pointer("/status/phase")
+                .and_then(|p| p.as_str())
+                .unwrap_or("Pending");
+            if phase == "Bound" {
+                continue;
+            }
+            findings.push(unused(
+                K8sResourceType::PersistentVolumeClaim,
+                claim,
+                format!("Claim is {} and not bound to any volume", phase),
+                age_of(claim, now),
+                storage_at(claim, "spec.resources.requests.storage"),
+            ));
+        }
+
+        findings.extend(stale_replica_sets(
+            &replica_sets,
+            &workloads[&K8sResourceType::Deployment],
+            now,
+        ));
+
+        // completionTime is only set once a Job succeeds. Jobs run by a CronJob are
+        // pruned by its history limits.
+        let max_job_age = i64::from(options.completed_job_days) * 86_400;
+        for job in workloads[&K8sResourceType::Job]
+            .iter()
+            .filter(|j| !is_controlled(j))
+        {
+            let finished = seconds_since(
+                job.pointer("/status/completionTime")
+                    .and_then(|t| t.as_str()),
+                now,
+            );
+            if let Some(finished) = finished.filter(|f| *f >= max_job_age) {
+                findings.push(unused(
+                    K8sResourceType::Job,
+                    job,
+                    format!("Completed {} days ago", finished / 86_400),
+                    Some(finished),
+                    None,
+                ));
+            }
+        }
+
+        // A Service is unused when nothing can be routed to it. Its EndpointSlices say so
+        // for Services without a selector too, whose endpoints are managed by hand.
+        let pods = &workloads[&K8sResourceType::Pod];
+        for service in &services {
+            if service.pointer("/spec/type").and_then(|t| t.as_str()) == Some("ExternalName") {
+                continue;
+            }
+            let unhealthy = node_health(&K8sResourceType::Service, service, pods)
+                .filter(|h| h.status == HealthStatus::Error)
+                .map(|h| h.message.unwrap_or_default());
+            let reason = match &endpoints {
+                Some(ready) if ready.get(&name_of(service)).is_some_and(|n| *n > 0) => continue,
+                Some(_) => unhealthy.unwrap_or_else(|| {
+                    let selector = service
+                        .pointer("/spec/selector")
+                        .and_then(|s| s.as_object())
+                        .is_some_and(|s| !s.is_empty());
+                    if selector {
+                        "No ready endpoints in its EndpointSlices".to_string()
+                    } else {
+                        "No selector and no ready endpoints in its EndpointSlices".to_string()
+                    }
+                }),
+                None => match unhealthy {
+                    Some(message) => message,
+                    None => continue,
+                },
+            };
+            findings.push(unused(
+                K8sResourceType::Service,
+                service,
+                reason,
+                age_of(service, now),
+                None,
+            ));
+        }
+        Ok(findings)
+    }
+}
@@ -0,0 +1,723 @@
+use super::analysis::pod_reference_problems;
+use super::graph::selector_string;
+use super::health::{node_health, pod_is_ready};
+use super::k8s::K8sClient;
+use crate::models::{
+    ClusterEvent, DanglingReference, DiagnosisFinding, EndpointSliceSummary, EndpointSummary,
+    EventFilter, HealthStatus, K8sResourceType, NodeHealth, PodDiagnosis, PodProblem, SelectedPod,
+    ServiceDiagnosis, ServiceFinding, ServicePortCheck, ServiceProblem,
+};
+use k8s_openapi::api::core::v1::{Node, Pod, Service};
+use k8s_openapi::api::discovery::v1::EndpointSlice;
+use kube::api::{ListParams, LogParams};
+use kube::{Api, Client};
+use serde_json::Value;
+use std::error::Error;
+
+/// Log lines kept from a crashed container
+const LOG_TAIL_LINES: i64 = 20;
+
+/// Node conditions that mean trouble when true; Ready means trouble when it isn't
+const PRESSURE_CONDITIONS: [&str; 4] = [
+    "MemoryPressure",
+    "DiskPressure",
+    "PIDPressure",
+    "NetworkUnavailable",
+];
+
+fn str_at<'a>(resource: &'a Value, pointer: &str) -> Option<&'a str> {
+    resource.pointer(pointer).and_then(|v| v.as_str())
+}
+
+fn finding(
+    problem: PodProblem,
+    container: Option<&str>,
+    message: impl Into<String>,
+    remedy: impl Into<String>,
+) -> DiagnosisFinding {
+    DiagnosisFinding {
+        problem,
+        container: container.map(String::from),
+        message: message.into(),
+        exit_code: None,
+        log_tail: Vec::new(),
+        remedy: remedy.into(),
+    }
+}
+
+/// What a container's exit code usually means
+fn exit_code_remedy(exit_code: i32) -> &'static str {
+    match exit_code {
+        126 => "The command isn't executable; check the image's entrypoint and file permissions",
+        127 => "The command wasn't found in the image; check `command` and the image's entrypoint",
+        137 => "The container was killed (SIGKILL), usually by a failing liveness probe or the memory limit",
+        139 => "The process crashed with a segmentation fault; check the image is built for the node's architecture",
+        143 => "The container was asked to stop (SIGTERM) and exited; check what keeps terminating it",
+        _ => "The application exits on start; the last log lines usually say why",
+    }
+}
+
+/// Sorts a registry error into the usual causes
+fn image_pull_remedy(message: &str) -> &'static str {
+    let message = message.to_lowercase();
+    if [
+        "unauthorized",
+        "authentication required",
+        "denied",
+        "forbidden",
+    ]
+    .iter()
+    .any(|m| message.contains(m))
+    {
+        "The registry refused the credentials; add or fix the pod's imagePullSecrets"
+    } else if ["not found", "manifest unknown", "does not exist"]
+        .iter()
+        .any(|m| message.contains(m))
+    {
+        "The image or tag doesn't exist; check the image name and tag"
+    } else if [
+        "no such host",
+        "timeout",
+        "i/o",
+        "connection refused",
+        "tls",
+    ]
+    .iter()
+    .any(|m| message.contains(m))
+    {
+        "The node can't reach the registry; check DNS, proxies and network policies from the node"
+    } else {
+        "Check the image reference and that the node can pull it"
+    }
+}
+
+/// Splits a scheduler message such as `0/3 nodes are available: 1 Insufficient cpu, 2 node(s)
+/// had untolerated taint {...}. preemption: ...` into one finding per cause
+fn unschedulable_findings(message: &str) -> Vec<DiagnosisFinding> {
+    let reasons = message.split_once(": ").map_or(message, |(_, r)| r);
+    let reasons = reasons.split(" preemption:").next().unwrap_or(reasons);
+
+    let mut findings = Vec::new();
+    for reason in reasons.split(", ").map(|r| r.trim().trim_end_matches('.')) {
+        let lower = reason.to_lowercase();
+        let remedy = if lower.contains("insufficient cpu") {
+            "Lower the pod's cpu request or add nodes with free cpu"
+        } else if lower.contains("insufficient memory") {
+            "Lower the pod's memory request or add nodes with free memory"
+        } else if lower.contains("insufficient") {
+            "Lower the pod's request for this resource or add nodes that provide it"
+        } else if lower.contains("taint") {
+            "Add a toleration for the taint to the pod, or remove the taint from the nodes"
+        } else if lower.contains("volume node affinity") {
+            "The volume is tied to nodes the pod can't use; check the volume's zone"
+        } else if lower.contains("anti-affinity") || lower.contains("pod affinity") {
+            "Relax the pod's podAffinity/podAntiAffinity rules or add nodes"
+        } else if lower.contains("affinity") || lower.contains("selector") {
+            "Check the pod's nodeSelector and node affinity against the node labels"
+        } else if lower.contains("persistentvolumeclaim") {
+            "Bind the claim: check it exists and that its StorageClass can provision a volume"
+        } else {
+            continue;
+        };
+        findings.push(finding(PodProblem::Unschedulable, None, reason, remedy));
+    }
+    if findings.is_empty() {
+        findings.push(finding(
+            PodProblem::Unschedulable,
+            None,
+            message,
+            "Check the pod's requests, tolerations, affinity and volumes against the nodes",
+        ));
+    }
+    findings
+}
+
+/// Container name from an Event's field path such as `spec.containers{web}`
+fn event_container(event: &ClusterEvent) -> Option<&str> {
+    let path = event.involved_object.field_path.as_deref()?;
+    path.split_once('{')?.1.strip_suffix('}')
+}
+
+fn container_findings(pod: &Value, events: &[ClusterEvent]) -> Vec<DiagnosisFinding> {
+    let mut findings = Vec::new();
+    let statuses = ["/status/initContainerStatuses", "/status/containerStatuses"]
+        .iter()
+        .filter_map(|pointer| pod.pointer(pointer).and_then(|s| s.as_array()))
+        .flatten();
+
+    for status in statuses {
+        let container = str_at(status, "/name");
+        let waiting = str_at(status, "/state/waiting/reason");
+        let waiting_message = str_at(status, "/state/waiting/message").unwrap_or_default();
+        let terminated = status
+            .pointer("/state/terminated")
+            .or_else(|| status.pointer("/lastState/terminated"));
+        let terminated_reason = terminated.and_then(|t| str_at(t, "/reason"));
+        let exit_code = terminated
+            .and_then(|t| t.get("exitCode"))
+            .and_then(|c| c.as_i64())
+            .map(|c| c as i32);
+
+        if terminated_reason == Some("OOMKilled") {
+            let mut oom = finding(
+                PodProblem::OomKilled,
+                container,
+                "The container was killed for using more memory than its limit",
+                "Raise the container's memory limit, or find what makes it use more memory",
+            );
+            oom.exit_code = exit_code;
+            findings.push(oom);
+        } else if waiting == Some("CrashLoopBackOff") {
+            let restarts = status.get("restartCount").and_then(|r| r.as_i64());
+            let mut crash = finding(
+                PodProblem::CrashLoopBackOff,
+                container,
+                format!(
+                    "The container keeps exiting ({} restarts, last exit code {})",
+                    restarts.unwrap_or(0),
+                    exit_code.map_or("unknown".to_string(), |c| c.to_string())
+                ),
+                exit_code.map_or(exit_code_remedy(1), exit_code_remedy),
+            );
+            crash.exit_code = exit_code;
+            findings.push(crash);
+        }
+
+        match waiting {
+            Some("ImagePullBackOff" | "ErrImagePull" | "InvalidImageName") => {
+                // The kubelet's Failed event carries the registry's answer
+                let registry_error = events
+                    .iter()
+                    .filter(|e| e.reason == "Failed" && event_container(e) == container)
+                    .map(|e| e.message.as_str())
+                    .find(|m| !m.is_empty())
+                    .unwrap_or(waiting_message);
+                let image = str_at(status, "/image").unwrap_or_default();
+                let message = if registry_error.is_empty() {
+                    format!("Can't pull image {}", image)
+                } else {
+                    registry_error.to_string()
+                };
+                findings.push(finding(
+                    PodProblem::ImagePullBackOff,
+                    container,
+                    message,
+                    image_pull_remedy(registry_error),
+                ));
+            }
+            Some(reason @ ("CreateContainerConfigError" | "CreateContainerError")) => {
+                findings.push(finding(
+                    PodProblem::ContainerConfigError,
+                    container,
+                    if waiting_message.is_empty() {
+                        reason
+                    } else {
+                        waiting_message
+                    },
+                    "Fix the ConfigMap, Secret or key the container's env or volumes refer to",
+                ));
+            }
+            _ => {}
+        }
+    }
+    findings
+}
+
+/// Probe failures reported by the kubelet, one finding per container and probe
+fn probe_findings(events: &[ClusterEvent]) -> Vec<DiagnosisFinding> {
+    let mut findings: Vec<DiagnosisFinding> = Vec::new();
+    for event in events.iter().filter(|e| e.reason == "Unhealthy") {
+        let container = event_container(event);
+        let probe = ["Liveness", "Readiness", "Startup"]
+            .into_iter()
+            .find(|p| event.message.starts_with(p))
+            .unwrap_or("A");
+        let seen = findings
+            .iter()
+            .any(|f| f.container.as_deref() == container && f.message.starts_with(probe));
+        if seen {
+            continue;
+        }
+        let remedy = match probe {
+            "Liveness" => {
+                "Failing liveness probes restart the container; check the probe's endpoint, \
+                 and raise initialDelaySeconds or timeoutSeconds if the app is slow to answer"
+            }
+            "Readiness" => {
+                "The pod gets no Service traffic until the probe passes; check the probe's \
+                 endpoint and what the app needs before it is ready"
+            }
+            "Startup" => {
+                "The app doesn't start within failureThreshold x periodSeconds; raise them \
+                 or check why startup is slow"
+            }
+            _ => "Check the probe's endpoint, port and timeouts",
+        };
+        let message = if event.count > 1 {
+            format!("{} ({} times)", event.message, event.count)
+        } else {
+            event.message.clone()
+        };
+        findings.push(finding(
+            PodProblem::ProbeFailure,
+            container,
+            message,
+            remedy,
+        ));
+    }
+    findings
+}
+
+fn node_findings(node: &Value) -> Vec<DiagnosisFinding> {
+    let name = str_at(node, "/metadata/name").unwrap_or_default();
+    let conditions = node
+        .pointer("/status/conditions")
+        .and_then(|c| c.as_array());
+    conditions
+        .into_iter()
+        .flatten()
+        .filter_map(|condition| {
+            let kind = str_at(condition, "/type")?;
+            let status = str_at(condition, "/status")?;
+            let failing = if kind == "Ready" {
+                status != "True"
+            } else {
+                PRESSURE_CONDITIONS.contains(&kind) && status == "True"
+            };
+            if !failing {
+                return None;
+            }
+            let detail = str_at(condition, "/message").unwrap_or_default();
+            let remedy = if kind == "Ready" {
+                "Check the node's kubelet and container runtime, or cordon and drain it"
+            } else {
+                "Free up the node or move workloads off it; the kubelet evicts pods under pressure"
+            };
+            Some(finding(
+                PodProblem::NodeCondition,
+                None,
+                format!("Node {} has {}={}: {}", name, kind, status, detail),
+                remedy,
+            ))
+        })
+        .collect()
+}
+
+fn reference_finding(reference: &DanglingReference) -> DiagnosisFinding {
+    let remedy = match reference.target_name.as_deref() {
+        Some(target) => format!(
+            "Create {} {} or fix the reference at {}",
+            reference.target_kind, target, reference.field_path
+        ),
+        None => {
+            "Check the claim's StorageClass and provisioner, or bind it to a volume".to_string()
+        }
+    };
+    finding(
+        PodProblem::MissingReference,
+        None,
+        reference.message.clone(),
+        remedy,
+    )
+}
+
+/// Works out what is wrong with a pod from its status, its Events, its node and the
+/// references in its spec that don't resolve
+pub(crate) fn diagnose(
+    pod: &Value,
+    events: &[ClusterEvent],
+    node: Option<&Value>,
+    references: &[DanglingReference],
+) -> Vec<DiagnosisFinding> {
+    let mut findings = container_findings(pod, events);
+
+    let unschedulable = pod
+        .pointer("/status/conditions")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .find(|c| {
+            str_at(c, "/type") == Some("PodScheduled") && str_at(c, "/status") == Some("False")
+        });
+    if let Some(condition) = unschedulable {
+        let message = str_at(condition, "/message")
+            .or_else(|| {
+                events
+                    .iter()
+                    .find(|e| e.reason == "FailedScheduling")
+                    .map(|e| e.message.as_str())
+            })
+            .unwrap_or("Unschedulable");
+        findings.extend(unschedulable_findings(message));
+    }
+
+    findings.extend(probe_findings(events));
+    findings.extend(node.map(node_findings).unwrap_or_default());
+    findings.extend(references.iter().map(reference_finding));
+    findings
+}
+
+/// Why a selected pod isn't ready, from its health or its Ready condition
+fn not_ready_reason(pod: &Value) -> String {
+    let health = node_health(&K8sResourceType::Pod, pod, &[]).and_then(|h| h.message);
+    let condition = pod
+        .pointer("/status/conditions")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .find(|c| str_at(c, "/type") == Some("Ready"))
+        .and_then(|c| str_at(c, "/message"));
+    match (health.as_deref(), condition) {
+        // The condition says which containers are holding the pod back
+        (Some("Running, not ready"), Some(condition)) => condition.to_string(),
+        (Some(health), _) => health.to_string(),
+        (None, condition) => condition.unwrap_or("Not ready").to_string(),
+    }
+}
+
+/// Resolves a Service target port against one pod's containers
+fn container_port(pod: &Value, target: &Value) -> Option<i32> {
+    let containers = pod.pointer("/spec/containers").and_then(|c| c.as_array());
+    containers
+        .into_iter()
+        .flatten()
+        .filter_map(|c| c.get("ports").and_then(|p| p.as_array()))
+        .flatten()
+        .find(|port| match target {
+            Value::String(name) => port.get("name").and_then(|n| n.as_str()) == Some(name),
+            number => port.get("containerPort") == Some(number),
+        })
+        .and_then(|port| port.get("containerPort"))
+        .and_then(|p| p.as_i64())
+        .map(|p| p as i32)
+}
+
+fn port_checks(service: &Value, pods: &[Value]) -> Vec<ServicePortCheck> {
+    let ports = service.pointer("/spec/ports").and_then(|p| p.as_array());
+    ports
+        .into_iter()
+        .flatten()
+        .map(|port| {
+            let number = port.get("port").cloned().unwrap_or_default();
+            // targetPort defaults to the Service port
+            let target = port.get("targetPort").cloned().unwrap_or(number.clone());
+            let resolved: Vec<i32> = pods
+                .iter()
+                .filter_map(|p| container_port(p, &target))
+                .collect();
+            ServicePortCheck {
+                name: str_at(port, "/name").map(String::from),
+                port: number.as_i64().unwrap_or_default() as i32,
+                protocol: str_at(port, "/protocol").unwrap_or("TCP").to_string(),
+                target_port: match &target {
+                    Value::String(name) => name.clone(),
+                    other => other.to_string(),
+                },
+                container_port: resolved.first().copied(),
+                declared_by: resolved.len(),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn endpoint_slice_summary(slice: &Value) -> EndpointSliceSummary {
+    let ports = slice.get("ports").and_then(|p| p.as_array());
+    let endpoints = slice.get("endpoints").and_then(|e| e.as_array());
+    EndpointSliceSummary {
+        name: str_at(slice, "/metadata/name")
+            .unwrap_or_default()
+            .to_string(),
+        address_type: str_at(slice, "/addressType")
+            .unwrap_or_default()
+            .to_string(),
+        ports: ports
+            .into_iter()
+            .flatten()
+            .map(|p| {
+                format!(
+                    "{}:{}/{}",
+                    str_at(p, "/name").unwrap_or_default(),
+                    p.get("port").and_then(|n| n.as_i64()).unwrap_or_default(),
+                    str_at(p, "/protocol").unwrap_or("TCP")
+                )
+            })
+            .collect(),
+        endpoints: endpoints
+            .into_iter()
+            .flatten()
+            .map(|e| EndpointSummary {
+                addresses: e
+                    .get("addresses")
+                    .and_then(|a| a.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|a| a.as_str().map(String::from))
+                    .collect(),
+                // An unset ready condition means ready
+                ready: e.pointer("/conditions/ready").and_then(|r| r.as_bool()) != Some(false),
+                pod: str_at(e, "/targetRef/name").map(String::from),
+                node: str_at(e, "/nodeName").map(String::from),
+            })
+            .collect(),
+    }
+}
+
+fn to_values<T: serde::Serialize>(items: Vec<T>) -> Vec<Value> {
+    items
+        .into_iter()
+        .map(|item| serde_json::to_value(item).unwrap_or_default())
+        .collect()
+}
+
+fn service_finding(
+    problem: ServiceProblem,
+    message: impl Into<String>,
+    remedy: impl Into<String>,
+) -> ServiceFinding {
+    ServiceFinding {
+        problem,
+        message: message.into(),
+        remedy: remedy.into(),
+    }
+}
+
+/// Follows a Service from its selector through the pods' readiness and ports to its
+/// EndpointSlices. `pods` are the pods its selector matches.
+pub(crate) fn diagnose_service(
+    service: &Value,
+    pods: &[Value],
+    slices: &[Value],
+) -> ServiceDiagnosis {
+    let name = str_at(service, "/metadata/name").unwrap_or_default();
+    let service_type = str_at(service, "/spec/type").unwrap_or("ClusterIP");
+    let selector = service
+        .pointer("/spec/selector")
+        .and_then(|s| s.as_object())
+        .filter(|s| !s.is_empty())
+        .map(selector_string);
+
+    let selected: Vec<SelectedPod> = pods
+        .iter()
+        .map(|pod| {
+            let ready = pod_is_ready(pod);
+            SelectedPod {
+                name: str_at(pod, "/metadata/name")
+                    .unwrap_or_default()
+                    .to_string(),
+                ready,
+                phase: str_at(pod, "/status/phase")
+                    .unwrap_or("Unknown")
+                    .to_string(),
+                ip: str_at(pod, "/status/podIP").map(String::from),
+                reason: (!ready).then(|| not_ready_reason(pod)),
+            }
+        })
+        .collect();
+    let ports = port_checks(service, pods);
+    let endpoint_slices: Vec<EndpointSliceSummary> =
+        slices.iter().map(endpoint_slice_summary).collect();
+
+    let mut findings = Vec::new();
+    if service_type != "ExternalName" {
+        match &selector {
+            None => findings.push(service_finding(
+                ServiceProblem::NoSelector,
+                "The Service has no selector, so Kubernetes doesn't manage its endpoints",
+                "Add a selector matching the pods' labels, or create EndpointSlices for it yourself",
+            )),
+            Some(selector) if pods.is_empty() => findings.push(service_finding(
+                ServiceProblem::NoMatchingPods,
+                format!("Selector {} matches no pods", selector),
+                "Compare the selector with the pod template's labels, and check the workload is \
+                 scaled up",
+            )),
+            Some(_) => {}
+        }
+        for pod in selected.iter().filter(|p| !p.ready) {
+            findings.push(service_finding(
+                ServiceProblem::PodNotReady,
+                format!(
+                    "Pod {} is not ready: {}",
+                    pod.name,
+                    pod.reason.as_deref().unwrap_or_default()
+                ),
+                format!(
+                    "Pods only receive traffic once ready; diagnose pod {} for details",
+                    pod.name
+                ),
+            ));
+        }
+        for port in ports
+            .iter()
+            .filter(|p| !pods.is_empty() && p.declared_by == 0)
+        {
+            let named = port.target_port.parse::<i32>().is_err();
+            findings.push(if named {
+                service_finding(
+                    ServiceProblem::TargetPortNotFound,
+                    format!(
+                        "Port {} targets the named port {}, which no selected container declares",
+                        port.port, port.target_port
+                    ),
+                    "Name a containerPort to match targetPort, or set targetPort to the number \
+                     the app listens on",
+                )
+            } else {
+                service_finding(
+                    ServiceProblem::PortNotDeclared,
+                    format!(
+                        "Port {} targets {}, which no selected container declares",
+                        port.port, port.target_port
+                    ),
+                    "Check the app listens on this port and declare it in the container's ports",
+                )
+            });
+        }
+        let ready_endpoints = endpoint_slices
+            .iter()
+            .flat_map(|s| &s.endpoints)
+            .filter(|e| e.ready)
+            .count();
+        let ready_pods = selected.iter().filter(|p| p.ready).count();
+        if ready_endpoints == 0 && ready_pods > 0 {
+            findings.push(service_finding(
+                ServiceProblem::NoReadyEndpoints,
+                format!(
+                    "{} pods are ready but the EndpointSlices list no ready endpoints",
+                    ready_pods
+                ),
+                "Check the target ports resolve on the pods, and that the endpoint slice \
+                 controller is running",
+            ));
+        }
+    }
+
+    ServiceDiagnosis {
+        name: name.to_string(),
+        namespace: str_at(service, "/metadata/namespace")
+            .unwrap_or_default()
+            .to_string(),
+        service_type: service_type.to_string(),
+        selector,
+        pods: selected,
+        ports,
+        endpoint_slices,
+        findings,
+    }
+}
+
+impl K8sClient {
+    /// Helper to diagnose a pod using a provided client, exposed for testing
+    pub(crate) async fn diagnose_pod_with_client(
+        client: Client,
+        name: &str,
+        namespace: &str,
+    ) -> Result<PodDiagnosis, Box<dyn Error + Send + Sync>> {
+        let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+        let pod = pods
+            .get(name)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        let pod_value = serde_json::to_value(&pod).unwrap_or_default();
+
+        let filter = EventFilter {
+            namespace: Some(namespace.to_string()),
+            involved_uid: pod.metadata.uid.clone(),
+            ..Default::default()
+        };
+        let node_name = pod.spec.as_ref().and_then(|s| s.node_name.clone());
+        let nodes: Api<Node> = Api::all(client.clone());
+        let (events, references, node) = tokio::join!(
+            Self::list_events_with_client(client.clone(), &filter),
+            pod_reference_problems(client.clone(), namespace, &pod_value),
+            async {
+                match &node_name {
+                    Some(node_name) => nodes.get_opt(node_name).await.ok().flatten(),
+                    None => None,
+                }
+            },
+        );
+        // A diagnosis is still useful without the Events or the reference checks
+        let events = events.unwrap_or_else(|e| {
+            tracing::warn!("Failed to list events for pod {}: {}", name, e);
+            Vec::new()
+        });
+        let references = references.unwrap_or_else(|e| {
+            tracing::warn!("Failed to check references of pod {}: {}", name, e);
+            Vec::new()
+        });
+        let node = node.map(|n| serde_json::to_value(n).unwrap_or_default());
+
+        let mut findings = diagnose(&pod_value, &events, node.as_ref(), &references);
+        for finding in findings
+            .iter_mut()
+            .filter(|f| f.problem == PodProblem::CrashLoopBackOff)
+        {
+            let params = LogParams {
+                container: finding.container.clone(),
+                previous: true,
+                tail_lines: Some(LOG_TAIL_LINES),
+                ..Default::default()
+            };
+            if let Ok(logs) = pods.logs(name, &params).await {
+                finding.log_tail = logs.lines().map(String::from).collect();
+            }
+        }
+
+        let health = node_health(&K8sResourceType::Pod, &pod_value, &[])
+            .unwrap_or_else(|| NodeHealth::new(HealthStatus::Unknown, "Unknown"));
+        Ok(PodDiagnosis {
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            phase: str_at(&pod_value, "/status/phase")
+                .unwrap_or("Unknown")
+                .to_string(),
+            node: node_name,
+            health,
+            findings,
+        })
+    }
+
+    /// Helper to explain a Service's endpoints using a provided client, exposed for testing
+    pub(crate) async fn diagnose_service_with_client(
+        client: Client,
+        name: &str,
+        namespace: &str,
+    ) -> Result<ServiceDiagnosis, Box<dyn Error + Send + Sync>> {
+        let services: Api<Service> = Api::namespaced(client.clone(), namespace);
+        let service = services
+            .get(name)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        let service = serde_json::to_value(service).unwrap_or_default();
+
+        let selector = service
+            .pointer("/spec/selector")
+            .and_then(|s| s.as_object())
+            .filter(|s| !s.is_empty())
+            .map(selector_string);
+        let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+        let slices: Api<EndpointSlice> = Api::namespaced(client, namespace);
+        let slice_params =
+            ListParams::default().labels(&format!("kubernetes.io/service-name={}", name));
+        let (pods, slices) = tokio::try_join!(
+            async {
+                match &selector {
+                    Some(selector) => Ok(pods
+                        .list(&ListParams::default().labels(selector))
+                        .await?
+                        .items),
+                    None => Ok(Vec::new()),
+                }
+            },
+            async { Ok(slices.list(&slice_params).await?.items) },
+        )
+        .map_err(|e: kube::Error| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+
+        Ok(diagnose_service(
+            &service,
+            &to_values(pods),
+            &to_values(slices),
+        ))
+    }
+}
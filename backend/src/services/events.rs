@@ -0,0 +1,141 @@
+use super::k8s::K8sClient;
+use crate::models::{ClusterEvent, EventFilter, EventType, InvolvedObject};
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::Event;
+use kube::api::ListParams;
+use kube::runtime::{watcher, WatchStreamExt};
+use kube::{Api, Client};
+use std::error::Error;
+use tokio::sync::mpsc;
+
+fn events_api(client: Client, filter: &EventFilter) -> Api<Event> {
+    match &filter.namespace {
+        Some(namespace) => Api::namespaced(client, namespace),
+        None => Api::all(client),
+    }
+}
+
+/// The parts of a filter the API server can apply itself
+fn field_selector(filter: &EventFilter) -> String {
+    let mut fields = Vec::new();
+    if let Some(kind) = &filter.involved_kind {
+        fields.push(format!("involvedObject.kind={}", kind));
+    }
+    if let Some(name) = &filter.involved_name {
+        fields.push(format!("involvedObject.name={}", name));
+    }
+    if let Some(uid) = &filter.involved_uid {
+        fields.push(format!("involvedObject.uid={}", uid));
+    }
+    if let Some(event_type) = &filter.event_type {
+        fields.push(format!("type={:?}", event_type));
+    }
+    fields.join(",")
+}
+
+fn in_window(event: &ClusterEvent, filter: &EventFilter) -> bool {
+    let Some(last_seen) = event.last_seen else {
+        return filter.since.is_none() && filter.until.is_none();
+    };
+    filter.since.is_none_or(|since| last_seen >= since)
+        && filter.until.is_none_or(|until| last_seen <= until)
+}
+
+/// Folds the legacy (`count`, `lastTimestamp`) and the newer (`series`, `eventTime`) ways
+/// reporters record when and how often an Event happened
+pub(crate) fn to_cluster_event(event: Event) -> ClusterEvent {
+    let created = event.metadata.creation_timestamp.map(|t| t.0);
+    let event_time = event.event_time.map(|t| t.0);
+    let series = event.series.as_ref();
+    let last_seen = series
+        .and_then(|s| s.last_observed_time.as_ref())
+        .map(|t| t.0)
+        .or(event.last_timestamp.map(|t| t.0))
+        .or(event_time)
+        .or(created);
+    let source = event
+        .reporting_component
+        .filter(|c| !c.is_empty())
+        .or(event.source.and_then(|s| s.component));
+    let involved = event.involved_object;
+
+    ClusterEvent {
+        name: event.metadata.name.unwrap_or_default(),
+        namespace: event.metadata.namespace.unwrap_or_default(),
+        involved_object: InvolvedObject {
+            kind: involved.kind.unwrap_or_default(),
+            name: involved.name.unwrap_or_default(),
+            namespace: involved.namespace,
+            uid: involved.uid,
+            field_path: involved.field_path,
+        },
+        event_type: event
+            .type_
+            .unwrap_or_else(|| format!("{:?}", EventType::Normal)),
+        reason: event.reason.unwrap_or_default(),
+        message: event.message.unwrap_or_default(),
+        count: series.and_then(|s| s.count).or(event.count).unwrap_or(1),
+        source,
+        first_seen: event
+            .first_timestamp
+            .map(|t| t.0)
+            .or(event_time)
+            .or(created),
+        last_seen,
+    }
+}
+
+impl K8sClient {
+    /// Helper to list Events matching a filter, newest first, using a provided client,
+    /// exposed for testing
+    pub(crate) async fn list_events_with_client(
+        client: Client,
+        filter: &EventFilter,
+    ) -> Result<Vec<ClusterEvent>, Box<dyn Error + Send + Sync>> {
+        let lp = ListParams::default().fields(&field_selector(filter));
+        let list = events_api(client, filter)
+            .list(&lp)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+
+        let mut events: Vec<ClusterEvent> = list
+            .items
+            .into_iter()
+            .map(to_cluster_event)
+            .filter(|e| in_window(e, filter))
+            .collect();
+        events.sort_by_key(|e| std::cmp::Reverse(e.last_seen));
+        Ok(events)
+    }
+
+    /// Helper to send Events matching a filter as they are reported, using a provided client.
+    /// Events that exist when the watch starts are not sent. Runs until `events` is closed.
+    pub(crate) async fn watch_events_with_client(
+        client: Client,
+        filter: EventFilter,
+        events: mpsc::Sender<ClusterEvent>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let config = watcher::Config::default().fields(&field_selector(&filter));
+        let mut stream = watcher(events_api(client, &filter), config)
+            .default_backoff()
+            .boxed();
+
+        loop {
+            let next = tokio::select! {
+                _ = events.closed() => return Ok(()),
+                next = stream.next() => next,
+            };
+            match next {
+                Some(Ok(watcher::Event::Apply(event))) => {
+                    let event = to_cluster_event(event);
+                    if in_window(&event, &filter) && events.send(event).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => tracing::warn!("Event watch failed, retrying: {}", e),
+                None => return Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,1644 @@
+use super::health::{node_health, pod_is_ready};
+use super::k8s::K8sClient;
+use crate::models::{
+    GraphData, GraphDetail, GraphEdge, GraphNode, GraphOptions, GraphStats, HealthStatus,
+    K8sResourceType, NamespaceGraphOptions, NodeHealth,
+};
+use futures::future::join_all;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
+use kube::api::DynamicObject;
+use kube::{Api, Client};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{OnceCell, Semaphore};
+
+/// Caps the `depth` option so a single request can't crawl an entire cluster
+pub const MAX_GRAPH_DEPTH: usize = 10;
+
+/// API calls one graph request may have in flight at once
+const MAX_CONCURRENT_GRAPH_CALLS: usize = 8;
+
+/// Kinds listed for a namespace topology, roughly from the outside in
+const TOPOLOGY_KINDS: [K8sResourceType; 13] = [
+    K8sResourceType::Ingress,
+    K8sResourceType::Service,
+    K8sResourceType::CronJob,
+    K8sResourceType::Deployment,
+    K8sResourceType::StatefulSet,
+    K8sResourceType::DaemonSet,
+    K8sResourceType::Job,
+    K8sResourceType::ReplicaSet,
+    K8sResourceType::Pod,
+    K8sResourceType::ConfigMap,
+    K8sResourceType::Secret,
+    K8sResourceType::PersistentVolumeClaim,
+    K8sResourceType::ServiceAccount,
+];
+
+/// Kinds whose objects carry an ownerReference back to an object of the given kind
+fn owned_kinds(resource_type: &K8sResourceType) -> &'static [K8sResourceType] {
+    match resource_type {
+        K8sResourceType::Deployment => &[K8sResourceType::ReplicaSet],
+        K8sResourceType::ReplicaSet
+        | K8sResourceType::StatefulSet
+        | K8sResourceType::DaemonSet
+        | K8sResourceType::Job => &[K8sResourceType::Pod],
+        K8sResourceType::CronJob => &[K8sResourceType::Job],
+        _ => &[],
+    }
+}
+
+fn uid_of(resource: &Value) -> Option<&str> {
+    resource.pointer("/metadata/uid").and_then(|u| u.as_str())
+}
+
+fn name_of(resource: &Value) -> Option<&str> {
+    resource.pointer("/metadata/name").and_then(|n| n.as_str())
+}
+
+fn owner_refs(resource: &Value) -> Vec<OwnerReference> {
+    resource
+        .pointer("/metadata/ownerReferences")
+        .cloned()
+        .and_then(|refs| serde_json::from_value(refs).ok())
+        .unwrap_or_default()
+}
+
+/// ServiceAccount a pod runs as; pods without one set get the namespace's `default`
+fn pod_service_account(pod: &Value) -> Option<&str> {
+    let spec = pod.get("spec")?;
+    Some(
+        spec.get("serviceAccountName")
+            .and_then(|s| s.as_str())
+            .unwrap_or("default"),
+    )
+}
+
+/// Headless Service named in a StatefulSet's spec.serviceName
+fn governing_service(statefulset: &Value) -> Option<&str> {
+    statefulset
+        .pointer("/spec/serviceName")
+        .and_then(|s| s.as_str())
+        .filter(|s| !s.is_empty())
+}
+
+/// Names of a StatefulSet's volumeClaimTemplates
+fn claim_templates(statefulset: &Value) -> Vec<String> {
+    statefulset
+        .pointer("/spec/volumeClaimTemplates")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|t| name_of(t).map(String::from))
+        .collect()
+}
+
+/// Whether `claim` is one of the PVCs a StatefulSet creates from a volumeClaimTemplate,
+/// which are named `<template>-<statefulset>-<ordinal>` and not owned by the StatefulSet
+fn is_template_claim(claim: &str, template: &str, statefulset: &str) -> bool {
+    claim
+        .strip_prefix(template)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .and_then(|rest| rest.strip_prefix(statefulset))
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(|ordinal| !ordinal.is_empty() && ordinal.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Backend Services an Ingress routes to, each with a `host/path` label.
+/// Several routes to the same Service are folded into one comma-separated label.
+fn ingress_routes(ingress: &Value) -> Vec<(String, String)> {
+    let mut routes: Vec<(String, String)> = Vec::new();
+    let mut add = |service: Option<&str>, route: String| {
+        let Some(service) = service else {
+            return;
+        };
+        match routes.iter_mut().find(|(name, _)| name == service) {
+            Some((_, label)) => {
+                label.push_str(", ");
+                label.push_str(&route);
+            }
+            None => routes.push((service.to_string(), route)),
+        }
+    };
+
+    if let Some(backend) = ingress.pointer("/spec/defaultBackend/service/name") {
+        add(backend.as_str(), "default backend".to_string());
+    }
+    for rule in ingress
+        .pointer("/spec/rules")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let host = rule.get("host").and_then(|h| h.as_str()).unwrap_or("*");
+        for path in rule
+            .pointer("/http/paths")
+            .and_then(|p| p.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let service = path
+                .pointer("/backend/service/name")
+                .and_then(|n| n.as_str());
+            let path = path.get("path").and_then(|p| p.as_str()).unwrap_or("/");
+            add(service, format!("{}{}", host, path));
+        }
+    }
+    routes
+}
+
+/// TLS Secrets an Ingress terminates with, each labelled with the hosts it covers
+fn ingress_tls_secrets(ingress: &Value) -> Vec<(String, String)> {
+    ingress
+        .pointer("/spec/tls")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|tls| {
+            let secret = tls.get("secretName")?.as_str()?.to_string();
+            let hosts: Vec<&str> = tls
+                .get("hosts")
+                .and_then(|h| h.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|h| h.as_str())
+                .collect();
+            let label = if hosts.is_empty() {
+                "tls".to_string()
+            } else {
+                format!("tls {}", hosts.join(", "))
+            };
+            Some((secret, label))
+        })
+        .collect()
+}
+
+/// `(name, namespace)` of every ServiceAccount subject of a RoleBinding or ClusterRoleBinding.
+/// RoleBinding subjects without a namespace default to the binding's own.
+fn binding_subjects(binding: &Value) -> Vec<(String, String)> {
+    let binding_ns = binding
+        .pointer("/metadata/namespace")
+        .and_then(|n| n.as_str())
+        .unwrap_or_default();
+    binding
+        .get("subjects")
+        .and_then(|s| s.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|subject| subject.get("kind").and_then(|k| k.as_str()) == Some("ServiceAccount"))
+        .filter_map(|subject| {
+            let name = subject.get("name")?.as_str()?;
+            let namespace = subject
+                .get("namespace")
+                .and_then(|n| n.as_str())
+                .unwrap_or(binding_ns);
+            Some((name.to_string(), namespace.to_string()))
+        })
+        .collect()
+}
+
+/// An empty selector matches nothing here, mirroring how Services treat it
+pub(crate) fn selector_matches(selector: &Map<String, Value>, labels: &Map<String, Value>) -> bool {
+    !selector.is_empty()
+        && selector
+            .iter()
+            .all(|(k, v)| labels.get(k).and_then(|l| l.as_str()) == v.as_str())
+}
+
+/// `key=value,...` form of a label selector, as kubectl prints it
+pub(crate) fn selector_string(selector: &Map<String, Value>) -> String {
+    selector
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v.as_str().unwrap_or_default()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// A ConfigMap, Secret or PVC named in a pod spec
+pub(crate) struct SpecReference {
+    pub(crate) resource_type: K8sResourceType,
+    pub(crate) name: String,
+    /// Field path relative to the pod spec, e.g. `volumes[0].configMap.name`
+    pub(crate) path: String,
+    /// Marked `optional: true`, so the pod starts without it
+    pub(crate) optional: bool,
+}
+
+/// ConfigMaps, Secrets and PVCs a pod spec refers to through volumes, env and envFrom
+pub(crate) fn pod_spec_references(spec: &Value) -> Vec<SpecReference> {
+    let mut refs = Vec::new();
+    let mut push = |resource_type: K8sResourceType, source: &Value, key: &str, path: String| {
+        if let Some(name) = source.get(key).and_then(|n| n.as_str()) {
+            refs.push(SpecReference {
+                resource_type,
+                name: name.to_string(),
+                path: format!("{}.{}", path, key),
+                optional: source.get("optional").and_then(|o| o.as_bool()) == Some(true),
+            });
+        }
+    };
+    let items = |value: &Value, key: &str| -> Vec<Value> {
+        value
+            .get(key)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    for (i, vol) in items(spec, "volumes").iter().enumerate() {
+        if let Some(cm) = vol.get("configMap") {
+            let path = format!("volumes[{}].configMap", i);
+            push(K8sResourceType::ConfigMap, cm, "name", path);
+        }
+        if let Some(secret) = vol.get("secret") {
+            let path = format!("volumes[{}].secret", i);
+            push(K8sResourceType::Secret, secret, "secretName", path);
+        }
+        if let Some(pvc) = vol.get("persistentVolumeClaim") {
+            let path = format!("volumes[{}].persistentVolumeClaim", i);
+            push(
+                K8sResourceType::PersistentVolumeClaim,
+                pvc,
+                "claimName",
+                path,
+            );
+        }
+    }
+
+    for key in ["containers", "initContainers"] {
+        for (c, container) in items(spec, key).iter().enumerate() {
+            for (e, env) in items(container, "env").iter().enumerate() {
+                let Some(value_from) = env.get("valueFrom") else {
+                    continue;
+                };
+                let path = format!("{}[{}].env[{}].valueFrom", key, c, e);
+                if let Some(cm) = value_from.get("configMapKeyRef") {
+                    let path = format!("{}.configMapKeyRef", path);
+                    push(K8sResourceType::ConfigMap, cm, "name", path);
+                }
+                if let Some(secret) = value_from.get("secretKeyRef") {
+                    let path = format!("{}.secretKeyRef", path);
+                    push(K8sResourceType::Secret, secret, "name", path);
+                }
+            }
+            for (e, env_from) in items(container, "envFrom").iter().enumerate() {
+                let path = format!("{}[{}].envFrom[{}]", key, c, e);
+                if let Some(cm) = env_from.get("configMapRef") {
+                    let path = format!("{}.configMapRef", path);
+                    push(K8sResourceType::ConfigMap, cm, "name", path);
+                }
+                if let Some(secret) = env_from.get("secretRef") {
+                    let path = format!("{}.secretRef", path);
+                    push(K8sResourceType::Secret, secret, "name", path);
+                }
+            }
+        }
+    }
+    refs
+}
+
+/// ConfigMaps, Secrets and PVCs a pod refers to, as (kind, name, optional)
+pub(crate) fn pod_dependencies(pod: &Value) -> Vec<(K8sResourceType, String, bool)> {
+    pod.get("spec")
+        .map(pod_spec_references)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| (r.resource_type, r.name, r.optional))
+        .collect()
+}
+
+/// Whether a workload or claim is ready, for kinds where that has a clear meaning
+fn readiness(resource_type: &K8sResourceType, resource: &Value) -> Option<bool> {
+    let count = |pointer: &str| resource.pointer(pointer).and_then(|v| v.as_i64());
+    match resource_type {
+        K8sResourceType::Pod => Some(pod_is_ready(resource)),
+        K8sResourceType::Deployment
+        | K8sResourceType::StatefulSet
+        | K8sResourceType::ReplicaSet => {
+            let desired = count("/spec/replicas").unwrap_or(1);
+            Some(count("/status/readyReplicas").unwrap_or(0) >= desired)
+        }
+        K8sResourceType::DaemonSet => {
+            let desired = count("/status/desiredNumberScheduled").unwrap_or(0);
+            Some(count("/status/numberReady").unwrap_or(0) >= desired)
+        }
+        K8sResourceType::PersistentVolumeClaim => {
+            Some(resource.pointer("/status/phase").and_then(|p| p.as_str()) == Some("Bound"))
+        }
+        _ => None,
+    }
+}
+
+/// The few fields a graph node needs when the full object is not requested
+fn summarize(resource_type: &K8sResourceType, resource: &Value) -> Value {
+    serde_json::json!({
+        "kind": format!("{:?}", resource_type),
+        "name": name_of(resource),
+        "namespace": resource.pointer("/metadata/namespace"),
+        "uid": uid_of(resource),
+        "phase": resource.pointer("/status/phase"),
+        "ready": readiness(resource_type, resource),
+    })
+}
+
+type ListKey = (K8sResourceType, Option<String>);
+type ObjectKey = (K8sResourceType, Option<String>, String);
+/// A listed kind, or why it couldn't be listed
+type Listing = Result<Arc<Vec<Value>>, String>;
+/// Shared result of one API call; the first caller fetches, later callers wait for it
+type Shared<T> = Arc<OnceCell<T>>;
+
+/// One edge found by a graph rule, plus the object at its far end when that object still
+/// needs a node. Rules only collect relations; the builder applies them in a fixed order.
+struct Relation {
+    source: String,
+    target: String,
+    label: String,
+    node: Option<(K8sResourceType, Value)>,
+    /// Why the reference is broken; the node is then a placeholder for the missing object
+    error: Option<String>,
+}
+
+impl Relation {
+    /// `anchor -> object`
+    fn to(
+        anchor: &str,
+        resource_type: K8sResourceType,
+        object: Value,
+        label: &str,
+    ) -> Option<Self> {
+        Some(Self {
+            source: anchor.to_string(),
+            target: uid_of(&object)?.to_string(),
+            label: label.to_string(),
+            node: Some((resource_type, object)),
+            error: None,
+        })
+    }
+
+    /// `object -> anchor`
+    fn from(
+        object: Value,
+        resource_type: K8sResourceType,
+        anchor: &str,
+        label: &str,
+    ) -> Option<Self> {
+        Some(Self {
+            source: uid_of(&object)?.to_string(),
+            target: anchor.to_string(),
+            label: label.to_string(),
+            node: Some((resource_type, object)),
+            error: None,
+        })
+    }
+
+    /// Edge between two objects that are already in the graph
+    fn edge(source: &str, target: &str, label: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            target: target.to_string(),
+            label: label.to_string(),
+            node: None,
+            error: None,
+        }
+    }
+
+    /// `anchor -> placeholder` for a referenced object that could not be found
+    fn broken(
+        anchor: &str,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<&str>,
+        label: &str,
+        error: String,
+    ) -> Self {
+        let id = match namespace {
+            Some(ns) => format!("missing:{:?}/{}/{}", resource_type, ns, name),
+            None => format!("missing:{:?}/{}", resource_type, name),
+        };
+        let placeholder = serde_json::json!({
+            "metadata": { "name": name, "namespace": namespace, "uid": id }
+        });
+        Self {
+            source: anchor.to_string(),
+            target: id,
+            label: label.to_string(),
+            node: Some((resource_type, placeholder)),
+            error: Some(error),
+        }
+    }
+}
+
+/// Why an object couldn't be fetched
+#[derive(Debug, Clone)]
+enum Unfetched {
+    NotFound(String),
+    /// Denied by RBAC, timed out or failed on the server; the object may well exist
+    Unreadable(String),
+}
+
+/// API access for building one graph. Calls run concurrently up to a limit, and every list
+/// or object is fetched at most once no matter how many rules ask for it.
+/// A failed lookup never fails the whole graph; rules either skip it or report a broken link.
+pub(crate) struct GraphFetcher {
+    client: Client,
+    namespace: Option<String>,
+    permits: Semaphore,
+    lists: Mutex<HashMap<ListKey, Shared<Listing>>>,
+    objects: Mutex<HashMap<ObjectKey, Shared<Result<Value, Unfetched>>>>,
+    api_calls: AtomicUsize,
+    cache_hits: AtomicUsize,
+}
+
+impl GraphFetcher {
+    pub(crate) fn new(client: Client, namespace: Option<String>) -> Self {
+        Self {
+            client,
+            namespace,
+            permits: Semaphore::new(MAX_CONCURRENT_GRAPH_CALLS),
+            lists: Mutex::new(HashMap::new()),
+            objects: Mutex::new(HashMap::new()),
+            api_calls: AtomicUsize::new(0),
+            cache_hits: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn stats(&self, started: Instant) -> GraphStats {
+        GraphStats {
+            duration_ms: started.elapsed().as_millis() as u64,
+            api_calls: self.api_calls.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Namespace to look objects of this kind up in; cluster-scoped kinds get none
+    fn scope(&self, resource_type: &K8sResourceType) -> Option<String> {
+        if resource_type.is_namespaced() {
+            self.namespace.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the shared cell for `key`, creating it on first use
+    fn cell<K, T>(&self, cache: &Mutex<HashMap<K, Shared<T>>>, key: K) -> Shared<T>
+    where
+        K: std::hash::Hash + Eq,
+    {
+        let mut cache = cache.lock().unwrap();
+        match cache.get(&key) {
+            Some(cell) => {
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                cell.clone()
+            }
+            None => cache.entry(key).or_default().clone(),
+        }
+    }
+
+    /// Fetches one object; the error says why it isn't there
+    async fn lookup(
+        &self,
+        resource_type: &K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+    ) -> Result<Value, Unfetched> {
+        let key = (resource_type.clone(), namespace.clone(), name.to_string());
+        let cell = self.cell(&self.objects, key);
+        cell.get_or_init(|| async {
+            let _permit = self.permits.acquire().await;
+            self.api_calls.fetch_add(1, Ordering::Relaxed);
+            K8sClient::get_resource_with_client(
+                self.client.clone(),
+                resource_type.clone(),
+                name,
+                namespace,
+            )
+            .await
+            .map_err(|e| match e.downcast_ref::<kube::Error>() {
+                Some(kube::Error::Api(response)) if response.code == 404 => {
+                    Unfetched::NotFound(format!("{:?} {} not found", resource_type, name))
+                }
+                _ => Unfetched::Unreadable(format!(
+                    "could not fetch {:?} {}: {}",
+                    resource_type, name, e
+                )),
+            })
+        })
+        .await
+        .clone()
+    }
+
+    async fn get(
+        &self,
+        resource_type: &K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+    ) -> Option<Value> {
+        self.lookup(resource_type, name, namespace).await.ok()
+    }
+
+    /// `anchor -> object` when the object exists, a broken link to a placeholder when it
+    /// doesn't, and nothing when it can't be read
+    async fn link(
+        &self,
+        anchor: &str,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+        label: &str,
+    ) -> Option<Relation> {
+        match self.lookup(&resource_type, name, namespace.clone()).await {
+            Ok(object) => Relation::to(anchor, resource_type, object, label),
+            Err(Unfetched::NotFound(error)) => Some(Relation::broken(
+                anchor,
+                resource_type,
+                name,
+                namespace.as_deref(),
+                label,
+                error,
+            )),
+            Err(Unfetched::Unreadable(error)) => {
+                tracing::debug!("Graph relation skipped: {}", error);
+                None
+            }
+        }
+    }
+
+    /// Lists one kind; the error says why it couldn't be read, e.g. an RBAC denial
+    async fn list(&self, resource_type: &K8sResourceType, namespace: Option<&str>) -> Listing {
+        let key = (resource_type.clone(), namespace.map(String::from));
+        let cell = self.cell(&self.lists, key);
+        cell.get_or_init(|| async {
+            let _permit = self.permits.acquire().await;
+            self.api_calls.fetch_add(1, Ordering::Relaxed);
+            let api_resource = resource_type.get_api_resource();
+            let api: Api<DynamicObject> = match namespace {
+                Some(ns) => Api::namespaced_with(self.client.clone(), ns, &api_resource),
+                None => Api::all_with(self.client.clone(), &api_resource),
+            };
+            match api.list(&Default::default()).await {
+                Ok(list) => Ok(Arc::new(
+                    list.items
+                        .into_iter()
+                        .map(|item| serde_json::to_value(item).unwrap_or_default())
+                        .collect(),
+                )),
+                Err(e) => Err(format!("could not list {:?}s: {}", resource_type, e)),
+            }
+        })
+        .await
+        .clone()
+    }
+
+    /// Lists one kind for rules where an unreadable list only means fewer relations
+    async fn listed(
+        &self,
+        resource_type: &K8sResourceType,
+        namespace: Option<&str>,
+    ) -> Arc<Vec<Value>> {
+        self.list(resource_type, namespace)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::debug!("Graph relations skipped: {}", error);
+                Arc::default()
+            })
+    }
+
+    /// Follows ownerReferences upwards, e.g. Pod -> ReplicaSet -> Deployment.
+    /// Each level's owners are fetched together; an owner already seen is linked but not revisited.
+    async fn owner_chain(&self, start: &Value, depth: usize) -> Vec<Relation> {
+        let mut relations = Vec::new();
+        let mut seen: HashSet<String> = uid_of(start).map(String::from).into_iter().collect();
+        let mut frontier = vec![start.clone()];
+
+        for _ in 0..depth {
+            let mut lookups = Vec::new();
+            for child in &frontier {
+                let Some(child_uid) = uid_of(child) else {
+                    continue;
+                };
+                for owner in owner_refs(child) {
+                    let Some(owner_type) = K8sResourceType::from_kind(&owner.kind) else {
+                        continue;
+                    };
+                    if seen.contains(&owner.uid) {
+                        relations.push(Relation::edge(&owner.uid, child_uid, "owner"));
+                    } else {
+                        lookups.push((child_uid.to_string(), owner_type, owner));
+                    }
+                }
+            }
+
+            let fetched = join_all(lookups.iter().map(|(_, owner_type, owner)| {
+                self.get(owner_type, &owner.name, self.scope(owner_type))
+            }))
+            .await;
+
+            let mut next = Vec::new();
+            for ((child_uid, owner_type, owner), resource) in lookups.into_iter().zip(fetched) {
+                let Some(resource) = resource else {
+                    continue;
+                };
+                if seen.insert(owner.uid.clone()) {
+                    next.push(resource.clone());
+                }
+                relations.push(Relation {
+                    source: owner.uid,
+                    target: child_uid,
+                    label: "owner".to_string(),
+                    node: Some((owner_type, resource)),
+                    error: None,
+                });
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        relations
+    }
+
+    /// Follows ownerReferences downwards, e.g. Deployment -> ReplicaSets -> Pods.
+    /// Children are found in namespace-wide lists that are fetched once per kind.
+    async fn descendants(
+        &self,
+        resource_type: &K8sResourceType,
+        start: &Value,
+        depth: usize,
+    ) -> Vec<Relation> {
+        let mut relations = Vec::new();
+        let Some(ns) = self.namespace.clone() else {
+            return relations;
+        };
+        let mut seen: HashSet<String> = uid_of(start).map(String::from).into_iter().collect();
+        let mut frontier = vec![(resource_type.clone(), start.clone())];
+
+        for _ in 0..depth {
+            let mut kinds: Vec<&K8sResourceType> = Vec::new();
+            for (parent_type, _) in &frontier {
+                for kind in owned_kinds(parent_type) {
+                    if !kinds.contains(&kind) {
+                        kinds.push(kind);
+                    }
+                }
+            }
+            let lists = join_all(kinds.iter().map(|kind| self.listed(kind, Some(&ns)))).await;
+            let lists: Vec<(&K8sResourceType, Arc<Vec<Value>>)> =
+                kinds.into_iter().zip(lists).collect();
+
+            let mut next = Vec::new();
+            for (parent_type, parent) in &frontier {
+                let Some(parent_uid) = uid_of(parent) else {
+                    continue;
+                };
+                for (child_type, children) in &lists {
+                    if !owned_kinds(parent_type).contains(child_type) {
+                        continue;
+                    }
+                    for child in children.iter() {
+                        if !owner_refs(child).iter().any(|r| r.uid == parent_uid) {
+                            continue;
+                        }
+                        let Some(child_uid) = uid_of(child) else {
+                            continue;
+                        };
+                        if seen.insert(child_uid.to_string()) {
+                            next.push(((*child_type).clone(), child.clone()));
+                        }
+                        relations.extend(Relation::to(
+                            parent_uid,
+                            (*child_type).clone(),
+                            child.clone(),
+                            "manages",
+                        ));
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        relations
+    }
+
+    /// Non-ownership relations of the requested object (selectors, mounts, bindings).
+    /// Independent rules for the same object run concurrently.
+    async fn related(&self, resource_type: &K8sResourceType, resource: &Value) -> Vec<Relation> {
+        let Some(uid) = uid_of(resource) else {
+            return Vec::new();
+        };
+        let name = name_of(resource).unwrap_or_default();
+
+        let groups: Vec<Vec<Relation>> = match resource_type {
+            K8sResourceType::Service => {
+                let (pods, ingresses) = tokio::join!(
+                    self.selected_pods(uid, resource),
+                    self.routing_ingresses(uid, name)
+                );
+                vec![pods, ingresses]
+            }
+            K8sResourceType::Ingress => vec![self.ingress_targets(uid, resource).await],
+            K8sResourceType::Pod => {
+                let (services, dependencies, account) = tokio::join!(
+                    self.selecting_services(uid, resource),
+                    self.pod_dependencies(uid, resource),
+                    self.pod_service_account(uid, resource)
+                );
+                vec![services, dependencies, account]
+            }
+            K8sResourceType::ServiceAccount => {
+                let namespace = self.namespace.clone().unwrap_or_default();
+                vec![self.subject_bindings(uid, name, &namespace).await]
+            }
+            K8sResourceType::RoleBinding | K8sResourceType::ClusterRoleBinding => {
+                let (subjects, role) = tokio::join!(
+                    self.binding_subjects(uid, resource),
+                    self.role_ref(uid, resource)
+                );
+                vec![subjects, role]
+            }
+            K8sResourceType::Role | K8sResourceType::ClusterRole => {
+                vec![self.role_bindings(uid, resource_type, name).await]
+            }
+            K8sResourceType::PersistentVolumeClaim => {
+                let (storage, pods) = tokio::join!(
+                    self.claim_storage(uid, resource),
+                    self.dependent_pods(uid, resource_type, name)
+                );
+                vec![storage, pods]
+            }
+            K8sResourceType::ConfigMap | K8sResourceType::Secret => {
+                vec![self.dependent_pods(uid, resource_type, name).await]
+            }
+            K8sResourceType::PersistentVolume => vec![self.bound_claim(uid, resource).await],
+            K8sResourceType::StorageClass => vec![self.class_claims(uid, name).await],
+            K8sResourceType::StatefulSet => {
+                let (service, claims) = tokio::join!(
+                    self.governing_service(uid, resource),
+                    self.template_claims(uid, resource, name)
+                );
+                vec![service, claims]
+            }
+            _ => Vec::new(),
+        };
+        groups.into_iter().flatten().collect()
+    }
+
+    /// StatefulSet -> the headless Service named in spec.serviceName
+    async fn governing_service(&self, uid: &str, statefulset: &Value) -> Vec<Relation> {
+        let Some(service) = governing_service(statefulset) else {
+            return Vec::new();
+        };
+        let service_type = K8sResourceType::Service;
+        let namespace = self.scope(&service_type);
+        self.link(uid, service_type, service, namespace, "governed by")
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// StatefulSet -> PVCs created from its volumeClaimTemplates
+    async fn template_claims(&self, uid: &str, statefulset: &Value, name: &str) -> Vec<Relation> {
+        let templates = claim_templates(statefulset);
+        let Some(ns) = self.namespace.as_deref() else {
+            return Vec::new();
+        };
+        if templates.is_empty() {
+            return Vec::new();
+        }
+        let claims = self
+            .listed(&K8sResourceType::PersistentVolumeClaim, Some(ns))
+            .await;
+        claims
+            .iter()
+            .filter(|claim| {
+                let claim_name = name_of(claim).unwrap_or_default();
+                templates
+                    .iter()
+                    .any(|template| is_template_claim(claim_name, template, name))
+            })
+            .filter_map(|claim| {
+                Relation::to(
+                    uid,
+                    K8sResourceType::PersistentVolumeClaim,
+                    claim.clone(),
+                    "claims",
+                )
+            })
+            .collect()
+    }
+
+    /// Service -> Pods matched by its selector
+    async fn selected_pods(&self, uid: &str, service: &Value) -> Vec<Relation> {
+        let Some(selector) = service
+            .pointer("/spec/selector")
+            .and_then(|s| s.as_object())
+        else {
+            return Vec::new();
+        };
+        if selector.is_empty() {
+            return Vec::new();
+        }
+        let pods = match self
+            .list(&K8sResourceType::Pod, self.namespace.as_deref())
+            .await
+        {
+            Ok(pods) => pods,
+            // Whether the selector matches anything is unknown, so nothing is drawn
+            Err(error) => {
+                tracing::debug!("Service pods skipped: {}", error);
+                return Vec::new();
+            }
+        };
+        let relations: Vec<Relation> = pods
+            .iter()
+            .filter(|pod| {
+                pod.pointer("/metadata/labels")
+                    .and_then(|l| l.as_object())
+                    .is_some_and(|labels| selector_matches(selector, labels))
+            })
+            .filter_map(|pod| Relation::to(uid, K8sResourceType::Pod, pod.clone(), "selects"))
+            .collect();
+        if !relations.is_empty() {
+            return relations;
+        }
+
+        let selector = selector_string(selector);
+        vec![Relation::broken(
+            uid,
+            K8sResourceType::Pod,
+            &selector,
+            self.namespace.as_deref(),
+            "selects",
+            format!("selector {} matches no pods", selector),
+        )]
+    }
+
+    /// Ingresses with a rule or default backend pointing at this Service -> it
+    async fn routing_ingresses(&self, uid: &str, name: &str) -> Vec<Relation> {
+        let Some(ns) = self.namespace.as_deref() else {
+            return Vec::new();
+        };
+        let ingresses = self.listed(&K8sResourceType::Ingress, Some(ns)).await;
+        ingresses
+            .iter()
+            .filter_map(|ingress| {
+                let (_, route) = ingress_routes(ingress)
+                    .into_iter()
+                    .find(|(service, _)| service == name)?;
+                Relation::from(ingress.clone(), K8sResourceType::Ingress, uid, &route)
+            })
+            .collect()
+    }
+
+    /// Ingress -> backend Services, TLS Secrets and its IngressClass
+    async fn ingress_targets(&self, uid: &str, ingress: &Value) -> Vec<Relation> {
+        let routes = ingress_routes(ingress);
+        let secrets = ingress_tls_secrets(ingress);
+        // Older Ingresses still name their class through the legacy annotation
+        let class = ingress
+            .pointer("/spec/ingressClassName")
+            .or_else(|| ingress.pointer("/metadata/annotations/kubernetes.io~1ingress.class"))
+            .and_then(|c| c.as_str());
+
+        let (services, secret_objects, class_object) = tokio::join!(
+            join_all(routes.iter().map(|(service, route)| {
+                let namespace = self.scope(&K8sResourceType::Service);
+                self.link(uid, K8sResourceType::Service, service, namespace, route)
+            })),
+            join_all(secrets.iter().map(|(secret, label)| {
+                let namespace = self.scope(&K8sResourceType::Secret);
+                self.link(uid, K8sResourceType::Secret, secret, namespace, label)
+            })),
+            async {
+                match class {
+                    Some(class) => {
+                        self.link(uid, K8sResourceType::IngressClass, class, None, "class")
+                            .await
+                    }
+                    None => None,
+                }
+            }
+        );
+
+        services
+            .into_iter()
+            .chain(secret_objects)
+            .chain(std::iter::once(class_object))
+            .flatten()
+            .collect()
+    }
+
+    /// Services whose selector matches the pod's labels -> Pod
+    async fn selecting_services(&self, uid: &str, pod: &Value) -> Vec<Relation> {
+        let Some(labels) = pod.pointer("/metadata/labels").and_then(|l| l.as_object()) else {
+            return Vec::new();
+        };
+        let services = self
+            .listed(&K8sResourceType::Service, self.namespace.as_deref())
+            .await;
+        services
+            .iter()
+            .filter(|service| {
+                service
+                    .pointer("/spec/selector")
+                    .and_then(|s| s.as_object())
+                    .is_some_and(|selector| selector_matches(selector, labels))
+            })
+            .filter_map(|service| {
+                Relation::from(service.clone(), K8sResourceType::Service, uid, "selects")
+            })
+            .collect()
+    }
+
+    /// Pod -> ConfigMaps, Secrets and PVCs it mounts or reads env from. A missing optional
+    /// one is left out: the pod starts fine without it.
+    async fn pod_dependencies(&self, uid: &str, pod: &Value) -> Vec<Relation> {
+        let deps = pod_dependencies(pod);
+        join_all(deps.iter().map(|(dep_type, name, optional)| async move {
+            let namespace = self.scope(dep_type);
+            if !optional {
+                return self
+                    .link(uid, dep_type.clone(), name, namespace, "uses")
+                    .await;
+            }
+            let object = self.get(dep_type, name, namespace).await?;
+            Relation::to(uid, dep_type.clone(), object, "uses")
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Pod -> the ServiceAccount it runs as, and on to what that account is bound to
+    async fn pod_service_account(&self, uid: &str, pod: &Value) -> Vec<Relation> {
+        let (Some(account), Some(namespace)) =
+            (pod_service_account(pod), self.namespace.as_deref())
+        else {
+            return Vec::new();
+        };
+        let Some(relation) = self
+            .link(
+                uid,
+                K8sResourceType::ServiceAccount,
+                account,
+                Some(namespace.to_string()),
+                "runs as",
+            )
+            .await
+        else {
+            return Vec::new();
+        };
+        if relation.error.is_some() {
+            return vec![relation];
+        }
+        let bindings = self
+            .subject_bindings(&relation.target, account, namespace)
+            .await;
+        std::iter::once(relation).chain(bindings).collect()
+    }
+
+    /// RoleBindings and ClusterRoleBindings naming this ServiceAccount as a subject -> it,
+    /// each followed by the role it grants
+    async fn subject_bindings(&self, uid: &str, name: &str, namespace: &str) -> Vec<Relation> {
+        let (role_bindings, cluster_role_bindings) = tokio::join!(
+            self.listed(&K8sResourceType::RoleBinding, None),
+            self.listed(&K8sResourceType::ClusterRoleBinding, None)
+        );
+        let matching: Vec<(K8sResourceType, &Value)> = role_bindings
+            .iter()
+            .map(|b| (K8sResourceType::RoleBinding, b))
+            .chain(
+                cluster_role_bindings
+                    .iter()
+                    .map(|b| (K8sResourceType::ClusterRoleBinding, b)),
+            )
+            .filter(|(_, binding)| {
+                binding_subjects(binding)
+                    .iter()
+                    .any(|(subject, subject_ns)| subject == name && subject_ns == namespace)
+            })
+            .collect();
+
+        let roles = join_all(matching.iter().map(|(_, binding)| {
+            let binding_uid = uid_of(binding).unwrap_or_default();
+            self.role_ref(binding_uid, binding)
+        }))
+        .await;
+
+        let mut relations = Vec::new();
+        for ((binding_type, binding), role) in matching.into_iter().zip(roles) {
+            relations.extend(Relation::from(binding.clone(), binding_type, uid, "binds"));
+            relations.extend(role);
+        }
+        relations
+    }
+
+    /// RoleBinding/ClusterRoleBinding -> the ServiceAccounts among its subjects
+    async fn binding_subjects(&self, uid: &str, binding: &Value) -> Vec<Relation> {
+        let subjects = binding_subjects(binding);
+        join_all(subjects.iter().map(|(name, namespace)| {
+            self.link(
+                uid,
+                K8sResourceType::ServiceAccount,
+                name,
+                Some(namespace.clone()),
+                "binds",
+            )
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// RoleBinding/ClusterRoleBinding -> the Role or ClusterRole in its roleRef
+    async fn role_ref(&self, uid: &str, binding: &Value) -> Vec<Relation> {
+        let kind = binding.pointer("/roleRef/kind").and_then(|k| k.as_str());
+        let Some(name) = binding.pointer("/roleRef/name").and_then(|n| n.as_str()) else {
+            return Vec::new();
+        };
+        let (role_type, namespace) = match kind {
+            Some("Role") => (
+                K8sResourceType::Role,
+                binding
+                    .pointer("/metadata/namespace")
+                    .and_then(|n| n.as_str())
+                    .map(String::from),
+            ),
+            Some("ClusterRole") => (K8sResourceType::ClusterRole, None),
+            _ => return Vec::new(),
+        };
+        self.link(uid, role_type, name, namespace, "grants")
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Bindings whose roleRef points at this Role or ClusterRole -> it
+    async fn role_bindings(
+        &self,
+        uid: &str,
+        role_type: &K8sResourceType,
+        name: &str,
+    ) -> Vec<Relation> {
+        let (kind, binding_types, namespace) = match role_type {
+            K8sResourceType::Role => (
+                "Role",
+                vec![K8sResourceType::RoleBinding],
+                self.namespace.as_deref(),
+            ),
+            _ => (
+                "ClusterRole",
+                vec![
+                    K8sResourceType::RoleBinding,
+                    K8sResourceType::ClusterRoleBinding,
+                ],
+                None,
+            ),
+        };
+        let lists = join_all(
+            binding_types
+                .iter()
+                .map(|binding_type| self.listed(binding_type, namespace)),
+        )
+        .await;
+
+        let mut relations = Vec::new();
+        for (binding_type, bindings) in binding_types.into_iter().zip(lists) {
+            for binding in bindings.iter() {
+                let references = binding.pointer("/roleRef/kind").and_then(|k| k.as_str())
+                    == Some(kind)
+                    && binding.pointer("/roleRef/name").and_then(|n| n.as_str()) == Some(name);
+                if references {
+                    relations.extend(Relation::from(
+                        binding.clone(),
+                        binding_type.clone(),
+                        uid,
+                        "grants",
+                    ));
+                }
+            }
+        }
+        relations
+    }
+
+    /// PVC -> StorageClass and the PV it is bound to
+    async fn claim_storage(&self, uid: &str, claim: &Value) -> Vec<Relation> {
+        let class = claim
+            .pointer("/spec/storageClassName")
+            .and_then(|s| s.as_str());
+        let volume = claim.pointer("/spec/volumeName").and_then(|s| s.as_str());
+        let (class, volume) = tokio::join!(
+            async {
+                match class {
+                    Some(class) => {
+                        self.link(uid, K8sResourceType::StorageClass, class, None, "uses")
+                            .await
+                    }
+                    None => None,
+                }
+            },
+            async {
+                match volume {
+                    Some(volume) => {
+                        self.link(
+                            uid,
+                            K8sResourceType::PersistentVolume,
+                            volume,
+                            None,
+                            "bound",
+                        )
+                        .await
+                    }
+                    None => None,
+                }
+            }
+        );
+        class.into_iter().chain(volume).collect()
+    }
+
+    /// PV -> the PVC in its claimRef
+    async fn bound_claim(&self, uid: &str, volume: &Value) -> Vec<Relation> {
+        let claim_ref = volume.pointer("/spec/claimRef");
+        let name = claim_ref
+            .and_then(|c| c.get("name"))
+            .and_then(|n| n.as_str());
+        let namespace = claim_ref
+            .and_then(|c| c.get("namespace"))
+            .and_then(|n| n.as_str());
+        let (Some(name), Some(namespace)) = (name, namespace) else {
+            return Vec::new();
+        };
+        self.link(
+            uid,
+            K8sResourceType::PersistentVolumeClaim,
+            name,
+            Some(namespace.to_string()),
+            "bound",
+        )
+        .await
+        .into_iter()
+        .collect()
+    }
+
+    /// Pods that mount or read env from this ConfigMap, Secret or PVC -> it
+    async fn dependent_pods(
+        &self,
+        uid: &str,
+        resource_type: &K8sResourceType,
+        name: &str,
+    ) -> Vec<Relation> {
+        let Some(ns) = self.namespace.as_deref() else {
+            return Vec::new();
+        };
+        let pods = self.listed(&K8sResourceType::Pod, Some(ns)).await;
+        pods.iter()
+            .filter(|pod| {
+                pod_dependencies(pod)
+                    .iter()
+                    .any(|(dep_type, dep_name, _)| dep_type == resource_type && dep_name == name)
+            })
+            .filter_map(|pod| Relation::from(pod.clone(), K8sResourceType::Pod, uid, "uses"))
+            .collect()
+    }
+
+    /// PVCs requesting this StorageClass -> it
+    async fn class_claims(&self, uid: &str, name: &str) -> Vec<Relation> {
+        let claims = self
+            .listed(&K8sResourceType::PersistentVolumeClaim, None)
+            .await;
+        claims
+            .iter()
+            .filter(|claim| {
+                claim
+                    .pointer("/spec/storageClassName")
+                    .and_then(|s| s.as_str())
+                    == Some(name)
+            })
+            .filter_map(|claim| {
+                Relation::from(
+                    claim.clone(),
+                    K8sResourceType::PersistentVolumeClaim,
+                    uid,
+                    "uses",
+                )
+            })
+            .collect()
+    }
+}
+
+/// Collects graph nodes and edges, keeping every object (by UID) and every edge only once
+pub(crate) struct GraphBuilder {
+    detail: GraphDetail,
+    /// Pods of the graph's namespace, which decide whether a Service has ready endpoints,
+    /// or why they couldn't be listed
+    pods: Listing,
+    graph: GraphData,
+    node_ids: HashSet<String>,
+    edge_ids: HashSet<String>,
+}
+
+impl GraphBuilder {
+    pub(crate) fn new(detail: GraphDetail, pods: Listing) -> Self {
+        Self {
+            detail,
+            pods,
+            graph: GraphData {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                stats: None,
+            },
+            node_ids: HashSet::new(),
+            edge_ids: HashSet::new(),
+        }
+    }
+
+    /// Nodes keep their whole object until here, so usage can be joined with pod specs
+    /// before a summary drops them
+    pub(crate) fn finish(mut self, stats: GraphStats) -> GraphData {
+        if self.detail == GraphDetail::Summary {
+            for node in &mut self.graph.nodes {
+                // Group nodes are not a kind and carry no object
+                if let Some(kind) = K8sResourceType::from_kind(&node.resource_type) {
+                    node.data = summarize(&kind, &node.data);
+                }
+            }
+        }
+        tracing::info!(
+            nodes = self.graph.nodes.len(),
+            edges = self.graph.edges.len(),
+            duration_ms = stats.duration_ms,
+            api_calls = stats.api_calls,
+            cache_hits = stats.cache_hits,
+            "built resource graph"
+        );
+        self.graph.stats = Some(stats);
+        self.graph
+    }
+
+    /// Adds a node unless its UID is already present. Returns whether it was new.
+    fn add_node(&mut self, resource_type: &K8sResourceType, data: Value) -> bool {
+        let health = match (&self.pods, resource_type) {
+            (Err(error), K8sResourceType::Service) => {
+                Some(NodeHealth::new(HealthStatus::Unknown, error.as_str()))
+            }
+            (pods, _) => node_health(
+                resource_type,
+                &data,
+                pods.as_ref().map(|p| p.as_slice()).unwrap_or_default(),
+            ),
+        };
+        self.insert_node(resource_type, data, health)
+    }
+
+    fn insert_node(
+        &mut self,
+        resource_type: &K8sResourceType,
+        data: Value,
+        health: Option<NodeHealth>,
+    ) -> bool {
+        let Some(uid) = uid_of(&data).map(String::from) else {
+            return false;
+        };
+        if !self.node_ids.insert(uid.clone()) {
+            return false;
+        }
+        let label = name_of(&data).unwrap_or_default().to_string();
+        self.graph.nodes.push(GraphNode {
+            id: uid,
+            label,
+            resource_type: format!("{:?}", resource_type),
+            data,
+            health,
+            usage: None,
+        });
+        true
+    }
+
+    fn add_edge(&mut self, source: &str, target: &str, label: &str) {
+        self.insert_edge(source, target, label, None);
+    }
+
+    fn insert_edge(&mut self, source: &str, target: &str, label: &str, error: Option<String>) {
+        let id = format!("{}-{}", source, target);
+        if self.edge_ids.insert(id.clone()) {
+            self.graph.edges.push(GraphEdge {
+                id,
+                source: source.to_string(),
+                target: target.to_string(),
+                label: label.to_string(),
+                error,
+            });
+        }
+    }
+
+    fn apply(&mut self, relations: impl IntoIterator<Item = Relation>) {
+        for relation in relations {
+            match (relation.node, &relation.error) {
+                (Some((resource_type, placeholder)), Some(error)) => {
+                    let health = NodeHealth::new(HealthStatus::Error, error.as_str());
+                    self.insert_node(&resource_type, placeholder, Some(health));
+                }
+                (Some((resource_type, object)), None) => {
+                    self.add_node(&resource_type, object);
+                }
+                (None, _) => {}
+            }
+            self.insert_edge(
+                &relation.source,
+                &relation.target,
+                &relation.label,
+                relation.error,
+            );
+        }
+    }
+
+    /// Replaces objects that ended up without any edge by one grouping node per kind
+    fn collapse_unconnected(&mut self) {
+        let connected: HashSet<String> = self
+            .graph
+            .edges
+            .iter()
+            .flat_map(|e| [e.source.clone(), e.target.clone()])
+            .collect();
+
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        self.graph.nodes.retain(|node| {
+            if connected.contains(&node.id) {
+                return true;
+            }
+            match groups
+                .iter_mut()
+                .find(|(kind, _)| *kind == node.resource_type)
+            {
+                Some((_, members)) => members.push(node.label.clone()),
+                None => groups.push((node.resource_type.clone(), vec![node.label.clone()])),
+            }
+            false
+        });
+
+        for (kind, members) in groups {
+            self.graph.nodes.push(GraphNode {
+                id: format!("group-{}", kind),
+                label: format!("{} ({})", kind, members.len()),
+                resource_type: "Group".to_string(),
+                data: serde_json::json!({ "kind": kind, "members": members }),
+                health: None,
+                usage: None,
+            });
+        }
+    }
+}
+
+impl K8sClient {
+    /// Helper to get resource graph using a provided client, exposed for testing
+    pub(crate) async fn get_resource_graph_with_client(
+        client: Client,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+        options: GraphOptions,
+    ) -> Result<GraphData, Box<dyn Error + Send + Sync>> {
+        let started = Instant::now();
+        let resource = Self::get_resource_with_client(
+            client.clone(),
+            resource_type.clone(),
+            name,
+            namespace.clone(),
+        )
+        .await?;
+        uid_of(&resource).ok_or("Missing UID")?;
+
+        let namespace = resource
+            .pointer("/metadata/namespace")
+            .and_then(|n| n.as_str())
+            .map(String::from)
+            .or(namespace);
+        let depth = options.depth.min(MAX_GRAPH_DEPTH);
+
+        let fetcher = GraphFetcher::new(client.clone(), namespace);
+        let (owners, children, related) = tokio::join!(
+            fetcher.owner_chain(&resource, depth),
+            fetcher.descendants(&resource_type, &resource, depth),
+            fetcher.related(&resource_type, &resource)
+        );
+
+        // Services get their health from the pods behind them
+        let has_service = resource_type == K8sResourceType::Service
+            || [&owners, &children, &related]
+                .into_iter()
+                .flatten()
+                .any(|r| matches!(r.node, Some((K8sResourceType::Service, _))));
+        let pods = match (has_service, fetcher.namespace.as_deref()) {
+            (true, Some(ns)) => fetcher.list(&K8sResourceType::Pod, Some(ns)).await,
+            _ => Ok(Arc::default()),
+        };
+
+        let mut graph = GraphBuilder::new(options.detail, pods);
+        graph.add_node(&resource_type, resource);
+        graph.apply(owners);
+        graph.apply(children);
+        graph.apply(related);
+
+        let mut stats = fetcher.stats(started);
+        // The requested object itself is fetched before the fetcher exists
+        stats.api_calls += 1;
+        if options.usage {
+            Self::attach_graph_usage_with_client(client, &mut graph.graph).await;
+        }
+        Ok(graph.finish(stats))
+    }
+
+    /// Helper to get the topology of a whole namespace using a provided client, exposed for testing.
+    /// Objects are listed per kind and linked with the same rules as the single-resource graph.
+    pub(crate) async fn get_namespace_graph_with_client(
+        client: Client,
+        namespace: &str,
+        options: NamespaceGraphOptions,
+    ) -> Result<GraphData, Box<dyn Error + Send + Sync>> {
+        let started = Instant::now();
+        let fetcher = GraphFetcher::new(client.clone(), Some(namespace.to_string()));
+
+        let kinds: Vec<&K8sResourceType> = TOPOLOGY_KINDS
+            .iter()
+            .filter(|kind| !options.exclude.contains(kind))
+            .collect();
+        let lists = join_all(kinds.iter().map(|kind| fetcher.list(kind, Some(namespace)))).await;
+        let mut objects: Vec<(K8sResourceType, Value)> = Vec::new();
+        let mut unreadable: HashMap<K8sResourceType, String> = HashMap::new();
+        for (kind, list) in kinds.into_iter().zip(lists) {
+            match list {
+                Ok(items) => objects.extend(items.iter().map(|item| (kind.clone(), item.clone()))),
+                Err(error) => {
+                    tracing::warn!("Namespace graph of {}: {}", namespace, error);
+                    unreadable.insert(kind.clone(), error);
+                }
+            }
+        }
+
+        // Kinds left out or unreadable can't show that a reference is broken
+        let listed = |kind: &K8sResourceType| {
+            !options.exclude.contains(kind) && !unreadable.contains_key(kind)
+        };
+        let pods = match unreadable.get(&K8sResourceType::Pod) {
+            Some(error) => Err(error.clone()),
+            None if !options.exclude.contains(&K8sResourceType::Pod) => Ok(Arc::new(
+                objects
+                    .iter()
+                    .filter(|(kind, _)| *kind == K8sResourceType::Pod)
+                    .map(|(_, pod)| pod.clone())
+                    .collect(),
+            )),
+            None if !options.exclude.contains(&K8sResourceType::Service) => {
+                fetcher.list(&K8sResourceType::Pod, Some(namespace)).await
+            }
+            None => Ok(Arc::default()),
+        };
+
+        let mut graph = GraphBuilder::new(options.detail, pods);
+        let mut by_name: HashMap<(K8sResourceType, String), String> = HashMap::new();
+        for (kind, object) in &objects {
+            if let (Some(name), Some(uid)) = (name_of(object), uid_of(object)) {
+                by_name.insert((kind.clone(), name.to_string()), uid.to_string());
+            }
+            graph.add_node(kind, object.clone());
+        }
+        let lookup = |kind: K8sResourceType, name: &str| by_name.get(&(kind, name.to_string()));
+        // A reference is only known to be broken when its kind was listed
+        let missing = |anchor: &str, kind: K8sResourceType, name: &str, label: &str| {
+            listed(&kind).then(|| {
+                let error = format!("{:?} {} not found", kind, name);
+                Relation::broken(anchor, kind, name, Some(namespace), label, error)
+            })
+        };
+
+        for (kind, object) in &objects {
+            let Some(uid) = uid_of(object) else {
+                continue;
+            };
+            for owner in owner_refs(object) {
+                if graph.node_ids.contains(&owner.uid) {
+                    graph.add_edge(&owner.uid, uid, "manages");
+                }
+            }
+
+            match kind {
+                K8sResourceType::Service => {
+                    let Some(selector) =
+                        object.pointer("/spec/selector").and_then(|s| s.as_object())
+                    else {
+                        continue;
+                    };
+                    let mut selected = false;
+                    for (_, pod) in objects.iter().filter(|(k, _)| *k == K8sResourceType::Pod) {
+                        let labels = pod.pointer("/metadata/labels").and_then(|l| l.as_object());
+                        if let (Some(labels), Some(pod_uid)) = (labels, uid_of(pod)) {
+                            if selector_matches(selector, labels) {
+                                graph.add_edge(uid, pod_uid, "selects");
+                                selected = true;
+                            }
+                        }
+                    }
+                    if !selected && !selector.is_empty() && listed(&K8sResourceType::Pod) {
+                        let selector = selector_string(selector);
+                        let error = format!("selector {} matches no pods", selector);
+                        graph.apply(vec![Relation::broken(
+                            uid,
+                            K8sResourceType::Pod,
+                            &selector,
+                            Some(namespace),
+                            "selects",
+                            error,
+                        )]);
+                    }
+                }
+                K8sResourceType::Ingress => {
+                    for (service, route) in ingress_routes(object) {
+                        match lookup(K8sResourceType::Service, &service) {
+                            Some(target) => graph.add_edge(uid, target, &route),
+                            None => graph.apply(missing(
+                                uid,
+                                K8sResourceType::Service,
+                                &service,
+                                &route,
+                            )),
+                        }
+                    }
+                    for (secret, label) in ingress_tls_secrets(object) {
+                        match lookup(K8sResourceType::Secret, &secret) {
+                            Some(target) => graph.add_edge(uid, target, &label),
+                            None => {
+                                graph.apply(missing(uid, K8sResourceType::Secret, &secret, &label))
+                            }
+                        }
+                    }
+                }
+                K8sResourceType::Pod => {
+                    for (dep_type, dep_name, optional) in pod_dependencies(object) {
+                        match lookup(dep_type.clone(), &dep_name) {
+                            Some(target) => graph.add_edge(uid, target, "uses"),
+                            None if optional => {}
+                            None => graph.apply(missing(uid, dep_type, &dep_name, "uses")),
+                        }
+                    }
+                    if let Some(account) = pod_service_account(object) {
+                        match lookup(K8sResourceType::ServiceAccount, account) {
+                            Some(target) => graph.add_edge(uid, target, "runs as"),
+                            None => graph.apply(missing(
+                                uid,
+                                K8sResourceType::ServiceAccount,
+                                account,
+                                "runs as",
+                            )),
+                        }
+                    }
+                }
+                K8sResourceType::StatefulSet => {
+                    if let Some(service) = governing_service(object) {
+                        match lookup(K8sResourceType::Service, service) {
+                            Some(target) => graph.add_edge(uid, target, "governed by"),
+                            None => graph.apply(missing(
+                                uid,
+                                K8sResourceType::Service,
+                                service,
+                                "governed by",
+                            )),
+                        }
+                    }
+                    let name = name_of(object).unwrap_or_default();
+                    let templates = claim_templates(object);
+                    let claims = objects
+                        .iter()
+                        .filter(|(k, _)| *k == K8sResourceType::PersistentVolumeClaim);
+                    for (_, claim) in claims {
+                        let claim_name = name_of(claim).unwrap_or_default();
+                        if templates
+                            .iter()
+                            .any(|template| is_template_claim(claim_name, template, name))
+                        {
+                            if let Some(claim_uid) = uid_of(claim) {
+                                graph.add_edge(uid, claim_uid, "claims");
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if options.collapse_unrelated {
+            graph.collapse_unconnected();
+        }
+        if options.usage {
+            Self::attach_graph_usage_with_client(client, &mut graph.graph).await;
+        }
+        Ok(graph.finish(fetcher.stats(started)))
+    }
+}
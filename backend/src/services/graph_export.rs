@@ -0,0 +1,153 @@
+use crate::models::{GraphData, GraphFormat};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Renders a graph for use outside the UI. Only kinds, labels and edge labels are kept;
+/// the embedded objects are left out. Returns the content type and the document.
+pub fn render_graph(graph: &GraphData, format: &GraphFormat) -> (&'static str, String) {
+    match format {
+        GraphFormat::Json => (
+            "application/json",
+            serde_json::to_string(graph).unwrap_or_default(),
+        ),
+        GraphFormat::Dot => ("text/vnd.graphviz", to_dot(graph)),
+        GraphFormat::Mermaid => ("text/plain; charset=utf-8", to_mermaid(graph)),
+        GraphFormat::Graphml => ("application/graphml+xml", to_graphml(graph)),
+        GraphFormat::Cytoscape => ("application/json", to_cytoscape(graph)),
+    }
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn to_dot(graph: &GraphData) -> String {
+    let mut out = String::from("digraph resources {\n  rankdir=TB;\n  node [shape=box];\n");
+    for node in &graph.nodes {
+        let _ = writeln!(
+            out,
+            "  \"{}\" [label=\"{}\\n({})\", kind=\"{}\"];",
+            dot_escape(&node.id),
+            dot_escape(&node.label),
+            dot_escape(&node.resource_type),
+            dot_escape(&node.resource_type)
+        );
+    }
+    for edge in &graph.edges {
+        let _ = writeln!(
+            out,
+            "  \"{}\" -> \"{}\" [label=\"{}\"];",
+            dot_escape(&edge.source),
+            dot_escape(&edge.target),
+            dot_escape(&edge.label)
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Mermaid labels can't hold quotes or pipes verbatim; it understands HTML entity codes
+fn mermaid_escape(value: &str) -> String {
+    value.replace('"', "#quot;").replace('|', "#124;")
+}
+
+fn to_mermaid(graph: &GraphData) -> String {
+    // UIDs aren't valid Mermaid identifiers, so nodes are numbered instead
+    let mut ids: HashMap<String, String> = HashMap::new();
+    let mut id_for = |uid: &str| -> String {
+        let next = format!("n{}", ids.len());
+        ids.entry(uid.to_string()).or_insert(next).clone()
+    };
+
+    let mut out = String::from("flowchart TD\n");
+    for node in &graph.nodes {
+        let _ = writeln!(
+            out,
+            "  {}[\"{}: {}\"]",
+            id_for(&node.id),
+            mermaid_escape(&node.resource_type),
+            mermaid_escape(&node.label)
+        );
+    }
+    for edge in &graph.edges {
+        let (source, target) = (id_for(&edge.source), id_for(&edge.target));
+        if edge.label.is_empty() {
+            let _ = writeln!(out, "  {} --> {}", source, target);
+        } else {
+            let _ = writeln!(
+                out,
+                "  {} -->|\"{}\"| {}",
+                source,
+                mermaid_escape(&edge.label),
+                target
+            );
+        }
+    }
+    out
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn to_graphml(graph: &GraphData) -> String {
+    let mut out = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+        "  <key id=\"edge_label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        "  <graph id=\"resources\" edgedefault=\"directed\">\n",
+    ));
+    for node in &graph.nodes {
+        let _ = writeln!(
+            out,
+            "    <node id=\"{}\"><data key=\"label\">{}</data><data key=\"kind\">{}</data></node>",
+            xml_escape(&node.id),
+            xml_escape(&node.label),
+            xml_escape(&node.resource_type)
+        );
+    }
+    for edge in &graph.edges {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"{}\" source=\"{}\" target=\"{}\"><data key=\"edge_label\">{}</data></edge>",
+            xml_escape(&edge.id),
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            xml_escape(&edge.label)
+        );
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// Cytoscape.js `elements` JSON, which Cytoscape desktop imports as well
+fn to_cytoscape(graph: &GraphData) -> String {
+    let nodes: Vec<_> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            json!({ "data": { "id": node.id, "label": node.label, "kind": node.resource_type } })
+        })
+        .collect();
+    let edges: Vec<_> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            json!({ "data": {
+                "id": edge.id,
+                "source": edge.source,
+                "target": edge.target,
+                "label": edge.label,
+            } })
+        })
+        .collect();
+    json!({ "elements": { "nodes": nodes, "edges": edges } }).to_string()
+}
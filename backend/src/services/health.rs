@@ -0,0 +1,225 @@
+use super::graph::selector_matches;
+use crate::models::{HealthStatus, K8sResourceType, NodeHealth};
+use serde_json::Value;
+
+/// Container waiting reasons that won't clear up without someone changing something
+const FAILING_WAIT_REASONS: [&str; 6] = [
+    "CrashLoopBackOff",
+    "ImagePullBackOff",
+    "ErrImagePull",
+    "InvalidImageName",
+    "CreateContainerConfigError",
+    "CreateContainerError",
+];
+
+/// Computes a normalized health status for an object. `pods` are the pods of the object's
+/// namespace; they back a Service's endpoints. Kinds without a meaningful health get `None`.
+pub(crate) fn node_health(
+    resource_type: &K8sResourceType,
+    resource: &Value,
+    pods: &[Value],
+) -> Option<NodeHealth> {
+    match resource_type {
+        K8sResourceType::Pod => Some(pod_health(resource)),
+        K8sResourceType::Deployment => Some(replica_health(
+            resource,
+            "/status/availableReplicas",
+            "available",
+        )),
+        K8sResourceType::StatefulSet | K8sResourceType::ReplicaSet => {
+            Some(replica_health(resource, "/status/readyReplicas", "ready"))
+        }
+        K8sResourceType::DaemonSet => Some(daemon_set_health(resource)),
+        K8sResourceType::Job => Some(job_health(resource)),
+        K8sResourceType::PersistentVolumeClaim => Some(claim_health(resource)),
+        K8sResourceType::PersistentVolume => Some(volume_health(resource)),
+        K8sResourceType::Service => Some(service_health(resource, pods)),
+        K8sResourceType::Node => Some(if condition_is(resource, "Ready", "True") {
+            NodeHealth::new(HealthStatus::Healthy, "Ready")
+        } else {
+            NodeHealth::new(HealthStatus::Error, "NotReady")
+        }),
+        _ => None,
+    }
+}
+
+fn str_at<'a>(resource: &'a Value, pointer: &str) -> Option<&'a str> {
+    resource.pointer(pointer).and_then(|v| v.as_str())
+}
+
+fn count_at(resource: &Value, pointer: &str) -> Option<i64> {
+    resource.pointer(pointer).and_then(|v| v.as_i64())
+}
+
+fn find_condition<'a>(resource: &'a Value, condition: &str) -> Option<&'a Value> {
+    resource
+        .pointer("/status/conditions")
+        .and_then(|c| c.as_array())?
+        .iter()
+        .find(|c| c.get("type").and_then(|t| t.as_str()) == Some(condition))
+}
+
+fn condition_is(resource: &Value, condition: &str, status: &str) -> bool {
+    find_condition(resource, condition)
+        .and_then(|c| c.get("status"))
+        .and_then(|s| s.as_str())
+        == Some(status)
+}
+
+/// Whether the pod's Ready condition is true
+pub(crate) fn pod_is_ready(pod: &Value) -> bool {
+    condition_is(pod, "Ready", "True")
+}
+
+fn pod_health(pod: &Value) -> NodeHealth {
+    let phase = str_at(pod, "/status/phase").unwrap_or("Unknown");
+    match phase {
+        "Succeeded" => return NodeHealth::new(HealthStatus::Healthy, "Completed"),
+        "Failed" => {
+            let reason = str_at(pod, "/status/reason").unwrap_or("Failed");
+            return NodeHealth::new(HealthStatus::Error, reason);
+        }
+        _ => {}
+    }
+
+    let statuses = ["/status/initContainerStatuses", "/status/containerStatuses"]
+        .iter()
+        .filter_map(|pointer| pod.pointer(pointer).and_then(|s| s.as_array()))
+        .flatten();
+    for status in statuses {
+        let reason = status
+            .pointer("/state/waiting/reason")
+            .and_then(|r| r.as_str());
+        if let Some(reason) = reason.filter(|r| FAILING_WAIT_REASONS.contains(r)) {
+            let container = status.get("name").and_then(|n| n.as_str()).unwrap_or("");
+            return NodeHealth::new(HealthStatus::Error, format!("{}: {}", container, reason));
+        }
+    }
+
+    match phase {
+        "Pending" => {
+            let unschedulable = find_condition(pod, "PodScheduled")
+                .filter(|c| c.get("status").and_then(|s| s.as_str()) == Some("False"));
+            match unschedulable {
+                Some(condition) => {
+                    let message = condition
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("Unschedulable");
+                    NodeHealth::new(HealthStatus::Degraded, message)
+                }
+                None => NodeHealth::new(HealthStatus::Progressing, "Pending"),
+            }
+        }
+        "Running" if pod_is_ready(pod) => NodeHealth::new(HealthStatus::Healthy, "Ready"),
+        "Running" => NodeHealth::new(HealthStatus::Degraded, "Running, not ready"),
+        _ => NodeHealth::new(HealthStatus::Unknown, phase),
+    }
+}
+
+/// Deployments, StatefulSets and ReplicaSets: `field` replicas against spec.replicas
+fn replica_health(resource: &Value, field: &str, word: &str) -> NodeHealth {
+    let desired = count_at(resource, "/spec/replicas").unwrap_or(1);
+    let current = count_at(resource, field).unwrap_or(0);
+    let message = format!("{}/{} {}", current, desired, word);
+    if desired == 0 {
+        NodeHealth::new(HealthStatus::Healthy, "Scaled to zero")
+    } else if current >= desired {
+        NodeHealth::new(HealthStatus::Healthy, message)
+    } else if current == 0 {
+        NodeHealth::new(HealthStatus::Error, message)
+    } else {
+        NodeHealth::new(HealthStatus::Degraded, message)
+    }
+}
+
+fn daemon_set_health(daemon_set: &Value) -> NodeHealth {
+    let desired = count_at(daemon_set, "/status/desiredNumberScheduled").unwrap_or(0);
+    let available = count_at(daemon_set, "/status/numberAvailable").unwrap_or(0);
+    let message = format!("{}/{} available", available, desired);
+    if available >= desired {
+        NodeHealth::new(HealthStatus::Healthy, message)
+    } else if available == 0 {
+        NodeHealth::new(HealthStatus::Error, message)
+    } else {
+        NodeHealth::new(HealthStatus::Degraded, message)
+    }
+}
+
+fn job_health(job: &Value) -> NodeHealth {
+    if condition_is(job, "Complete", "True") {
+        NodeHealth::new(HealthStatus::Healthy, "Complete")
+    } else if condition_is(job, "Failed", "True") {
+        let reason = find_condition(job, "Failed")
+            .and_then(|c| c.get("reason"))
+            .and_then(|r| r.as_str())
+            .unwrap_or("Failed");
+        NodeHealth::new(HealthStatus::Error, reason)
+    } else if job.pointer("/spec/suspend").and_then(|s| s.as_bool()) == Some(true) {
+        NodeHealth::new(HealthStatus::Unknown, "Suspended")
+    } else {
+        NodeHealth::new(HealthStatus::Progressing, "Running")
+    }
+}
+
+fn claim_health(claim: &Value) -> NodeHealth {
+    match str_at(claim, "/status/phase") {
+        Some("Bound") => NodeHealth::new(HealthStatus::Healthy, "Bound"),
+        Some("Lost") => NodeHealth::new(HealthStatus::Error, "Lost"),
+        Some(phase) => NodeHealth::new(HealthStatus::Progressing, phase),
+        None => NodeHealth::new(HealthStatus::Unknown, "Unknown"),
+    }
+}
+
+fn volume_health(volume: &Value) -> NodeHealth {
+    match str_at(volume, "/status/phase") {
+        Some(phase @ ("Bound" | "Available")) => NodeHealth::new(HealthStatus::Healthy, phase),
+        Some("Released") => NodeHealth::new(HealthStatus::Degraded, "Released"),
+        Some("Failed") => NodeHealth::new(HealthStatus::Error, "Failed"),
+        Some(phase) => NodeHealth::new(HealthStatus::Progressing, phase),
+        None => NodeHealth::new(HealthStatus::Unknown, "Unknown"),
+    }
+}
+
+/// A Service is healthy when at least one pod behind its selector is ready
+fn service_health(service: &Value, pods: &[Value]) -> NodeHealth {
+    if str_at(service, "/spec/type") == Some("ExternalName") {
+        return NodeHealth::new(HealthStatus::Healthy, "ExternalName");
+    }
+    let selector = service
+        .pointer("/spec/selector")
+        .and_then(|s| s.as_object())
+        .filter(|s| !s.is_empty());
+    let Some(selector) = selector else {
+        // Endpoints are managed by hand or by another controller
+        return NodeHealth::new(HealthStatus::Unknown, "No selector");
+    };
+    let namespace = str_at(service, "/metadata/namespace");
+
+    let backing: Vec<&Value> = pods
+        .iter()
+        .filter(|pod| str_at(pod, "/metadata/namespace") == namespace)
+        .filter(|pod| {
+            pod.pointer("/metadata/labels")
+                .and_then(|l| l.as_object())
+                .is_some_and(|labels| selector_matches(selector, labels))
+        })
+        .collect();
+    let ready = backing.iter().filter(|pod| pod_is_ready(pod)).count();
+
+    if backing.is_empty() {
+        NodeHealth::new(HealthStatus::Error, "Selector matches no pods")
+    } else if ready == 0 {
+        NodeHealth::new(HealthStatus::Error, "No ready endpoints")
+    } else if ready < backing.len() {
+        NodeHealth::new(
+            HealthStatus::Degraded,
+            format!("{}/{} endpoints ready", ready, backing.len()),
+        )
+    } else {
+        NodeHealth::new(
+            HealthStatus::Healthy,
+            format!("{}/{} endpoints ready", ready, backing.len()),
+        )
+    }
+}
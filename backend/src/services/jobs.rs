@@ -0,0 +1,188 @@
+use super::k8s::K8sClient;
+use k8s_openapi::api::batch::v1::{CronJob, Job};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference};
+use k8s_openapi::Resource;
+use kube::api::{Patch, PatchParams, PostParams};
+use kube::{Api, Client};
+use std::collections::BTreeMap;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Same annotation `kubectl create job --from=cronjob/...` sets
+const INSTANTIATE_ANNOTATION: &str = "cronjob.kubernetes.io/instantiate";
+
+/// Labels the Job controller stamps on pods and selectors; a cloned Job must not carry them
+const CONTROLLER_LABELS: [&str; 4] = [
+    "controller-uid",
+    "batch.kubernetes.io/controller-uid",
+    "job-name",
+    "batch.kubernetes.io/job-name",
+];
+
+/// Job names end up in pod labels, which cap values at 63 characters
+const MAX_JOB_NAME_LEN: usize = 63;
+
+impl K8sClient {
+    /// Builds `<base>-<infix>-<suffix>` while keeping it a valid Job name
+    pub(crate) fn derived_job_name(base: &str, infix: &str, suffix: &str) -> String {
+        let budget = MAX_JOB_NAME_LEN - infix.len() - suffix.len() - 2;
+        let base = &base[..base.len().min(budget)];
+        format!("{}-{}-{}", base.trim_end_matches('-'), infix, suffix)
+    }
+
+    /// Builds the Job a manual "run now" of a CronJob creates
+    pub(crate) fn job_from_cronjob(
+        cronjob: &CronJob,
+        suffix: &str,
+    ) -> Result<Job, Box<dyn Error + Send + Sync>> {
+        let name = cronjob
+            .metadata
+            .name
+            .as_deref()
+            .ok_or("CronJob has no name")?;
+        let uid = cronjob.metadata.uid.clone().ok_or("CronJob has no UID")?;
+        let template = cronjob
+            .spec
+            .as_ref()
+            .map(|s| s.job_template.clone())
+            .ok_or("CronJob has no spec")?;
+        let template_meta = template.metadata.unwrap_or_default();
+
+        let mut annotations = template_meta.annotations.unwrap_or_default();
+        annotations.insert(INSTANTIATE_ANNOTATION.to_string(), "manual".to_string());
+
+        Ok(Job {
+            metadata: ObjectMeta {
+                name: Some(Self::derived_job_name(name, "manual", suffix)),
+                namespace: cronjob.metadata.namespace.clone(),
+                labels: template_meta.labels,
+                annotations: Some(annotations),
+                owner_references: Some(vec![OwnerReference {
+                    api_version: CronJob::API_VERSION.to_string(),
+                    kind: CronJob::KIND.to_string(),
+                    name: name.to_string(),
+                    uid,
+                    controller: Some(true),
+                    block_owner_deletion: Some(true),
+                }]),
+                ..Default::default()
+            },
+            spec: template.spec,
+            ..Default::default()
+        })
+    }
+
+    /// Clones a finished Job so it can run again, dropping what the Job controller generated
+    pub(crate) fn job_for_rerun(
+        job: &Job,
+        suffix: &str,
+    ) -> Result<Job, Box<dyn Error + Send + Sync>> {
+        let name = job.metadata.name.as_deref().ok_or("Job has no name")?;
+
+        let finished = job
+            .status
+            .as_ref()
+            .and_then(|s| s.conditions.as_ref())
+            .is_some_and(|conditions| {
+                conditions
+                    .iter()
+                    .any(|c| (c.type_ == "Complete" || c.type_ == "Failed") && c.status == "True")
+            });
+        if !finished {
+            return Err(format!("Job {} has not finished yet", name).into());
+        }
+
+        let strip = |labels: Option<BTreeMap<String, String>>| {
+            labels.map(|mut labels| {
+                for key in CONTROLLER_LABELS {
+                    labels.remove(key);
+                }
+                labels
+            })
+        };
+
+        let mut spec = job.spec.clone().ok_or("Job has no spec")?;
+        spec.selector = None;
+        spec.manual_selector = None;
+        if let Some(meta) = spec.template.metadata.as_mut() {
+            meta.labels = strip(meta.labels.take());
+        }
+
+        Ok(Job {
+            metadata: ObjectMeta {
+                name: Some(Self::derived_job_name(name, "rerun", suffix)),
+                namespace: job.metadata.namespace.clone(),
+                labels: strip(job.metadata.labels.clone()),
+                annotations: job.metadata.annotations.clone(),
+                owner_references: job.metadata.owner_references.clone(),
+                ..Default::default()
+            },
+            spec: Some(spec),
+            ..Default::default()
+        })
+    }
+
+    /// Helper to create a Job from a CronJob's template using a provided client, exposed for testing
+    pub(crate) async fn trigger_cronjob_with_client(
+        client: Client,
+        name: &str,
+        namespace: &str,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let cronjobs: Api<CronJob> = Api::namespaced(client.clone(), namespace);
+        let jobs: Api<Job> = Api::namespaced(client, namespace);
+
+        let cronjob = cronjobs
+            .get(name)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        let job = Self::job_from_cronjob(&cronjob, &Self::short_suffix())?;
+
+        let created = jobs
+            .create(&PostParams::default(), &job)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        Ok(serde_json::to_value(created).unwrap_or_default())
+    }
+
+    /// Helper to re-run a finished Job using a provided client, exposed for testing
+    pub(crate) async fn rerun_job_with_client(
+        client: Client,
+        name: &str,
+        namespace: &str,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let jobs: Api<Job> = Api::namespaced(client, namespace);
+
+        let job = jobs
+            .get(name)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        let rerun = Self::job_for_rerun(&job, &Self::short_suffix())?;
+
+        let created = jobs
+            .create(&PostParams::default(), &rerun)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        Ok(serde_json::to_value(created).unwrap_or_default())
+    }
+
+    /// Helper to suspend or resume a CronJob using a provided client, exposed for testing
+    pub(crate) async fn set_cronjob_suspended_with_client(
+        client: Client,
+        name: &str,
+        namespace: &str,
+        suspend: bool,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let cronjobs: Api<CronJob> = Api::namespaced(client, namespace);
+        let patch = serde_json::json!({ "spec": { "suspend": suspend } });
+
+        let patched = cronjobs
+            .patch(name, &PatchParams::default(), &Patch::Merge(patch))
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        Ok(serde_json::to_value(patched).unwrap_or_default())
+    }
+
+    fn short_suffix() -> String {
+        Uuid::new_v4().simple().to_string()[..5].to_string()
+    }
+}
@@ -1,18 +1,57 @@
-use crate::models::{GraphData, GraphEdge, GraphNode, K8sResourceType};
+use crate::models::{
+    ClusterEvent, ClusterOverview, DanglingReference, DebugContainerSpec, DrainEvent, DrainOptions,
+    EventFilter, GraphData, GraphOptions, K8sResourceType, NamespaceGraphOptions, NodeUsage,
+    PatchType, PodDiagnosis, PodUsage, ReplaceOutcome, ResourcePatch, ResourceTable,
+    ServiceDiagnosis, TableOptions, UnusedResource, UnusedResourceOptions, UsageReport,
+};
 use async_trait::async_trait;
+use kube::api::{AttachParams, AttachedProcess};
 use kube::config::{KubeConfigOptions, Kubeconfig};
 use kube::{Api, Client};
 use std::error::Error;
+use std::time::Duration;
+
+/// How long to wait for an injected debug container to reach Running
+const DEBUG_CONTAINER_START_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Pause between eviction attempts for pods protected by a PodDisruptionBudget
+const EVICTION_RETRY_INTERVAL: Duration = Duration::from_secs(5);
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait K8sService: Send + Sync {
     async fn get_contexts(&self) -> Result<Vec<String>, Box<dyn Error + Send + Sync>>;
+    async fn get_current_context(&self) -> Result<Option<String>, Box<dyn Error + Send + Sync>>;
+    async fn get_cluster_overview(
+        &self,
+        context_name: &str,
+    ) -> Result<ClusterOverview, Box<dyn Error + Send + Sync>>;
     async fn list_resources(
         &self,
         context_name: &str,
         resource_type: K8sResourceType,
     ) -> Result<Vec<serde_json::Value>, Box<dyn Error + Send + Sync>>;
+    async fn list_pod_usage(
+        &self,
+        context_name: &str,
+        namespace: Option<String>,
+    ) -> Result<UsageReport<PodUsage>, Box<dyn Error + Send + Sync>>;
+    async fn list_node_usage(
+        &self,
+        context_name: &str,
+    ) -> Result<UsageReport<NodeUsage>, Box<dyn Error + Send + Sync>>;
+    async fn list_resource_table(
+        &self,
+        context_name: &str,
+        resource_type: K8sResourceType,
+        options: TableOptions,
+    ) -> Result<ResourceTable, Box<dyn Error + Send + Sync>>;
+    async fn list_custom_resource_table(
+        &self,
+        context_name: &str,
+        crd_name: &str,
+        options: TableOptions,
+    ) -> Result<ResourceTable, Box<dyn Error + Send + Sync>>;
     async fn get_resource(
         &self,
         context_name: &str,
@@ -20,13 +59,59 @@ pub trait K8sService: Send + Sync {
         name: &str,
         namespace: Option<String>,
     ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>>;
+    async fn patch_resource(
+        &self,
+        context_name: &str,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+        patch: ResourcePatch,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>>;
+    async fn replace_resource(
+        &self,
+        context_name: &str,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+        object: serde_json::Value,
+        dry_run: bool,
+    ) -> Result<ReplaceOutcome, Box<dyn Error + Send + Sync>>;
     async fn get_resource_graph(
         &self,
         context_name: &str,
         resource_type: K8sResourceType,
         name: &str,
         namespace: Option<String>,
+        options: GraphOptions,
+    ) -> Result<GraphData, Box<dyn Error + Send + Sync>>;
+    async fn get_namespace_graph(
+        &self,
+        context_name: &str,
+        namespace: &str,
+        options: NamespaceGraphOptions,
     ) -> Result<GraphData, Box<dyn Error + Send + Sync>>;
+    async fn find_dangling_references(
+        &self,
+        context_name: &str,
+        namespace: &str,
+    ) -> Result<Vec<DanglingReference>, Box<dyn Error + Send + Sync>>;
+    async fn find_unused_resources(
+        &self,
+        context_name: &str,
+        namespace: &str,
+        options: UnusedResourceOptions,
+    ) -> Result<Vec<UnusedResource>, Box<dyn Error + Send + Sync>>;
+    async fn list_events(
+        &self,
+        context_name: &str,
+        filter: EventFilter,
+    ) -> Result<Vec<ClusterEvent>, Box<dyn Error + Send + Sync>>;
+    async fn watch_events(
+        &self,
+        context_name: &str,
+        filter: EventFilter,
+        events: tokio::sync::mpsc::Sender<ClusterEvent>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
     async fn get_pod_logs(
         &self,
         context_name: &str,
@@ -34,6 +119,74 @@ pub trait K8sService: Send + Sync {
         namespace: &str,
         container: Option<String>,
     ) -> Result<String, Box<dyn Error + Send + Sync>>;
+    async fn diagnose_pod(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<PodDiagnosis, Box<dyn Error + Send + Sync>>;
+    async fn diagnose_service(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<ServiceDiagnosis, Box<dyn Error + Send + Sync>>;
+    async fn exec_pod(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        container: Option<String>,
+        command: Vec<String>,
+        tty: bool,
+    ) -> Result<AttachedProcess, Box<dyn Error + Send + Sync>>;
+    async fn create_debug_container(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        spec: DebugContainerSpec,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+    async fn attach_pod(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        container: String,
+        tty: bool,
+    ) -> Result<AttachedProcess, Box<dyn Error + Send + Sync>>;
+    async fn trigger_cronjob(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>>;
+    async fn rerun_job(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>>;
+    async fn set_cronjob_suspended(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        suspend: bool,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>>;
+    async fn set_node_unschedulable(
+        &self,
+        context_name: &str,
+        name: &str,
+        unschedulable: bool,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>>;
+    async fn drain_node(
+        &self,
+        context_name: &str,
+        name: &str,
+        options: DrainOptions,
+        progress: tokio::sync::mpsc::Sender<DrainEvent>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
 }
 
 #[derive(Clone)]
@@ -107,783 +260,243 @@ impl K8sClient {
         Ok(serde_json::to_value(resource).unwrap_or_default())
     }
 
-    /// Helper to get resource graph using a provided client, exposed for testing
-    pub(crate) async fn get_resource_graph_with_client(
+    /// Helper to patch a single resource using a provided client, exposed for testing
+    pub(crate) async fn patch_resource_with_client(
         client: Client,
         resource_type: K8sResourceType,
         name: &str,
         namespace: Option<String>,
-    ) -> Result<GraphData, Box<dyn Error + Send + Sync>> {
-        let mut nodes = Vec::new();
-        let mut edges = Vec::new();
-
-        // 1. Fetch the primary resource
-        let resource_json = Self::get_resource_with_client(
-            client.clone(),
-            resource_type.clone(),
-            name,
-            namespace.clone(),
-        )
-        .await?;
-
-        // Extract metadata
-        let metadata = resource_json
-            .get("metadata")
-            .and_then(|m| m.as_object())
-            .ok_or("Missing metadata")?;
-        let uid = metadata
-            .get("uid")
-            .and_then(|u| u.as_str())
-            .ok_or("Missing UID")?
-            .to_string();
-        let name = metadata
-            .get("name")
-            .and_then(|n| n.as_str())
-            .unwrap_or(name)
-            .to_string();
+        patch: ResourcePatch,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let api_resource = resource_type.get_api_resource();
+        let api: Api<kube::api::DynamicObject> = if let Some(ns) = namespace {
+            Api::namespaced_with(client, &ns, &api_resource)
+        } else {
+            Api::all_with(client, &api_resource)
+        };
 
-        // Add primary node
-        nodes.push(GraphNode {
-            id: uid.clone(),
-            label: name.clone(),
-            resource_type: format!("{:?}", resource_type),
-            data: resource_json.clone(),
-        });
+        let params = kube::api::PatchParams {
+            dry_run: patch.dry_run,
+            ..Default::default()
+        };
 
-        // 2. Check OwnerReferences (Upstream)
-        if let Some(owner_refs) = metadata.get("ownerReferences").and_then(|o| o.as_array()) {
-            for owner in owner_refs {
-                let owner_kind = owner
-                    .get("kind")
-                    .and_then(|k| k.as_str())
-                    .unwrap_or_default();
-                let owner_name = owner
-                    .get("name")
-                    .and_then(|n| n.as_str())
-                    .unwrap_or_default();
-                let owner_uid = owner
-                    .get("uid")
-                    .and_then(|u| u.as_str())
-                    .unwrap_or_default();
-
-                if let Some(owner_type) = K8sResourceType::from_kind(owner_kind) {
-                    // Fetch owner details
-                    if let Ok(owner_resource) = Self::get_resource_with_client(
-                        client.clone(),
-                        owner_type.clone(),
-                        owner_name,
-                        namespace.clone(),
-                    )
-                    .await
-                    {
-                        nodes.push(GraphNode {
-                            id: owner_uid.to_string(),
-                            label: owner_name.to_string(),
-                            resource_type: format!("{:?}", owner_type),
-                            data: owner_resource,
-                        });
-
-                        edges.push(GraphEdge {
-                            id: format!("{}-{}", owner_uid, uid),
-                            source: owner_uid.to_string(),
-                            target: uid.clone(),
-                            label: "owner".to_string(),
-                        });
-                    }
+        let result = match patch.patch_type {
+            PatchType::Json => {
+                let mut ops = patch
+                    .body
+                    .as_array()
+                    .cloned()
+                    .ok_or("JSON patch body must be an array of operations")?;
+                // The API server rejects the whole patch if a `test` op fails, which gives us
+                // the resourceVersion precondition for free.
+                if let Some(rv) = patch.resource_version {
+                    ops.insert(
+                        0,
+                        serde_json::json!({
+                            "op": "test",
+                            "path": "/metadata/resourceVersion",
+                            "value": rv,
+                        }),
+                    );
                 }
+                let json_patch: json_patch::Patch = serde_json::from_value(ops.into())
+                    .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+                api.patch(name, &params, &kube::api::Patch::Json::<()>(json_patch))
+                    .await
             }
-        }
-
-        // 3. Check Service Selectors (Downstream)
-        if resource_type == K8sResourceType::Service {
-            if let Some(spec) = resource_json.get("spec") {
-                if let Some(selector) = spec.get("selector").and_then(|s| s.as_object()) {
-                    let selector_str = selector
-                        .iter()
-                        .map(|(k, v)| format!("{}={}", k, v.as_str().unwrap_or("")))
-                        .collect::<Vec<_>>()
-                        .join(",");
-
-                    if !selector_str.is_empty() {
-                        let api_resource = K8sResourceType::Pod.get_api_resource();
-                        let api: Api<kube::api::DynamicObject> = if let Some(ns) = &namespace {
-                            Api::namespaced_with(client.clone(), ns, &api_resource)
-                        } else {
-                            Api::all_with(client.clone(), &api_resource)
-                        };
-
-                        let lp = kube::api::ListParams::default().labels(&selector_str);
-                        if let Ok(pod_list) = api.list(&lp).await {
-                            for pod in pod_list {
-                                let pod_meta = pod.metadata;
-                                let pod_uid = pod_meta
-                                    .uid
-                                    .as_ref()
-                                    .map(|s| s.to_string())
-                                    .unwrap_or_default();
-                                let pod_name = pod_meta
-                                    .name
-                                    .as_ref()
-                                    .map(|s| s.to_string())
-                                    .unwrap_or_default();
-
-                                // Reconstruct full object for data
-                                let mut pod_data =
-                                    serde_json::to_value(pod.data).unwrap_or_default();
-                                if let Some(obj) = pod_data.as_object_mut() {
-                                    obj.insert(
-                                        "metadata".to_string(),
-                                        serde_json::to_value(&pod_meta).unwrap_or_default(),
-                                    );
-                                }
-
-                                nodes.push(GraphNode {
-                                    id: pod_uid.clone(),
-                                    label: pod_name,
-                                    resource_type: "Pod".to_string(),
-                                    data: pod_data,
-                                });
-
-                                edges.push(GraphEdge {
-                                    id: format!("{}-{}", uid, pod_uid),
-                                    source: uid.clone(),
-                                    target: pod_uid,
-                                    label: "selects".to_string(),
-                                });
-                            }
-                        }
-                    }
+            PatchType::Merge | PatchType::Strategic => {
+                let mut body = patch.body;
+                // A resourceVersion in the patched metadata makes the API server answer
+                // with 409 Conflict when the object has moved on.
+                if let Some(rv) = patch.resource_version {
+                    let metadata = body
+                        .as_object_mut()
+                        .ok_or("Merge patch body must be a JSON object")?
+                        .entry("metadata")
+                        .or_insert_with(|| serde_json::json!({}));
+                    metadata
+                        .as_object_mut()
+                        .ok_or("Patch metadata must be a JSON object")?
+                        .insert("resourceVersion".to_string(), rv.into());
                 }
-            }
-        }
-
-        // 4. Check Reverse Networking (Pod -> Services)
-        if resource_type == K8sResourceType::Pod {
-            let pod_labels = metadata.get("labels").and_then(|l| l.as_object());
-            if let Some(labels) = pod_labels {
-                // List all services in namespace
-                let api_resource = K8sResourceType::Service.get_api_resource();
-                let api: Api<kube::api::DynamicObject> = if let Some(ns) = &namespace {
-                    Api::namespaced_with(client.clone(), ns, &api_resource)
+                if patch.patch_type == PatchType::Merge {
+                    api.patch(name, &params, &kube::api::Patch::Merge(body))
+                        .await
                 } else {
-                    Api::all_with(client.clone(), &api_resource)
-                };
-
-                if let Ok(services) = api.list(&Default::default()).await {
-                    for svc in services {
-                        let svc_meta = svc.metadata;
-                        let svc_spec = svc.data.get("spec");
-                        // Check selector
-                        if let Some(selector) = svc_spec
-                            .and_then(|s| s.get("selector"))
-                            .and_then(|s| s.as_object())
-                        {
-                            let mut match_all = true;
-                            for (k, v) in selector {
-                                let v_str = v.as_str().unwrap_or_default();
-                                if labels.get(k).and_then(|l| l.as_str()).unwrap_or_default()
-                                    != v_str
-                                {
-                                    match_all = false;
-                                    break;
-                                }
-                            }
-
-                            if match_all && !selector.is_empty() {
-                                // Add Service Node
-                                let svc_uid = svc_meta.uid.clone().unwrap_or_default();
-                                let svc_name = svc_meta.name.clone().unwrap_or_default();
-
-                                // Reconstruct data
-                                let mut svc_data =
-                                    serde_json::to_value(svc.data).unwrap_or_default();
-                                if let Some(obj) = svc_data.as_object_mut() {
-                                    obj.insert(
-                                        "metadata".to_string(),
-                                        serde_json::to_value(&svc_meta).unwrap_or_default(),
-                                    );
-                                }
-
-                                nodes.push(GraphNode {
-                                    id: svc_uid.clone(),
-                                    label: svc_name,
-                                    resource_type: "Service".to_string(),
-                                    data: svc_data,
-                                });
-
-                                edges.push(GraphEdge {
-                                    id: format!("{}-{}", svc_uid, uid),
-                                    source: svc_uid,
-                                    target: uid.clone(),
-                                    label: "selects".to_string(),
-                                });
-                            }
-                        }
-                    }
+                    api.patch(name, &params, &kube::api::Patch::Strategic(body))
+                        .await
                 }
             }
         }
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
 
-        // 5. Check Pod Configuration & Storage (Pod -> CM, Secret, PVC)
-        if resource_type == K8sResourceType::Pod {
-            if let Some(spec) = resource_json.get("spec") {
-                // Collect dependencies to fetch
-                let mut deps = Vec::new(); // (Type, Name, EdgeLabel)                // Check Volumes
-                if let Some(volumes) = spec.get("volumes").and_then(|v| v.as_array()) {
-                    for vol in volumes {
-                        if let Some(cm) = vol.get("configMap") {
-                            if let Some(name) = cm.get("name").and_then(|n| n.as_str()) {
-                                deps.push((K8sResourceType::ConfigMap, name.to_string(), "uses"));
-                            }
-                        }
-                        if let Some(secret) = vol.get("secret") {
-                            if let Some(name) = secret.get("secretName").and_then(|n| n.as_str()) {
-                                deps.push((K8sResourceType::Secret, name.to_string(), "uses"));
-                            }
-                        }
-                        if let Some(pvc) = vol.get("persistentVolumeClaim") {
-                            if let Some(name) = pvc.get("claimName").and_then(|n| n.as_str()) {
-                                deps.push((
-                                    K8sResourceType::PersistentVolumeClaim,
-                                    name.to_string(),
-                                    "uses",
-                                ));
-                            }
-                        }
-                    }
-                }
-
-                // Check Containers for Env
-                let mut containers = Vec::new();
-                if let Some(c) = spec.get("containers").and_then(|c| c.as_array()) {
-                    containers.extend(c);
-                }
-                if let Some(c) = spec.get("initContainers").and_then(|c| c.as_array()) {
-                    containers.extend(c);
-                }
+        Ok(serde_json::to_value(result).unwrap_or_default())
+    }
 
-                for container in containers {
-                    // env
-                    if let Some(env) = container.get("env").and_then(|e| e.as_array()) {
-                        for e in env {
-                            if let Some(val_from) = e.get("valueFrom") {
-                                if let Some(cm) = val_from.get("configMapKeyRef") {
-                                    if let Some(name) = cm.get("name").and_then(|n| n.as_str()) {
-                                        deps.push((
-                                            K8sResourceType::ConfigMap,
-                                            name.to_string(),
-                                            "uses",
-                                        ));
-                                    }
-                                }
-                                if let Some(secret) = val_from.get("secretKeyRef") {
-                                    if let Some(name) = secret.get("name").and_then(|n| n.as_str())
-                                    {
-                                        deps.push((
-                                            K8sResourceType::Secret,
-                                            name.to_string(),
-                                            "uses",
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    // envFrom
-                    if let Some(env_from) = container.get("envFrom").and_then(|e| e.as_array()) {
-                        for e in env_from {
-                            if let Some(cm) = e.get("configMapRef") {
-                                if let Some(name) = cm.get("name").and_then(|n| n.as_str()) {
-                                    deps.push((
-                                        K8sResourceType::ConfigMap,
-                                        name.to_string(),
-                                        "uses",
-                                    ));
-                                }
-                            }
-                            if let Some(secret) = e.get("secretRef") {
-                                if let Some(name) = secret.get("name").and_then(|n| n.as_str()) {
-                                    deps.push((K8sResourceType::Secret, name.to_string(), "uses"));
-                                }
-                            }
-                        }
-                    }
-                }
+    /// Helper to replace a resource with a full edited object using a provided client, exposed for testing
+    pub(crate) async fn replace_resource_with_client(
+        client: Client,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+        object: serde_json::Value,
+        dry_run: bool,
+    ) -> Result<ReplaceOutcome, Box<dyn Error + Send + Sync>> {
+        let edited_version = object
+            .get("metadata")
+            .and_then(|m| m.get("resourceVersion"))
+            .and_then(|v| v.as_str())
+            .ok_or("Edited object must carry metadata.resourceVersion")?
+            .to_string();
 
-                // Fetch and add dependencies
-                for (rtype, rname, edge_label) in deps {
-                    if let Ok(res) = Self::get_resource_with_client(
-                        client.clone(),
-                        rtype.clone(),
-                        &rname,
-                        namespace.clone(),
-                    )
-                    .await
-                    {
-                        let r_uid = res
-                            .get("metadata")
-                            .and_then(|m| m.get("uid"))
-                            .and_then(|u| u.as_str())
-                            .unwrap_or_default()
-                            .to_string();
-
-                        // Avoid duplicates
-                        if !nodes.iter().any(|n| n.id == r_uid) {
-                            nodes.push(GraphNode {
-                                id: r_uid.to_string(),
-                                label: rname.clone(),
-                                resource_type: format!("{:?}", rtype),
-                                data: res,
-                            });
-                        }
+        let live = Self::get_resource_with_client(
+            client.clone(),
+            resource_type.clone(),
+            name,
+            namespace.clone(),
+        )
+        .await?;
+        let live_version = live
+            .get("metadata")
+            .and_then(|m| m.get("resourceVersion"))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
 
-                        edges.push(GraphEdge {
-                            id: format!("{}-{}", uid, r_uid),
-                            source: uid.clone(),
-                            target: r_uid.to_string(),
-                            label: edge_label.to_string(),
-                        });
-                    }
-                }
-            }
+        if live_version != edited_version {
+            return Ok(ReplaceOutcome::Conflict { current: live });
         }
 
-        // 6. Check PVC -> PV & StorageClass
-        if resource_type == K8sResourceType::PersistentVolumeClaim {
-            if let Some(spec) = resource_json.get("spec") {
-                // StorageClass
-                if let Some(sc_name) = spec.get("storageClassName").and_then(|s| s.as_str()) {
-                    if let Ok(res) = Self::get_resource_with_client(
-                        client.clone(),
-                        K8sResourceType::StorageClass,
-                        sc_name,
-                        None, // Cluster scoped
-                    )
-                    .await
-                    {
-                        let sc_uid = res
-                            .get("metadata")
-                            .and_then(|m| m.get("uid"))
-                            .and_then(|u| u.as_str())
-                            .unwrap_or_default()
-                            .to_string();
-
-                        nodes.push(GraphNode {
-                            id: sc_uid.to_string(),
-                            label: sc_name.to_string(),
-                            resource_type: "StorageClass".to_string(),
-                            data: res,
-                        });
-
-                        edges.push(GraphEdge {
-                            id: format!("{}-{}", uid, sc_uid),
-                            source: uid.clone(),
-                            target: sc_uid.to_string(),
-                            label: "uses".to_string(),
-                        });
-                    }
-                }
-                // VolumeName (PV)
-                if let Some(pv_name) = spec.get("volumeName").and_then(|s| s.as_str()) {
-                    if let Ok(res) = Self::get_resource_with_client(
-                        client.clone(),
-                        K8sResourceType::PersistentVolume,
-                        pv_name,
-                        None, // Cluster scoped
-                    )
-                    .await
-                    {
-                        let pv_uid = res
-                            .get("metadata")
-                            .and_then(|m| m.get("uid"))
-                            .and_then(|u| u.as_str())
-                            .unwrap_or_default()
-                            .to_string();
-
-                        nodes.push(GraphNode {
-                            id: pv_uid.to_string(),
-                            label: pv_name.to_string(),
-                            resource_type: "PersistentVolume".to_string(),
-                            data: res,
-                        });
-
-                        edges.push(GraphEdge {
-                            id: format!("{}-{}", uid, pv_uid),
-                            source: uid.clone(),
-                            target: pv_uid.to_string(),
-                            label: "bound".to_string(),
-                        });
-                    }
-                }
-            }
-        }
+        let api_resource = resource_type.get_api_resource();
+        let api: Api<kube::api::DynamicObject> = if let Some(ns) = &namespace {
+            Api::namespaced_with(client.clone(), ns, &api_resource)
+        } else {
+            Api::all_with(client.clone(), &api_resource)
+        };
 
-        // 7. Check PV -> PVC
-        if resource_type == K8sResourceType::PersistentVolume {
-            if let Some(spec) = resource_json.get("spec") {
-                if let Some(claim_ref) = spec.get("claimRef") {
-                    let claim_name = claim_ref.get("name").and_then(|n| n.as_str());
-                    let claim_ns = claim_ref.get("namespace").and_then(|n| n.as_str());
-
-                    if let (Some(name), Some(ns)) = (claim_name, claim_ns) {
-                        if let Ok(res) = Self::get_resource_with_client(
-                            client.clone(),
-                            K8sResourceType::PersistentVolumeClaim,
-                            name,
-                            Some(ns.to_string()),
-                        )
-                        .await
-                        {
-                            let pvc_uid = res
-                                .get("metadata")
-                                .and_then(|m| m.get("uid"))
-                                .and_then(|u| u.as_str())
-                                .unwrap_or_default()
-                                .to_string();
-
-                            nodes.push(GraphNode {
-                                id: pvc_uid.to_string(),
-                                label: name.to_string(),
-                                resource_type: "PersistentVolumeClaim".to_string(),
-                                data: res,
-                            });
-
-                            edges.push(GraphEdge {
-                                id: format!("{}-{}", uid, pvc_uid),
-                                source: uid.clone(),
-                                target: pvc_uid.to_string(),
-                                label: "bound".to_string(),
-                            });
-                        }
-                    }
-                }
+        let data: kube::api::DynamicObject = serde_json::from_value(object)
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        let params = kube::api::PostParams {
+            dry_run,
+            ..Default::default()
+        };
+
+        // The API server still enforces resourceVersion, so an update that lands between our
+        // GET and this PUT surfaces as a 409 rather than silently overwriting it.
+        let replaced = match api.replace(name, &params, &data).await {
+            Ok(replaced) => serde_json::to_value(replaced).unwrap_or_default(),
+            Err(kube::Error::Api(e)) if e.code == 409 => {
+                let current =
+                    Self::get_resource_with_client(client, resource_type, name, namespace).await?;
+                return Ok(ReplaceOutcome::Conflict { current });
             }
+            Err(e) => return Err(Box::new(e)),
+        };
+
+        if !dry_run {
+            return Ok(ReplaceOutcome::Applied { resource: replaced });
         }
 
-        // 8. Reverse Lookups (Who uses me?)
-        // ConfigMap/Secret/PVC -> Pods
-        if matches!(
-            resource_type,
-            K8sResourceType::ConfigMap
-                | K8sResourceType::Secret
-                | K8sResourceType::PersistentVolumeClaim
-        ) {
-            if let Some(ns) = &namespace {
-                let api_resource = K8sResourceType::Pod.get_api_resource();
-                let api: Api<kube::api::DynamicObject> =
-                    Api::namespaced_with(client.clone(), ns, &api_resource);
-
-                if let Ok(pods) = api.list(&Default::default()).await {
-                    for pod in pods {
-                        let pod_meta = pod.metadata;
-                        let pod_uid = pod_meta.uid.clone().unwrap_or_default();
-                        let pod_name = pod_meta.name.clone().unwrap_or_default();
-
-                        let mut uses_me = false;
-
-                        if let Some(spec) = pod.data.get("spec") {
-                            // Check Volumes
-                            if let Some(volumes) = spec.get("volumes").and_then(|v| v.as_array()) {
-                                for vol in volumes {
-                                    match resource_type {
-                                        K8sResourceType::ConfigMap => {
-                                            if let Some(cm) = vol.get("configMap") {
-                                                if cm.get("name").and_then(|n| n.as_str())
-                                                    == Some(&name)
-                                                {
-                                                    uses_me = true;
-                                                }
-                                            }
-                                        }
-                                        K8sResourceType::Secret => {
-                                            if let Some(s) = vol.get("secret") {
-                                                if s.get("secretName").and_then(|n| n.as_str())
-                                                    == Some(&name)
-                                                {
-                                                    uses_me = true;
-                                                }
-                                            }
-                                        }
-                                        K8sResourceType::PersistentVolumeClaim => {
-                                            if let Some(p) = vol.get("persistentVolumeClaim") {
-                                                if p.get("claimName").and_then(|n| n.as_str())
-                                                    == Some(&name)
-                                                {
-                                                    uses_me = true;
-                                                }
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
-                            // Check Env (only for CM/Secret)
-                            if !uses_me
-                                && matches!(
-                                    resource_type,
-                                    K8sResourceType::ConfigMap | K8sResourceType::Secret
-                                )
-                            {
-                                let mut containers = Vec::new();
-                                if let Some(c) = spec.get("containers").and_then(|c| c.as_array()) {
-                                    containers.extend(c);
-                                }
-                                if let Some(c) =
-                                    spec.get("initContainers").and_then(|c| c.as_array())
-                                {
-                                    containers.extend(c);
-                                }
-
-                                for container in containers {
-                                    if let Some(env) =
-                                        container.get("env").and_then(|e| e.as_array())
-                                    {
-                                        for e in env {
-                                            if let Some(val_from) = e.get("valueFrom") {
-                                                match resource_type {
-                                                    K8sResourceType::ConfigMap => {
-                                                        if let Some(r) =
-                                                            val_from.get("configMapKeyRef")
-                                                        {
-                                                            if r.get("name")
-                                                                .and_then(|n| n.as_str())
-                                                                == Some(&name)
-                                                            {
-                                                                uses_me = true;
-                                                                break;
-                                                            }
-                                                        }
-                                                    }
-                                                    K8sResourceType::Secret => {
-                                                        if let Some(r) =
-                                                            val_from.get("secretKeyRef")
-                                                        {
-                                                            if r.get("name")
-                                                                .and_then(|n| n.as_str())
-                                                                == Some(&name)
-                                                            {
-                                                                uses_me = true;
-                                                                break;
-                                                            }
-                                                        }
-                                                    }
-                                                    _ => {}
-                                                }
-                                            }
-                                        }
-                                    }
-                                    if uses_me {
-                                        break;
-                                    }
-
-                                    if let Some(env_from) =
-                                        container.get("envFrom").and_then(|e| e.as_array())
-                                    {
-                                        for e in env_from {
-                                            match resource_type {
-                                                K8sResourceType::ConfigMap => {
-                                                    if let Some(r) = e.get("configMapRef") {
-                                                        if r.get("name").and_then(|n| n.as_str())
-                                                            == Some(&name)
-                                                        {
-                                                            uses_me = true;
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-                                                K8sResourceType::Secret => {
-                                                    if let Some(r) = e.get("secretRef") {
-                                                        if r.get("name").and_then(|n| n.as_str())
-                                                            == Some(&name)
-                                                        {
-                                                            uses_me = true;
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-                                                _ => {}
-                                            }
-                                        }
-                                    }
-                                    if uses_me {
-                                        break;
-                                    }
-                                }
-                            }
-                        }
+        let before = Self::strip_for_diff(live);
+        let after = Self::strip_for_diff(replaced);
+        let diff = serde_json::to_value(json_patch::diff(&before, &after)).unwrap_or_default();
+        Ok(ReplaceOutcome::Preview {
+            before,
+            after,
+            diff,
+        })
+    }
 
-                        if uses_me {
-                            // Reconstruct data
-                            let mut pod_data = serde_json::to_value(pod.data).unwrap_or_default();
-                            if let Some(obj) = pod_data.as_object_mut() {
-                                obj.insert(
-                                    "metadata".to_string(),
-                                    serde_json::to_value(&pod_meta).unwrap_or_default(),
-                                );
-                            }
-
-                            nodes.push(GraphNode {
-                                id: pod_uid.clone(),
-                                label: pod_name,
-                                resource_type: "Pod".to_string(),
-                                data: pod_data,
-                            });
-
-                            edges.push(GraphEdge {
-                                id: format!("{}-{}", pod_uid, uid),
-                                source: pod_uid,
-                                target: uid.clone(),
-                                label: "uses".to_string(),
-                            });
-                        }
-                    }
-                }
+    /// Drops server-owned noise (status, managedFields) so a diff only shows user edits
+    pub(crate) fn strip_for_diff(mut resource: serde_json::Value) -> serde_json::Value {
+        if let Some(obj) = resource.as_object_mut() {
+            obj.remove("status");
+            if let Some(metadata) = obj.get_mut("metadata").and_then(|m| m.as_object_mut()) {
+                metadata.remove("managedFields");
             }
         }
+        resource
+    }
 
-        // StorageClass -> PVCs
-        if resource_type == K8sResourceType::StorageClass {
-            let api_resource = K8sResourceType::PersistentVolumeClaim.get_api_resource();
-            let api: Api<kube::api::DynamicObject> = Api::all_with(client.clone(), &api_resource);
-
-            if let Ok(pvcs) = api.list(&Default::default()).await {
-                for pvc in pvcs {
-                    let pvc_meta = pvc.metadata;
-                    let pvc_uid = pvc_meta.uid.clone().unwrap_or_default();
-                    let pvc_name = pvc_meta.name.clone().unwrap_or_default();
-
-                    if let Some(spec) = pvc.data.get("spec") {
-                        if let Some(sc) = spec.get("storageClassName").and_then(|s| s.as_str()) {
-                            if sc == name {
-                                // Reconstruct data
-                                let mut pvc_data =
-                                    serde_json::to_value(pvc.data).unwrap_or_default();
-                                if let Some(obj) = pvc_data.as_object_mut() {
-                                    obj.insert(
-                                        "metadata".to_string(),
-                                        serde_json::to_value(&pvc_meta).unwrap_or_default(),
-                                    );
-                                }
-
-                                nodes.push(GraphNode {
-                                    id: pvc_uid.clone(),
-                                    label: pvc_name,
-                                    resource_type: "PersistentVolumeClaim".to_string(),
-                                    data: pvc_data,
-                                });
-
-                                edges.push(GraphEdge {
-                                    id: format!("{}-{}", pvc_uid, uid),
-                                    source: pvc_uid,
-                                    target: uid.clone(),
-                                    label: "uses".to_string(),
-                                });
-                            }
-                        }
-                    }
-                }
-            }
+    /// Helper to inject an ephemeral debug container and wait for it to run, exposed for testing
+    pub(crate) async fn create_debug_container_with_client(
+        client: Client,
+        name: &str,
+        namespace: &str,
+        spec: DebugContainerSpec,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let pods: Api<k8s_openapi::api::core::v1::Pod> = Api::namespaced(client, namespace);
+
+        let mut container = serde_json::json!({
+            "name": spec.name,
+            "image": spec.image,
+            "stdin": true,
+            "tty": true,
+        });
+        if let Some(target) = &spec.target_container {
+            container["targetContainerName"] = target.clone().into();
+        }
+        if !spec.command.is_empty() {
+            container["command"] = spec.command.clone().into();
         }
 
-        // 9. Check Deployment -> ReplicaSets (Downstream)
-        if resource_type == K8sResourceType::Deployment {
-            if let Some(ns) = &namespace {
-                let api_resource = K8sResourceType::ReplicaSet.get_api_resource();
-                let api: Api<kube::api::DynamicObject> =
-                    Api::namespaced_with(client.clone(), ns, &api_resource);
-
-                // We could use label selector from deployment spec, but checking ownerReferences is more reliable for "ownership"
-                // However, listing all RS in namespace is fine.
-                if let Ok(replicasets) = api.list(&Default::default()).await {
-                    for rs in replicasets {
-                        let rs_meta = rs.metadata;
-                        let rs_uid = rs_meta.uid.clone().unwrap_or_default();
-                        let rs_name = rs_meta.name.clone().unwrap_or_default();
-
-                        // Check if this RS is owned by the current Deployment
-                        let is_owned = rs_meta
-                            .owner_references
-                            .as_ref()
-                            .map_or(false, |refs| refs.iter().any(|r| r.uid == uid));
-
-                        if is_owned {
-                            // Reconstruct data
-                            let mut rs_data = serde_json::to_value(rs.data).unwrap_or_default();
-                            if let Some(obj) = rs_data.as_object_mut() {
-                                obj.insert(
-                                    "metadata".to_string(),
-                                    serde_json::to_value(&rs_meta).unwrap_or_default(),
-                                );
-                            }
-
-                            nodes.push(GraphNode {
-                                id: rs_uid.clone(),
-                                label: rs_name,
-                                resource_type: "ReplicaSet".to_string(),
-                                data: rs_data,
-                            });
-
-                            edges.push(GraphEdge {
-                                id: format!("{}-{}", uid, rs_uid),
-                                source: uid.clone(),
-                                target: rs_uid,
-                                label: "manages".to_string(),
-                            });
-                        }
+        // Strategic merge keys ephemeralContainers by name, so existing debuggers are kept
+        let patch = serde_json::json!({ "spec": { "ephemeralContainers": [container] } });
+        pods.patch_ephemeral_containers(
+            name,
+            &kube::api::PatchParams::default(),
+            &kube::api::Patch::Strategic(patch),
+        )
+        .await
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let pod = pods
+                .get(name)
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+            let state = pod
+                .status
+                .and_then(|s| s.ephemeral_container_statuses)
+                .and_then(|statuses| statuses.into_iter().find(|c| c.name == spec.name))
+                .and_then(|c| c.state);
+
+            if let Some(state) = state {
+                if state.running.is_some() {
+                    return Ok(());
+                }
+                if let Some(terminated) = state.terminated {
+                    return Err(format!(
+                        "Debug container {} terminated: {}",
+                        spec.name,
+                        terminated.reason.unwrap_or_default()
+                    )
+                    .into());
+                }
+                if let Some(reason) = state.waiting.and_then(|w| w.reason) {
+                    if matches!(
+                        reason.as_str(),
+                        "ErrImagePull" | "ImagePullBackOff" | "InvalidImageName"
+                    ) {
+                        return Err(format!(
+                            "Debug container {} cannot start: {}",
+                            spec.name, reason
+                        )
+                        .into());
                     }
                 }
             }
-        }
 
-        // 10. Check ReplicaSet -> Pods (Downstream)
-        if resource_type == K8sResourceType::ReplicaSet {
-            if let Some(ns) = &namespace {
-                let api_resource = K8sResourceType::Pod.get_api_resource();
-                let api: Api<kube::api::DynamicObject> =
-                    Api::namespaced_with(client.clone(), ns, &api_resource);
-
-                if let Ok(pods) = api.list(&Default::default()).await {
-                    for pod in pods {
-                        let pod_meta = pod.metadata;
-                        let pod_uid = pod_meta.uid.clone().unwrap_or_default();
-                        let pod_name = pod_meta.name.clone().unwrap_or_default();
-
-                        // Check if this Pod is owned by the current ReplicaSet
-                        let is_owned = pod_meta
-                            .owner_references
-                            .as_ref()
-                            .map_or(false, |refs| refs.iter().any(|r| r.uid == uid));
-
-                        if is_owned {
-                            // Reconstruct data
-                            let mut pod_data = serde_json::to_value(pod.data).unwrap_or_default();
-                            if let Some(obj) = pod_data.as_object_mut() {
-                                obj.insert(
-                                    "metadata".to_string(),
-                                    serde_json::to_value(&pod_meta).unwrap_or_default(),
-                                );
-                            }
-
-                            nodes.push(GraphNode {
-                                id: pod_uid.clone(),
-                                label: pod_name,
-                                resource_type: "Pod".to_string(),
-                                data: pod_data,
-                            });
-
-                            edges.push(GraphEdge {
-                                id: format!("{}-{}", uid, pod_uid),
-                                source: uid.clone(),
-                                target: pod_uid,
-                                label: "manages".to_string(),
-                            });
-                        }
-                    }
-                }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(format!(
+                    "Timed out waiting for debug container {} to start",
+                    spec.name
+                )
+                .into());
             }
+            tokio::time::sleep(Duration::from_secs(1)).await;
         }
-
-        Ok(GraphData { nodes, edges })
     }
 }
 
@@ -895,6 +508,20 @@ impl K8sService for K8sClient {
         Ok(Self::extract_contexts(kubeconfig))
     }
 
+    async fn get_current_context(&self) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+        let kubeconfig: Kubeconfig =
+            Kubeconfig::read().map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        Ok(kubeconfig.current_context)
+    }
+
+    async fn get_cluster_overview(
+        &self,
+        context_name: &str,
+    ) -> Result<ClusterOverview, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::get_cluster_overview_with_client(client).await
+    }
+
     async fn list_resources(
         &self,
         context_name: &str,
@@ -904,6 +531,43 @@ impl K8sService for K8sClient {
         Self::list_resources_with_client(client, resource_type).await
     }
 
+    async fn list_pod_usage(
+        &self,
+        context_name: &str,
+        namespace: Option<String>,
+    ) -> Result<UsageReport<PodUsage>, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::list_pod_usage_with_client(client, namespace.as_deref()).await
+    }
+
+    async fn list_node_usage(
+        &self,
+        context_name: &str,
+    ) -> Result<UsageReport<NodeUsage>, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::list_node_usage_with_client(client).await
+    }
+
+    async fn list_resource_table(
+        &self,
+        context_name: &str,
+        resource_type: K8sResourceType,
+        options: TableOptions,
+    ) -> Result<ResourceTable, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::list_resource_table_with_client(client, resource_type, options).await
+    }
+
+    async fn list_custom_resource_table(
+        &self,
+        context_name: &str,
+        crd_name: &str,
+        options: TableOptions,
+    ) -> Result<ResourceTable, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::list_custom_resource_table_with_client(client, crd_name, options).await
+    }
+
     async fn get_resource(
         &self,
         context_name: &str,
@@ -915,15 +579,90 @@ impl K8sService for K8sClient {
         Self::get_resource_with_client(client, resource_type, name, namespace).await
     }
 
+    async fn patch_resource(
+        &self,
+        context_name: &str,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+        patch: ResourcePatch,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::patch_resource_with_client(client, resource_type, name, namespace, patch).await
+    }
+
+    async fn replace_resource(
+        &self,
+        context_name: &str,
+        resource_type: K8sResourceType,
+        name: &str,
+        namespace: Option<String>,
+        object: serde_json::Value,
+        dry_run: bool,
+    ) -> Result<ReplaceOutcome, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::replace_resource_with_client(client, resource_type, name, namespace, object, dry_run)
+            .await
+    }
+
     async fn get_resource_graph(
         &self,
         context_name: &str,
         resource_type: K8sResourceType,
         name: &str,
         namespace: Option<String>,
+        options: GraphOptions,
+    ) -> Result<GraphData, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::get_resource_graph_with_client(client, resource_type, name, namespace, options).await
+    }
+
+    async fn get_namespace_graph(
+        &self,
+        context_name: &str,
+        namespace: &str,
+        options: NamespaceGraphOptions,
     ) -> Result<GraphData, Box<dyn Error + Send + Sync>> {
         let client = Self::create_client(context_name).await?;
-        Self::get_resource_graph_with_client(client, resource_type, name, namespace).await
+        Self::get_namespace_graph_with_client(client, namespace, options).await
+    }
+
+    async fn find_dangling_references(
+        &self,
+        context_name: &str,
+        namespace: &str,
+    ) -> Result<Vec<DanglingReference>, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::find_dangling_references_with_client(client, namespace).await
+    }
+
+    async fn find_unused_resources(
+        &self,
+        context_name: &str,
+        namespace: &str,
+        options: UnusedResourceOptions,
+    ) -> Result<Vec<UnusedResource>, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::find_unused_resources_with_client(client, namespace, options).await
+    }
+
+    async fn list_events(
+        &self,
+        context_name: &str,
+        filter: EventFilter,
+    ) -> Result<Vec<ClusterEvent>, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::list_events_with_client(client, &filter).await
+    }
+
+    async fn watch_events(
+        &self,
+        context_name: &str,
+        filter: EventFilter,
+        events: tokio::sync::mpsc::Sender<ClusterEvent>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::watch_events_with_client(client, filter, events).await
     }
 
     async fn get_pod_logs(
@@ -999,4 +738,144 @@ impl K8sService for K8sClient {
         );
         Ok(logs)
     }
+
+    async fn diagnose_pod(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<PodDiagnosis, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::diagnose_pod_with_client(client, name, namespace).await
+    }
+
+    async fn diagnose_service(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<ServiceDiagnosis, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::diagnose_service_with_client(client, name, namespace).await
+    }
+
+    async fn exec_pod(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        container: Option<String>,
+        command: Vec<String>,
+        tty: bool,
+    ) -> Result<AttachedProcess, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        let pods: Api<k8s_openapi::api::core::v1::Pod> = Api::namespaced(client, namespace);
+
+        // A TTY merges stderr into stdout, and the API server rejects asking for both
+        let mut params = AttachParams::default()
+            .stdin(true)
+            .stdout(true)
+            .stderr(!tty)
+            .tty(tty);
+        if let Some(container) = container {
+            params = params.container(container);
+        }
+
+        pods.exec(name, command, &params)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+    }
+
+    async fn create_debug_container(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        spec: DebugContainerSpec,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::create_debug_container_with_client(
+            client,
+            name,
+            namespace,
+            spec,
+            DEBUG_CONTAINER_START_TIMEOUT,
+        )
+        .await
+    }
+
+    async fn attach_pod(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        container: String,
+        tty: bool,
+    ) -> Result<AttachedProcess, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        let pods: Api<k8s_openapi::api::core::v1::Pod> = Api::namespaced(client, namespace);
+
+        let params = AttachParams::default()
+            .container(container)
+            .stdin(true)
+            .stdout(true)
+            .stderr(!tty)
+            .tty(tty);
+
+        pods.attach(name, &params)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+    }
+
+    async fn trigger_cronjob(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::trigger_cronjob_with_client(client, name, namespace).await
+    }
+
+    async fn rerun_job(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::rerun_job_with_client(client, name, namespace).await
+    }
+
+    async fn set_cronjob_suspended(
+        &self,
+        context_name: &str,
+        name: &str,
+        namespace: &str,
+        suspend: bool,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::set_cronjob_suspended_with_client(client, name, namespace, suspend).await
+    }
+
+    async fn set_node_unschedulable(
+        &self,
+        context_name: &str,
+        name: &str,
+        unschedulable: bool,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::set_node_unschedulable_with_client(client, name, unschedulable).await
+    }
+
+    async fn drain_node(
+        &self,
+        context_name: &str,
+        name: &str,
+        options: DrainOptions,
+        progress: tokio::sync::mpsc::Sender<DrainEvent>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = Self::create_client(context_name).await?;
+        Self::drain_node_with_client(client, name, options, progress, EVICTION_RETRY_INTERVAL).await
+    }
 }
@@ -0,0 +1,406 @@
+use super::analysis::list_objects;
+use super::k8s::K8sClient;
+use super::quantity::{parse_bytes, parse_millicores};
+use crate::models::{
+    ContainerUsage, GraphData, K8sResourceType, NodeUsage, PodUsage, ResourceUsage, UsageMeasure,
+    UsageReport,
+};
+use chrono::{DateTime, Utc};
+use kube::api::{ApiResource, DynamicObject, ListParams};
+use kube::{Api, Client};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+
+type Quantities = (Option<u64>, Option<u64>);
+
+fn metrics_resource(kind: &str, plural: &str) -> ApiResource {
+    ApiResource {
+        group: "metrics.k8s.io".to_string(),
+        version: "v1beta1".to_string(),
+        api_version: "metrics.k8s.io/v1beta1".to_string(),
+        kind: kind.to_string(),
+        plural: plural.to_string(),
+    }
+}
+
+/// Lists PodMetrics or NodeMetrics. `Err(message)` when the metrics API isn't served, which
+/// is how a cluster without metrics-server, or with one that is down, answers.
+async fn list_metrics(
+    client: Client,
+    resource: ApiResource,
+    namespace: Option<&str>,
+) -> Result<Result<Vec<Value>, String>, Box<dyn Error + Send + Sync>> {
+    let api: Api<DynamicObject> = match namespace {
+        Some(namespace) => Api::namespaced_with(client, namespace, &resource),
+        None => Api::all_with(client, &resource),
+    };
+    match api.list(&ListParams::default()).await {
+        Ok(list) => Ok(Ok(list
+            .items
+            .into_iter()
+            .map(|item| serde_json::to_value(item).unwrap_or_default())
+            .collect())),
+        Err(kube::Error::Api(status)) if status.code == 404 => Ok(Err(
+            "The metrics.k8s.io API is not served by this cluster; install metrics-server to see usage"
+                .to_string(),
+        )),
+        Err(kube::Error::Api(status)) if status.code == 503 => Ok(Err(format!(
+            "metrics-server is not responding: {}",
+            status.message
+        ))),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+fn unavailable<T>(message: String) -> UsageReport<T> {
+    UsageReport {
+        metrics_available: false,
+        message: Some(message),
+        items: Vec::new(),
+    }
+}
+
+fn str_at<'a>(resource: &'a Value, pointer: &str) -> Option<&'a str> {
+    resource.pointer(pointer).and_then(|v| v.as_str())
+}
+
+fn time_at(resource: &Value, pointer: &str) -> Option<DateTime<Utc>> {
+    str_at(resource, pointer).and_then(|t| t.parse().ok())
+}
+
+/// Cpu millicores and memory bytes of a `{cpu, memory}` quantity map
+fn quantities(resources: Option<&Value>) -> Quantities {
+    let field = |name: &str| resources.and_then(|r| r.get(name)).and_then(|q| q.as_str());
+    (
+        field("cpu").and_then(parse_millicores),
+        field("memory").and_then(parse_bytes),
+    )
+}
+
+fn percent(used: u64, total: Option<u64>) -> Option<f64> {
+    total
+        .filter(|total| *total > 0)
+        .map(|total| (used as f64 * 1000.0 / total as f64).round() / 10.0)
+}
+
+fn measure(
+    used: u64,
+    request: Option<u64>,
+    limit: Option<u64>,
+    allocatable: Option<u64>,
+) -> UsageMeasure {
+    UsageMeasure {
+        used,
+        request,
+        limit,
+        allocatable,
+        percent_of_request: percent(used, request),
+        percent_of_limit: percent(used, limit),
+        percent_of_allocatable: percent(used, allocatable),
+    }
+}
+
+fn usage(
+    used: Quantities,
+    requests: Quantities,
+    limits: Quantities,
+    allocatable: Quantities,
+) -> ResourceUsage {
+    ResourceUsage {
+        cpu: measure(
+            used.0.unwrap_or_default(),
+            requests.0,
+            limits.0,
+            allocatable.0,
+        ),
+        memory: measure(
+            used.1.unwrap_or_default(),
+            requests.1,
+            limits.1,
+            allocatable.1,
+        ),
+    }
+}
+
+/// Total of one quantity over containers, or `None` if any container leaves it unset
+fn pod_total(containers: &[&Value], pointer: &str) -> Quantities {
+    let each: Vec<Quantities> = containers
+        .iter()
+        .map(|c| quantities(c.pointer(pointer)))
+        .collect();
+    (
+        each.iter().map(|q| q.0).sum(),
+        each.iter().map(|q| q.1).sum(),
+    )
+}
+
+/// Joins one PodMetrics with its pod's spec and its node's allocatable
+fn pod_usage(
+    metrics: &Value,
+    pod: Option<&Value>,
+    allocatable: &HashMap<String, Quantities>,
+) -> PodUsage {
+    let node = pod
+        .and_then(|p| str_at(p, "/spec/nodeName"))
+        .map(String::from);
+    let node_allocatable = node
+        .as_ref()
+        .and_then(|n| allocatable.get(n))
+        .copied()
+        .unwrap_or_default();
+    let specs: Vec<&Value> = pod
+        .and_then(|p| p.pointer("/spec/containers"))
+        .and_then(|c| c.as_array())
+        .map(|c| c.iter().collect())
+        .unwrap_or_default();
+    let spec_of = |name: &str| {
+        specs
+            .iter()
+            .find(|c| c.get("name").and_then(|n| n.as_str()) == Some(name))
+            .copied()
+    };
+
+    let mut used = (0, 0);
+    let containers: Vec<ContainerUsage> = metrics
+        .get("containers")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .map(|container| {
+            let name = container
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or_default();
+            let spec = spec_of(name);
+            let current = quantities(container.get("usage"));
+            used.0 += current.0.unwrap_or_default();
+            used.1 += current.1.unwrap_or_default();
+            ContainerUsage {
+                name: name.to_string(),
+                usage: usage(
+                    current,
+                    quantities(spec.and_then(|s| s.pointer("/resources/requests"))),
+                    quantities(spec.and_then(|s| s.pointer("/resources/limits"))),
+                    node_allocatable,
+                ),
+            }
+        })
+        .collect();
+
+    let (requests, limits) = if specs.is_empty() {
+        ((None, None), (None, None))
+    } else {
+        (
+            pod_total(&specs, "/resources/requests"),
+            pod_total(&specs, "/resources/limits"),
+        )
+    };
+    PodUsage {
+        name: str_at(metrics, "/metadata/name")
+            .unwrap_or_default()
+            .to_string(),
+        namespace: str_at(metrics, "/metadata/namespace")
+            .unwrap_or_default()
+            .to_string(),
+        node,
+        timestamp: time_at(metrics, "/timestamp"),
+        window: str_at(metrics, "/window").map(String::from),
+        usage: usage(
+            (Some(used.0), Some(used.1)),
+            requests,
+            limits,
+            node_allocatable,
+        ),
+        containers,
+    }
+}
+
+fn node_usage(metrics: &Value, allocatable: &HashMap<String, Quantities>) -> NodeUsage {
+    let name = str_at(metrics, "/metadata/name").unwrap_or_default();
+    NodeUsage {
+        name: name.to_string(),
+        timestamp: time_at(metrics, "/timestamp"),
+        window: str_at(metrics, "/window").map(String::from),
+        usage: usage(
+            quantities(metrics.get("usage")),
+            (None, None),
+            (None, None),
+            allocatable.get(name).copied().unwrap_or_default(),
+        ),
+    }
+}
+
+/// Allocatable cpu and memory by node name. Users who may not list nodes still get pod
+/// usage, only without the share of the node.
+async fn node_allocatable(client: Client) -> HashMap<String, Quantities> {
+    let nodes = match K8sClient::list_resources_with_client(client, K8sResourceType::Node).await {
+        Ok(nodes) => nodes,
+        Err(e) => {
+            tracing::debug!("Node allocatable unavailable: {}", e);
+            return HashMap::new();
+        }
+    };
+    nodes
+        .iter()
+        .filter_map(|node| {
+            let name = str_at(node, "/metadata/name")?;
+            Some((
+                name.to_string(),
+                quantities(node.pointer("/status/allocatable")),
+            ))
+        })
+        .collect()
+}
+
+fn key_of(resource: &Value) -> (String, String) {
+    (
+        str_at(resource, "/metadata/namespace")
+            .unwrap_or_default()
+            .to_string(),
+        str_at(resource, "/metadata/name")
+            .unwrap_or_default()
+            .to_string(),
+    )
+}
+
+impl K8sClient {
+    /// Helper to read pod usage from metrics-server, joined with the pods' requests and
+    /// limits, using a provided client. All namespaces when `namespace` is `None`.
+    pub(crate) async fn list_pod_usage_with_client(
+        client: Client,
+        namespace: Option<&str>,
+    ) -> Result<UsageReport<PodUsage>, Box<dyn Error + Send + Sync>> {
+        let metrics = match list_metrics(
+            client.clone(),
+            metrics_resource("PodMetrics", "pods"),
+            namespace,
+        )
+        .await?
+        {
+            Ok(metrics) => metrics,
+            Err(message) => return Ok(unavailable(message)),
+        };
+        let (pods, allocatable) = tokio::join!(
+            async {
+                match namespace {
+                    Some(namespace) => {
+                        list_objects(client.clone(), K8sResourceType::Pod, namespace).await
+                    }
+                    None => {
+                        Self::list_resources_with_client(client.clone(), K8sResourceType::Pod).await
+                    }
+                }
+            },
+            node_allocatable(client.clone()),
+        );
+        let pods: HashMap<(String, String), Value> =
+            pods?.into_iter().map(|pod| (key_of(&pod), pod)).collect();
+
+        let mut items: Vec<PodUsage> = metrics
+            .iter()
+            .map(|m| pod_usage(m, pods.get(&key_of(m)), &allocatable))
+            .collect();
+        items.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+        Ok(UsageReport {
+            metrics_available: true,
+            message: None,
+            items,
+        })
+    }
+
+    /// Helper to read node usage from metrics-server against allocatable, using a provided
+    /// client
+    pub(crate) async fn list_node_usage_with_client(
+        client: Client,
+    ) -> Result<UsageReport<NodeUsage>, Box<dyn Error + Send + Sync>> {
+        let metrics = match list_metrics(
+            client.clone(),
+            metrics_resource("NodeMetrics", "nodes"),
+            None,
+        )
+        .await?
+        {
+            Ok(metrics) => metrics,
+            Err(message) => return Ok(unavailable(message)),
+        };
+        let allocatable = node_allocatable(client).await;
+        let mut items: Vec<NodeUsage> = metrics
+            .iter()
+            .map(|m| node_usage(m, &allocatable))
+            .collect();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(UsageReport {
+            metrics_available: true,
+            message: None,
+            items,
+        })
+    }
+
+    /// Fills in `usage` on the graph's Pod and Node nodes, joining the metrics with the
+    /// pods the graph already holds. Metrics and allocatable are each read once, side by
+    /// side. A graph is still worth showing without usage, so failures are only logged.
+    pub(crate) async fn attach_graph_usage_with_client(client: Client, graph: &mut GraphData) {
+        let namespaces: BTreeSet<&str> = graph
+            .nodes
+            .iter()
+            .filter(|n| n.resource_type == "Pod")
+            .filter_map(|n| str_at(&n.data, "/metadata/namespace"))
+            .collect();
+        let has_nodes = graph.nodes.iter().any(|n| n.resource_type == "Node");
+        if namespaces.is_empty() && !has_nodes {
+            return;
+        }
+
+        let (pod_metrics, node_metrics, allocatable) = tokio::join!(
+            futures::future::join_all(namespaces.iter().map(|namespace| list_metrics(
+                client.clone(),
+                metrics_resource("PodMetrics", "pods"),
+                Some(namespace)
+            ))),
+            async {
+                if !has_nodes {
+                    return Ok(Ok(Vec::new()));
+                }
+                list_metrics(
+                    client.clone(),
+                    metrics_resource("NodeMetrics", "nodes"),
+                    None,
+                )
+                .await
+            },
+            node_allocatable(client.clone()),
+        );
+        let mut pods = HashMap::new();
+        for metrics in pod_metrics {
+            match metrics {
+                Ok(Ok(metrics)) => pods.extend(metrics.into_iter().map(|m| (key_of(&m), m))),
+                Ok(Err(message)) => tracing::debug!("Pod usage unavailable: {}", message),
+                Err(e) => tracing::warn!("Failed to read pod usage: {}", e),
+            }
+        }
+        let nodes: HashMap<String, Value> = match node_metrics {
+            Ok(Ok(metrics)) => metrics.into_iter().map(|m| (key_of(&m).1, m)).collect(),
+            Ok(Err(message)) => {
+                tracing::debug!("Node usage unavailable: {}", message);
+                HashMap::new()
+            }
+            Err(e) => {
+                tracing::warn!("Failed to read node usage: {}", e);
+                HashMap::new()
+            }
+        };
+
+        for node in &mut graph.nodes {
+            node.usage = match node.resource_type.as_str() {
+                "Pod" => pods
+                    .get(&key_of(&node.data))
+                    .map(|m| pod_usage(m, Some(&node.data), &allocatable).usage),
+                "Node" => nodes
+                    .get(&key_of(&node.data).1)
+                    .map(|m| node_usage(m, &allocatable).usage),
+                _ => continue,
+            };
+        }
+    }
+}
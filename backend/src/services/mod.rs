@@ -1,4 +1,17 @@
+mod analysis;
+mod diagnose;
+mod events;
+mod graph;
+pub mod graph_export;
+mod health;
+mod jobs;
 pub mod k8s;
+mod metrics;
+mod nodes;
+mod overview;
+mod quantity;
+mod table;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
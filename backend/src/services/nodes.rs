@@ -0,0 +1,262 @@
+use super::k8s::K8sClient;
+use crate::models::{DrainEvent, DrainOptions};
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube::api::{DeleteParams, EvictParams, ListParams, Patch, PatchParams};
+use kube::{Api, Client};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Static pods carry this annotation on their API mirror; evicting them does nothing
+const MIRROR_POD_ANNOTATION: &str = "kubernetes.io/config.mirror";
+
+/// What a drain should do with one pod on the node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrainDecision {
+    Evict,
+    Skip(String),
+    Block(String),
+}
+
+impl K8sClient {
+    /// Helper to cordon (`true`) or uncordon (`false`) a node using a provided client, exposed for testing
+    pub(crate) async fn set_node_unschedulable_with_client(
+        client: Client,
+        name: &str,
+        unschedulable: bool,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let nodes: Api<Node> = Api::all(client);
+        let patch = serde_json::json!({ "spec": { "unschedulable": unschedulable } });
+
+        let node = nodes
+            .patch(name, &PatchParams::default(), &Patch::Merge(patch))
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        Ok(serde_json::to_value(node).unwrap_or_default())
+    }
+
+    /// Applies the same pod filters as `kubectl drain`
+    pub(crate) fn classify_drain_pod(pod: &Pod, options: &DrainOptions) -> DrainDecision {
+        let annotations = pod.metadata.annotations.as_ref();
+        if annotations.is_some_and(|a| a.contains_key(MIRROR_POD_ANNOTATION)) {
+            return DrainDecision::Skip("static (mirror) pod".to_string());
+        }
+
+        let controller = pod
+            .metadata
+            .owner_references
+            .as_ref()
+            .and_then(|refs| refs.iter().find(|r| r.controller == Some(true)));
+        if controller.is_some_and(|c| c.kind == "DaemonSet") {
+            return DrainDecision::Skip("managed by a DaemonSet".to_string());
+        }
+
+        let phase = pod.status.as_ref().and_then(|s| s.phase.as_deref());
+        if matches!(phase, Some("Succeeded") | Some("Failed")) {
+            return DrainDecision::Evict;
+        }
+
+        if controller.is_none() && !options.force {
+            return DrainDecision::Block(
+                "not managed by a controller and would not be recreated (set force)".to_string(),
+            );
+        }
+
+        let uses_empty_dir = pod
+            .spec
+            .as_ref()
+            .and_then(|s| s.volumes.as_ref())
+            .is_some_and(|volumes| volumes.iter().any(|v| v.empty_dir.is_some()));
+        if uses_empty_dir && !options.delete_emptydir_data {
+            return DrainDecision::Block(
+                "uses emptyDir storage that would be lost (set delete_emptydir_data)".to_string(),
+            );
+        }
+
+        DrainDecision::Evict
+    }
+
+    /// Helper to cordon and drain a node using a provided client, exposed for testing.
+    /// Evictions refused by a PodDisruptionBudget are retried, and evicted pods waited on
+    /// until they are gone, for up to `options.timeout_seconds`.
+    pub(crate) async fn drain_node_with_client(
+        client: Client,
+        name: &str,
+        options: DrainOptions,
+        progress: mpsc::Sender<DrainEvent>,
+        retry_interval: Duration,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Progress is best effort: the drain carries on if the listener goes away
+        let report = |event: DrainEvent| {
+            let progress = progress.clone();
+            async move {
+                let _ = progress.send(event).await;
+            }
+        };
+
+        Self::set_node_unschedulable_with_client(client.clone(), name, true).await?;
+        report(DrainEvent::Cordoned {
+            node: name.to_string(),
+        })
+        .await;
+
+        let pods: Api<Pod> = Api::all(client.clone());
+        let lp = ListParams::default().fields(&format!("spec.nodeName={}", name));
+        let pod_list = pods
+            .list(&lp)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+
+        let mut pending = Vec::new();
+        let mut blocking = Vec::new();
+        // A recreated pod can reuse the name, e.g. in a StatefulSet; the uid tells them apart
+        let mut uids = HashMap::new();
+        for pod in &pod_list.items {
+            let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+            let pod_name = pod.metadata.name.clone().unwrap_or_default();
+            if let Some(uid) = &pod.metadata.uid {
+                uids.insert((namespace.clone(), pod_name.clone()), uid.clone());
+            }
+            match Self::classify_drain_pod(pod, &options) {
+                DrainDecision::Evict => pending.push((namespace, pod_name)),
+                DrainDecision::Skip(reason) => {
+                    report(DrainEvent::Skipped {
+                        namespace,
+                        name: pod_name,
+                        reason,
+                    })
+                    .await
+                }
+                DrainDecision::Block(reason) => {
+                    blocking.push(format!("{}/{}", namespace, pod_name));
+                    report(DrainEvent::Blocked {
+                        namespace,
+                        name: pod_name,
+                        reason,
+                    })
+                    .await;
+                }
+            }
+        }
+
+        let evict_params = EvictParams {
+            delete_options: options.grace_period_seconds.map(|grace| DeleteParams {
+                grace_period_seconds: Some(grace),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(options.timeout_seconds);
+        let mut evicted = 0;
+        let mut terminating = Vec::new();
+        let mut reported_blocked = HashSet::new();
+
+        loop {
+            let mut retry = Vec::new();
+            for (namespace, pod_name) in pending {
+                let api: Api<Pod> = Api::namespaced(client.clone(), &namespace);
+                match api.evict(&pod_name, &evict_params).await {
+                    Ok(_) => {
+                        evicted += 1;
+                        report(DrainEvent::Evicted {
+                            namespace: namespace.clone(),
+                            name: pod_name.clone(),
+                        })
+                        .await;
+                        terminating.push((namespace, pod_name));
+                    }
+                    // 404: the pod went away on its own, which is just as good
+                    Err(kube::Error::Api(e)) if e.code == 404 => {
+                        evicted += 1;
+                        report(DrainEvent::Evicted {
+                            namespace,
+                            name: pod_name,
+                        })
+                        .await;
+                    }
+                    // 429: a PodDisruptionBudget doesn't allow the disruption right now
+                    Err(kube::Error::Api(e)) if e.code == 429 => {
+                        if reported_blocked.insert((namespace.clone(), pod_name.clone())) {
+                            report(DrainEvent::Blocked {
+                                namespace: namespace.clone(),
+                                name: pod_name.clone(),
+                                reason: e.message,
+                            })
+                            .await;
+                        }
+                        retry.push((namespace, pod_name));
+                    }
+                    Err(e) => {
+                        blocking.push(format!("{}/{}", namespace, pod_name));
+                        report(DrainEvent::Failed {
+                            namespace,
+                            name: pod_name,
+                            reason: e.to_string(),
+                        })
+                        .await;
+                    }
+                }
+            }
+
+            pending = retry;
+            if pending.is_empty() || tokio::time::Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(retry_interval).await;
+        }
+
+        // Like `kubectl drain`, only call the node drained once evicted pods have shut down
+        loop {
+            if terminating.is_empty() {
+                break;
+            }
+            let on_node = pods
+                .list(&lp)
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+            let remaining: HashSet<(String, String)> = on_node
+                .items
+                .iter()
+                .filter(|pod| {
+                    let key = (
+                        pod.metadata.namespace.clone().unwrap_or_default(),
+                        pod.metadata.name.clone().unwrap_or_default(),
+                    );
+                    uids.get(&key)
+                        .is_none_or(|uid| pod.metadata.uid.as_ref() == Some(uid))
+                })
+                .map(|pod| {
+                    (
+                        pod.metadata.namespace.clone().unwrap_or_default(),
+                        pod.metadata.name.clone().unwrap_or_default(),
+                    )
+                })
+                .collect();
+            let (still_there, gone): (Vec<_>, Vec<_>) = terminating
+                .into_iter()
+                .partition(|pod| remaining.contains(pod));
+            for (namespace, pod_name) in gone {
+                report(DrainEvent::Deleted {
+                    namespace,
+                    name: pod_name,
+                })
+                .await;
+            }
+            terminating = still_there;
+            if terminating.is_empty() || tokio::time::Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(retry_interval).await;
+        }
+
+        blocking.extend(
+            pending
+                .into_iter()
+                .chain(terminating)
+                .map(|(ns, pod)| format!("{}/{}", ns, pod)),
+        );
+        report(DrainEvent::Completed { evicted, blocking }).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use super::health::node_health;
+use super::k8s::K8sClient;
+use super::quantity::{parse_bytes, parse_millicores};
+use crate::models::{
+    ClusterOverview, EventFilter, EventType, FailingWorkload, HealthStatus, K8sResourceType,
+    NodeCounts, ResourceTotals,
+};
+use chrono::{Duration, Utc};
+use kube::Client;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// Warning Events returned with an overview; the rest are only counted
+const MAX_RECENT_WARNINGS: usize = 50;
+
+/// Controllers whose health the overview checks
+const WORKLOAD_KINDS: [K8sResourceType; 4] = [
+    K8sResourceType::Deployment,
+    K8sResourceType::StatefulSet,
+    K8sResourceType::DaemonSet,
+    K8sResourceType::Job,
+];
+
+fn str_at<'a>(resource: &'a Value, pointer: &str) -> Option<&'a str> {
+    resource.pointer(pointer).and_then(|v| v.as_str())
+}
+
+fn quantity(resources: Option<&Value>, name: &str, parse: fn(&str) -> Option<u64>) -> u64 {
+    resources
+        .and_then(|r| r.get(name))
+        .and_then(|q| q.as_str())
+        .and_then(parse)
+        .unwrap_or_default()
+}
+
+/// What the scheduler reserves for a pod: its containers together, or its largest init
+/// container if that asks for more, plus the runtime overhead
+pub(crate) fn pod_requests(pod: &Value, name: &str, parse: fn(&str) -> Option<u64>) -> u64 {
+    let requests = |key: &str| -> Vec<u64> {
+        pod.pointer(&format!("/spec/{}", key))
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .map(|c| quantity(c.pointer("/resources/requests"), name, parse))
+            .collect()
+    };
+    let containers: u64 = requests("containers").iter().sum();
+    let init = requests("initContainers")
+        .into_iter()
+        .max()
+        .unwrap_or_default();
+    containers.max(init) + quantity(pod.pointer("/spec/overhead"), name, parse)
+}
+
+/// Pods holding on to node resources: scheduled and not finished
+pub(crate) fn is_scheduled_and_running(pod: &Value) -> bool {
+    str_at(pod, "/spec/nodeName").is_some()
+        && !matches!(
+            str_at(pod, "/status/phase"),
+            Some("Succeeded") | Some("Failed")
+        )
+}
+
+fn node_counts(nodes: &[Value]) -> NodeCounts {
+    let health = |node: &Value| node_health(&K8sResourceType::Node, node, &[]);
+    NodeCounts {
+        total: nodes.len(),
+        ready: nodes
+            .iter()
+            .filter(|n| health(n).is_some_and(|h| h.status == HealthStatus::Healthy))
+            .count(),
+        unschedulable: nodes
+            .iter()
+            .filter(|n| n.pointer("/spec/unschedulable").and_then(|u| u.as_bool()) == Some(true))
+            .count(),
+    }
+}
+
+fn resource_totals(nodes: &[Value], pods: &[Value]) -> ResourceTotals {
+    let allocatable = |name: &str, parse: fn(&str) -> Option<u64>| -> u64 {
+        nodes
+            .iter()
+            .map(|n| quantity(n.pointer("/status/allocatable"), name, parse))
+            .sum()
+    };
+    let requested = |name: &str, parse: fn(&str) -> Option<u64>| -> u64 {
+        pods.iter()
+            .filter(|p| is_scheduled_and_running(p))
+            .map(|p| pod_requests(p, name, parse))
+            .sum()
+    };
+    ResourceTotals {
+        cpu_requests_millis: requested("cpu", parse_millicores),
+        cpu_allocatable_millis: allocatable("cpu", parse_millicores),
+        memory_requests_bytes: requested("memory", parse_bytes),
+        memory_allocatable_bytes: allocatable("memory", parse_bytes),
+    }
+}
+
+/// The objects of one overview section, or nothing with the reason noted in `unavailable`
+fn section<T: Default>(
+    unavailable: &mut BTreeMap<String, String>,
+    name: &str,
+    result: Result<T, Box<dyn Error + Send + Sync>>,
+) -> T {
+    result.unwrap_or_else(|e| {
+        tracing::debug!("Overview section {} unavailable: {}", name, e);
+        unavailable.insert(name.to_string(), e.to_string());
+        T::default()
+    })
+}
+
+impl K8sClient {
+    /// Helper to summarize a cluster for its landing page using a provided client, exposed
+    /// for testing. Lists every kind once, across all namespaces. A section that can't be
+    /// read, say for lack of permission, is left empty and named in `unavailable`.
+    pub(crate) async fn get_cluster_overview_with_client(
+        client: Client,
+    ) -> Result<ClusterOverview, Box<dyn Error + Send + Sync>> {
+        let list = |resource_type| Self::list_resources_with_client(client.clone(), resource_type);
+        let warnings = EventFilter {
+            event_type: Some(EventType::Warning),
+            since: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        let (version, nodes, pods, namespaces, workloads, warnings) = tokio::join!(
+            async {
+                client
+                    .apiserver_version()
+                    .await
+                    .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+            },
+            list(K8sResourceType::Node),
+            list(K8sResourceType::Pod),
+            list(K8sResourceType::Namespace),
+            futures::future::join_all(WORKLOAD_KINDS.iter().cloned().map(list)),
+            Self::list_events_with_client(client.clone(), &warnings),
+        );
+
+        // Anyone who may talk to the cluster may read its version; failing that, it is
+        // unreachable and there is nothing to show
+        let server_version = version?.git_version;
+        let mut unavailable = BTreeMap::new();
+        let nodes = section(&mut unavailable, "nodes", nodes);
+        let pods = section(&mut unavailable, "pods", pods);
+        let namespaces = section(&mut unavailable, "namespaces", namespaces);
+        let workloads: Vec<Vec<Value>> = WORKLOAD_KINDS
+            .iter()
+            .zip(workloads)
+            .map(|(kind, objects)| {
+                let name = format!("{:?}s", kind).to_lowercase();
+                section(&mut unavailable, &name, objects)
+            })
+            .collect();
+        let warnings = section(&mut unavailable, "warnings", warnings);
+
+        let mut pods_by_phase = BTreeMap::new();
+        for pod in &pods {
+            let phase = str_at(pod, "/status/phase").unwrap_or("Unknown");
+            *pods_by_phase.entry(phase.to_string()).or_insert(0) += 1;
+        }
+
+        let mut failing_workloads = Vec::new();
+        for (kind, objects) in WORKLOAD_KINDS.iter().zip(&workloads) {
+            for object in objects {
+                let Some(health) = node_health(kind, object, &[]) else {
+                    continue;
+                };
+                if !matches!(health.status, HealthStatus::Error | HealthStatus::Degraded) {
+                    continue;
+                }
+                failing_workloads.push(FailingWorkload {
+                    kind: format!("{:?}", kind),
+                    name: str_at(object, "/metadata/name")
+                        .unwrap_or_default()
+                        .to_string(),
+                    namespace: str_at(object, "/metadata/namespace")
+                        .unwrap_or_default()
+                        .to_string(),
+                    health,
+                });
+            }
+        }
+
+        let mut namespaces: Vec<String> = namespaces
+            .iter()
+            .filter_map(|n| str_at(n, "/metadata/name").map(String::from))
+            .collect();
+        namespaces.sort();
+
+        Ok(ClusterOverview {
+            server_version,
+            nodes: node_counts(&nodes),
+            namespaces,
+            pods_by_phase,
+            failing_workloads,
+            warning_count: warnings.len(),
+            recent_warnings: warnings.into_iter().take(MAX_RECENT_WARNINGS).collect(),
+            resources: resource_totals(&nodes, &pods),
+            generated_at: Utc::now(),
+            unavailable,
+        })
+    }
+}
@@ -0,0 +1,53 @@
+//! Parsing of Kubernetes resource quantities such as `512Mi` or `1.5G`
+
+const BINARY_SUFFIXES: [(&str, f64); 6] = [
+    ("Ki", 1024.0),
+    ("Mi", 1_048_576.0),
+    ("Gi", 1_073_741_824.0),
+    ("Ti", 1_099_511_627_776.0),
+    ("Pi", 1_125_899_906_842_624.0),
+    ("Ei", 1_152_921_504_606_846_976.0),
+];
+
+const DECIMAL_SUFFIXES: [(&str, f64); 9] = [
+    ("n", 1e-9),
+    ("u", 1e-6),
+    ("m", 1e-3),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+    ("T", 1e12),
+    ("P", 1e15),
+    ("E", 1e18),
+];
+
+/// Parses a quantity into its plain value, e.g. `1Ki` is 1024, `250m` is 0.25 and the
+/// nanocores metrics-server reports, `5000000n`, are 0.005
+pub(crate) fn parse_quantity(quantity: &str) -> Option<f64> {
+    let quantity = quantity.trim();
+    let (number, multiplier) = BINARY_SUFFIXES
+        .iter()
+        .chain(DECIMAL_SUFFIXES.iter())
+        .find_map(|(suffix, multiplier)| {
+            quantity
+                .strip_suffix(suffix)
+                .map(|number| (number, *multiplier))
+        })
+        .unwrap_or((quantity, 1.0));
+    let value: f64 = number.parse().ok()?;
+    value.is_finite().then_some(value * multiplier)
+}
+
+/// Parses a storage or memory quantity into bytes
+pub(crate) fn parse_bytes(quantity: &str) -> Option<u64> {
+    parse_quantity(quantity)
+        .filter(|bytes| *bytes >= 0.0)
+        .map(|bytes| bytes.ceil() as u64)
+}
+
+/// Parses a cpu quantity such as `250m` or `2` into millicores
+pub(crate) fn parse_millicores(quantity: &str) -> Option<u64> {
+    parse_quantity(quantity)
+        .filter(|cores| *cores >= 0.0)
+        .map(|cores| (cores * 1000.0).ceil() as u64)
+}
@@ -0,0 +1,838 @@
+use super::k8s::K8sClient;
+use crate::models::{K8sResourceType, ResourceTable, TableColumn, TableOptions, TableRow};
+use axum::http::header::ACCEPT;
+use axum::http::HeaderValue;
+use chrono::{DateTime, Utc};
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::api::{ApiResource, DynamicObject, ListParams};
+use kube::core::Request;
+use kube::{Api, Client};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::error::Error;
+
+/// Asks for a Table, and for the plain list from servers that can't render one
+const TABLE_ACCEPT: &str = "application/json;as=Table;v=v1;g=meta.k8s.io,application/json";
+
+fn str_at<'a>(resource: &'a Value, pointer: &str) -> &'a str {
+    resource
+        .pointer(pointer)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+}
+
+fn int_at(resource: &Value, pointer: &str) -> i64 {
+    resource
+        .pointer(pointer)
+        .and_then(|v| v.as_i64())
+        .unwrap_or_default()
+}
+
+fn len_at(resource: &Value, pointer: &str) -> usize {
+    match resource.pointer(pointer) {
+        Some(Value::Array(items)) => items.len(),
+        Some(Value::Object(items)) => items.len(),
+        _ => 0,
+    }
+}
+
+fn none_if_empty(value: String) -> String {
+    if value.is_empty() {
+        "<none>".to_string()
+    } else {
+        value
+    }
+}
+
+/// Formats a duration the way kubectl prints ages: `45s`, `5m10s`, `3h`, `4d5h`, `2y30d`
+pub(crate) fn human_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (minutes, hours, days) = (seconds / 60, seconds / 3600, seconds / 86_400);
+    if seconds < 120 {
+        format!("{}s", seconds)
+    } else if minutes < 10 {
+        match seconds % 60 {
+            0 => format!("{}m", minutes),
+            s => format!("{}m{}s", minutes, s),
+        }
+    } else if hours < 3 {
+        format!("{}m", minutes)
+    } else if hours < 8 {
+        match minutes % 60 {
+            0 => format!("{}h", hours),
+            m => format!("{}h{}m", hours, m),
+        }
+    } else if hours < 48 {
+        format!("{}h", hours)
+    } else if days < 8 {
+        match hours % 24 {
+            0 => format!("{}d", days),
+            h => format!("{}d{}h", days, h),
+        }
+    } else if days < 365 * 2 {
+        format!("{}d", days)
+    } else {
+        match days % 365 {
+            0 => format!("{}y", days / 365),
+            d => format!("{}y{}d", days / 365, d),
+        }
+    }
+}
+
+/// Reads an age printed by `human_duration`, or by the API server, back into seconds
+fn parse_duration(age: &str) -> Option<i64> {
+    let mut total = 0;
+    let mut number = String::new();
+    for c in age.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'y' => 365 * 86_400,
+            _ => return None,
+        };
+        total += number.parse::<i64>().ok()? * unit;
+        number.clear();
+    }
+    (number.is_empty() && !age.is_empty()).then_some(total)
+}
+
+fn age_cell(timestamp: &str, now: DateTime<Utc>) -> Value {
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(time) => json!(human_duration(
+            (now - time.with_timezone(&Utc)).num_seconds()
+        )),
+        Err(_) => json!("<unknown>"),
+    }
+}
+
+fn column(name: &str, column_type: &str, priority: i32) -> TableColumn {
+    TableColumn {
+        name: name.to_string(),
+        column_type: column_type.to_string(),
+        description: String::new(),
+        priority,
+    }
+}
+
+/// The STATUS column of `kubectl get pods`
+fn pod_status(pod: &Value) -> String {
+    let mut reason = match str_at(pod, "/status/reason") {
+        "" => str_at(pod, "/status/phase").to_string(),
+        reason => reason.to_string(),
+    };
+    let terminated_reason = |state: &Value| -> Option<String> {
+        let terminated = state.get("terminated")?;
+        Some(match terminated.get("reason").and_then(|r| r.as_str()) {
+            Some(reason) if !reason.is_empty() => reason.to_string(),
+            _ if int_at(terminated, "/signal") != 0 => {
+                format!("Signal:{}", int_at(terminated, "/signal"))
+            }
+            _ => format!("ExitCode:{}", int_at(terminated, "/exitCode")),
+        })
+    };
+
+    let init_statuses = pod
+        .pointer("/status/initContainerStatuses")
+        .and_then(|s| s.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let mut initializing = false;
+    for (i, status) in init_statuses.iter().enumerate() {
+        let state = status.get("state").cloned().unwrap_or_default();
+        let waiting = str_at(&state, "/waiting/reason");
+        if int_at(&state, "/terminated/exitCode") == 0 && state.get("terminated").is_some() {
+            continue;
+        }
+        initializing = true;
+        reason = if let Some(terminated) = terminated_reason(&state) {
+            format!("Init:{}", terminated)
+        } else if !waiting.is_empty() && waiting != "PodInitializing" {
+            format!("Init:{}", waiting)
+        } else {
+            format!("Init:{}/{}", i, len_at(pod, "/spec/initContainers"))
+        };
+        break;
+    }
+
+    if !initializing {
+        let statuses = pod
+            .pointer("/status/containerStatuses")
+            .and_then(|s| s.as_array())
+            .cloned()
+            .unwrap_or_default();
+        for status in statuses.iter().rev() {
+            let state = status.get("state").cloned().unwrap_or_default();
+            let waiting = str_at(&state, "/waiting/reason");
+            if !waiting.is_empty() {
+                reason = waiting.to_string();
+            } else if let Some(terminated) = terminated_reason(&state) {
+                reason = terminated;
+            }
+        }
+    }
+
+    if pod.pointer("/metadata/deletionTimestamp").is_some() {
+        reason = "Terminating".to_string();
+    }
+    reason
+}
+
+fn pod_cells(pod: &Value) -> Vec<Value> {
+    let statuses = pod
+        .pointer("/status/containerStatuses")
+        .and_then(|s| s.as_array());
+    let ready = statuses
+        .into_iter()
+        .flatten()
+        .filter(|s| s.get("ready").and_then(|r| r.as_bool()) == Some(true))
+        .count();
+    let restarts: i64 = statuses
+        .into_iter()
+        .flatten()
+        .map(|s| int_at(s, "/restartCount"))
+        .sum();
+    vec![
+        json!(format!("{}/{}", ready, len_at(pod, "/spec/containers"))),
+        json!(pod_status(pod)),
+        json!(restarts),
+        json!(none_if_empty(str_at(pod, "/status/podIP").to_string())),
+        json!(none_if_empty(str_at(pod, "/spec/nodeName").to_string())),
+    ]
+}
+
+fn node_cells(node: &Value) -> Vec<Value> {
+    let conditions = node
+        .pointer("/status/conditions")
+        .and_then(|c| c.as_array());
+    let ready = conditions
+        .into_iter()
+        .flatten()
+        .find(|c| str_at(c, "/type") == "Ready")
+        .map(|c| str_at(c, "/status"));
+    let mut status = match ready {
+        Some("True") => "Ready".to_string(),
+        _ => "NotReady".to_string(),
+    };
+    if node
+        .pointer("/spec/unschedulable")
+        .and_then(|u| u.as_bool())
+        == Some(true)
+    {
+        status.push_str(",SchedulingDisabled");
+    }
+    let labels = node.pointer("/metadata/labels").and_then(|l| l.as_object());
+    let roles: Vec<&str> = labels
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, _)| key.strip_prefix("node-role.kubernetes.io/"))
+        .collect();
+    vec![
+        json!(status),
+        json!(none_if_empty(roles.join(","))),
+        json!(str_at(node, "/status/nodeInfo/kubeletVersion")),
+    ]
+}
+
+fn service_cells(service: &Value) -> Vec<Value> {
+    let ingress = service
+        .pointer("/status/loadBalancer/ingress")
+        .and_then(|i| i.as_array());
+    let mut external: Vec<String> = ingress
+        .into_iter()
+        .flatten()
+        .map(|i| match str_at(i, "/ip") {
+            "" => str_at(i, "/hostname").to_string(),
+            ip => ip.to_string(),
+        })
+        .collect();
+    let external_ips = service
+        .pointer("/spec/externalIPs")
+        .and_then(|i| i.as_array());
+    external.extend(
+        external_ips
+            .into_iter()
+            .flatten()
+            .filter_map(|ip| ip.as_str().map(String::from)),
+    );
+    let service_type = match str_at(service, "/spec/type") {
+        "" => "ClusterIP",
+        service_type => service_type,
+    };
+    if external.is_empty() {
+        external.push(match service_type {
+            "LoadBalancer" => "<pending>".to_string(),
+            "ExternalName" => str_at(service, "/spec/externalName").to_string(),
+            _ => "<none>".to_string(),
+        });
+    }
+    let ports = service.pointer("/spec/ports").and_then(|p| p.as_array());
+    let ports: Vec<String> = ports
+        .into_iter()
+        .flatten()
+        .map(|p| {
+            let node_port = match int_at(p, "/nodePort") {
+                0 => String::new(),
+                node_port => format!(":{}", node_port),
+            };
+            let protocol = match str_at(p, "/protocol") {
+                "" => "TCP",
+                protocol => protocol,
+            };
+            format!("{}{}/{}", int_at(p, "/port"), node_port, protocol)
+        })
+        .collect();
+    vec![
+        json!(service_type),
+        json!(none_if_empty(
+            str_at(service, "/spec/clusterIP").to_string()
+        )),
+        json!(external.join(",")),
+        json!(none_if_empty(ports.join(","))),
+    ]
+}
+
+fn ingress_cells(ingress: &Value) -> Vec<Value> {
+    let rules = ingress.pointer("/spec/rules").and_then(|r| r.as_array());
+    let hosts: Vec<&str> = rules
+        .into_iter()
+        .flatten()
+        .map(|r| match str_at(r, "/host") {
+            "" => "*",
+            host => host,
+        })
+        .collect();
+    let addresses = ingress
+        .pointer("/status/loadBalancer/ingress")
+        .and_then(|i| i.as_array());
+    let addresses: Vec<&str> = addresses
+        .into_iter()
+        .flatten()
+        .map(|i| match str_at(i, "/ip") {
+            "" => str_at(i, "/hostname"),
+            ip => ip,
+        })
+        .collect();
+    let ports = if len_at(ingress, "/spec/tls") > 0 {
+        "80, 443"
+    } else {
+        "80"
+    };
+    vec![
+        json!(none_if_empty(
+            str_at(ingress, "/spec/ingressClassName").to_string()
+        )),
+        json!(none_if_empty(hosts.join(","))),
+        json!(addresses.join(",")),
+        json!(ports),
+    ]
+}
+
+fn access_modes(resource: &Value, pointer: &str) -> String {
+    let modes = resource.pointer(pointer).and_then(|m| m.as_array());
+    modes
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m.as_str())
+        .map(|m| match m {
+            "ReadWriteOnce" => "RWO",
+            "ReadOnlyMany" => "ROX",
+            "ReadWriteMany" => "RWX",
+            "ReadWriteOncePod" => "RWOP",
+            other => other,
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Columns after NAME and before AGE, and the cells for one object
+fn printer_columns(resource_type: &K8sResourceType) -> Vec<TableColumn> {
+    let columns: &[(&str, &str, i32)] = match resource_type {
+        K8sResourceType::Pod => &[
+            ("Ready", "string", 0),
+            ("Status", "string", 0),
+            ("Restarts", "integer", 0),
+            ("IP", "string", 1),
+            ("Node", "string", 1),
+        ],
+        K8sResourceType::Deployment => &[
+            ("Ready", "string", 0),
+            ("Up-to-date", "integer", 0),
+            ("Available", "integer", 0),
+        ],
+        K8sResourceType::ReplicaSet => &[
+            ("Desired", "integer", 0),
+            ("Current", "integer", 0),
+            ("Ready", "integer", 0),
+        ],
+        K8sResourceType::StatefulSet => &[("Ready", "string", 0)],
+        K8sResourceType::DaemonSet => &[
+            ("Desired", "integer", 0),
+            ("Current", "integer", 0),
+            ("Ready", "integer", 0),
+            ("Up-to-date", "integer", 0),
+            ("Available", "integer", 0),
+        ],
+        K8sResourceType::Job => &[("Status", "string", 0), ("Completions", "string", 0)],
+        K8sResourceType::CronJob => &[
+            ("Schedule", "string", 0),
+            ("Suspend", "boolean", 0),
+            ("Active", "integer", 0),
+            ("Last Schedule", "date", 0),
+        ],
+        K8sResourceType::Node => &[
+            ("Status", "string", 0),
+            ("Roles", "string", 0),
+            ("Version", "string", 0),
+        ],
+        K8sResourceType::Service => &[
+            ("Type", "string", 0),
+            ("Cluster-IP", "string", 0),
+            ("External-IP", "string", 0),
+            ("Port(s)", "string", 0),
+        ],
+        K8sResourceType::Ingress => &[
+            ("Class", "string", 0),
+            ("Hosts", "string", 0),
+            ("Address", "string", 0),
+            ("Ports", "string", 0),
+        ],
+        K8sResourceType::IngressClass => &[("Controller", "string", 0)],
+        K8sResourceType::PersistentVolume => &[
+            ("Capacity", "string", 0),
+            ("Access Modes", "string", 0),
+            ("Reclaim Policy", "string", 0),
+            ("Status", "string", 0),
+            ("Claim", "string", 0),
+            ("StorageClass", "string", 0),
+        ],
+        K8sResourceType::PersistentVolumeClaim => &[
+            ("Status", "string", 0),
+            ("Volume", "string", 0),
+            ("Capacity", "string", 0),
+            ("Access Modes", "string", 0),
+            ("StorageClass", "string", 0),
+        ],
+        K8sResourceType::StorageClass => &[
+            ("Provisioner", "string", 0),
+            ("ReclaimPolicy", "string", 0),
+            ("VolumeBindingMode", "string", 0),
+        ],
+        K8sResourceType::ConfigMap => &[("Data", "integer", 0)],
+        K8sResourceType::Secret => &[("Type", "string", 0), ("Data", "integer", 0)],
+        K8sResourceType::Namespace => &[("Status", "string", 0)],
+        K8sResourceType::RoleBinding | K8sResourceType::ClusterRoleBinding => {
+            &[("Role", "string", 0)]
+        }
+        K8sResourceType::ServiceAccount => &[("Secrets", "integer", 0)],
+        K8sResourceType::Role | K8sResourceType::ClusterRole => &[],
+    };
+    columns
+        .iter()
+        .map(|(name, column_type, priority)| column(name, column_type, *priority))
+        .collect()
+}
+
+fn printer_cells(resource_type: &K8sResourceType, o: &Value, now: DateTime<Utc>) -> Vec<Value> {
+    let count = |pointer: &str| json!(int_at(o, pointer));
+    let text = |pointer: &str| json!(none_if_empty(str_at(o, pointer).to_string()));
+    match resource_type {
+        K8sResourceType::Pod => pod_cells(o),
+        K8sResourceType::Deployment => vec![
+            json!(format!(
+                "{}/{}",
+                int_at(o, "/status/readyReplicas"),
+                int_at(o, "/spec/replicas")
+            )),
+            count("/status/updatedReplicas"),
+            count("/status/availableReplicas"),
+        ],
+        K8sResourceType::ReplicaSet => vec![
+            count("/spec/replicas"),
+            count("/status/replicas"),
+            count("/status/readyReplicas"),
+        ],
+        K8sResourceType::StatefulSet => vec![json!(format!(
+            "{}/{}",
+            int_at(o, "/status/readyReplicas"),
+            int_at(o, "/spec/replicas")
+        ))],
+        K8sResourceType::DaemonSet => vec![
+            count("/status/desiredNumberScheduled"),
+            count("/status/currentNumberScheduled"),
+            count("/status/numberReady"),
+            count("/status/updatedNumberScheduled"),
+            count("/status/numberAvailable"),
+        ],
+        K8sResourceType::Job => {
+            let conditions = o.pointer("/status/conditions").and_then(|c| c.as_array());
+            let finished = conditions.into_iter().flatten().find(|c| {
+                matches!(str_at(c, "/type"), "Complete" | "Failed")
+                    && str_at(c, "/status") == "True"
+            });
+            let status = finished.map_or("Running", |c| str_at(c, "/type"));
+            let completions = match o.pointer("/spec/completions").and_then(|c| c.as_i64()) {
+                Some(completions) => completions.to_string(),
+                None => "1".to_string(),
+            };
+            vec![
+                json!(status),
+                json!(format!(
+                    "{}/{}",
+                    int_at(o, "/status/succeeded"),
+                    completions
+                )),
+            ]
+        }
+        K8sResourceType::CronJob => vec![
+            text("/spec/schedule"),
+            json!(o.pointer("/spec/suspend").and_then(|s| s.as_bool()) == Some(true)),
+            json!(len_at(o, "/status/active")),
+            match str_at(o, "/status/lastScheduleTime") {
+                "" => json!("<none>"),
+                time => age_cell(time, now),
+            },
+        ],
+        K8sResourceType::Node => node_cells(o),
+        K8sResourceType::Service => service_cells(o),
+        K8sResourceType::Ingress => ingress_cells(o),
+        K8sResourceType::IngressClass => vec![text("/spec/controller")],
+        K8sResourceType::PersistentVolume => {
+            let claim = match str_at(o, "/spec/claimRef/name") {
+                "" => String::new(),
+                name => format!("{}/{}", str_at(o, "/spec/claimRef/namespace"), name),
+            };
+            vec![
+                text("/spec/capacity/storage"),
+                json!(access_modes(o, "/spec/accessModes")),
+                text("/spec/persistentVolumeReclaimPolicy"),
+                text("/status/phase"),
+                json!(claim),
+                json!(str_at(o, "/spec/storageClassName")),
+            ]
+        }
+        K8sResourceType::PersistentVolumeClaim => vec![
+            text("/status/phase"),
+            json!(str_at(o, "/spec/volumeName")),
+            json!(str_at(o, "/status/capacity/storage")),
+            json!(access_modes(o, "/status/accessModes")),
+            json!(str_at(o, "/spec/storageClassName")),
+        ],
+        K8sResourceType::StorageClass => vec![
+            text("/provisioner"),
+            json!(match str_at(o, "/reclaimPolicy") {
+                "" => "Delete",
+                policy => policy,
+            }),
+            json!(match str_at(o, "/volumeBindingMode") {
+                "" => "Immediate",
+                mode => mode,
+            }),
+        ],
+        K8sResourceType::ConfigMap => {
+            vec![json!(len_at(o, "/data") + len_at(o, "/binaryData"))]
+        }
+        K8sResourceType::Secret => vec![text("/type"), json!(len_at(o, "/data"))],
+        K8sResourceType::Namespace => vec![text("/status/phase")],
+        K8sResourceType::RoleBinding | K8sResourceType::ClusterRoleBinding => vec![json!(format!(
+            "{}/{}",
+            str_at(o, "/roleRef/kind"),
+            str_at(o, "/roleRef/name")
+        ))],
+        K8sResourceType::ServiceAccount => vec![json!(len_at(o, "/secrets"))],
+        K8sResourceType::Role | K8sResourceType::ClusterRole => Vec::new(),
+    }
+}
+
+fn row(object: &Value, mut cells: Vec<Value>) -> TableRow {
+    let name = str_at(object, "/metadata/name").to_string();
+    cells.insert(0, json!(name));
+    TableRow {
+        name,
+        namespace: object
+            .pointer("/metadata/namespace")
+            .and_then(|n| n.as_str())
+            .map(String::from),
+        cells,
+    }
+}
+
+/// Builds the table kubectl would print for objects of a built-in kind
+pub(crate) fn computed_table(
+    resource_type: &K8sResourceType,
+    objects: &[Value],
+    now: DateTime<Utc>,
+) -> ResourceTable {
+    let mut columns = vec![column("Name", "string", 0)];
+    columns.extend(printer_columns(resource_type));
+    columns.push(column("Age", "date", 0));
+    let rows = objects
+        .iter()
+        .map(|object| {
+            let mut cells = printer_cells(resource_type, object, now);
+            cells.push(age_cell(str_at(object, "/metadata/creationTimestamp"), now));
+            row(object, cells)
+        })
+        .collect();
+    ResourceTable {
+        columns,
+        rows,
+        server_side: false,
+    }
+}
+
+/// Evaluates the simple JSONPath CRDs use for printer columns, e.g. `.status.conditions[0].type`
+fn json_path<'a>(object: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut current = object;
+    for segment in path.trim_start_matches('.').split('.') {
+        let (key, indices) = match segment.find('[') {
+            Some(i) => (&segment[..i], &segment[i..]),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+        for index in indices.split('[').filter(|i| !i.is_empty()) {
+            current = current.get(index.trim_end_matches(']').parse::<usize>().ok()?)?;
+        }
+    }
+    Some(current)
+}
+
+/// Builds the table for custom resources from their CRD's `additionalPrinterColumns`.
+/// Without any, the API server prints NAME and AGE, and so does this.
+pub(crate) fn custom_table(
+    printer_columns: &[Value],
+    objects: &[Value],
+    now: DateTime<Utc>,
+) -> ResourceTable {
+    let default_columns = [json!({
+        "name": "Age", "type": "date", "jsonPath": ".metadata.creationTimestamp"
+    })];
+    let printer_columns = if printer_columns.is_empty() {
+        &default_columns[..]
+    } else {
+        printer_columns
+    };
+
+    let mut columns = vec![column("Name", "string", 0)];
+    columns.extend(printer_columns.iter().map(|c| TableColumn {
+        name: str_at(c, "/name").to_string(),
+        column_type: str_at(c, "/type").to_string(),
+        description: str_at(c, "/description").to_string(),
+        priority: int_at(c, "/priority") as i32,
+    }));
+    let rows = objects
+        .iter()
+        .map(|object| {
+            let cells = printer_columns
+                .iter()
+                .map(|c| match json_path(object, str_at(c, "/jsonPath")) {
+                    None | Some(Value::Null) => Value::Null,
+                    Some(Value::String(time)) if str_at(c, "/type") == "date" => {
+                        age_cell(time, now)
+                    }
+                    Some(value) => value.clone(),
+                })
+                .collect();
+            row(object, cells)
+        })
+        .collect();
+    ResourceTable {
+        columns,
+        rows,
+        server_side: false,
+    }
+}
+
+/// Reads a server-side Table; `None` when the server sent something else
+fn parse_server_table(table: &Value) -> Option<ResourceTable> {
+    if table.get("kind").and_then(|k| k.as_str()) != Some("Table") {
+        return None;
+    }
+    let columns = table.get("columnDefinitions")?.as_array()?;
+    let rows = table.get("rows").and_then(|r| r.as_array());
+    Some(ResourceTable {
+        columns: columns
+            .iter()
+            .map(|c| TableColumn {
+                name: str_at(c, "/name").to_string(),
+                column_type: str_at(c, "/type").to_string(),
+                description: str_at(c, "/description").to_string(),
+                priority: int_at(c, "/priority") as i32,
+            })
+            .collect(),
+        rows: rows
+            .into_iter()
+            .flatten()
+            .map(|r| TableRow {
+                name: str_at(r, "/object/metadata/name").to_string(),
+                namespace: r
+                    .pointer("/object/metadata/namespace")
+                    .and_then(|n| n.as_str())
+                    .map(String::from),
+                cells: r
+                    .get("cells")
+                    .and_then(|c| c.as_array())
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect(),
+        server_side: true,
+    })
+}
+
+/// How a cell compares when sorting: numbers by value, ages by duration, the rest as text
+fn sort_key(cell: Option<&Value>, is_age: bool) -> (u8, f64, String) {
+    match cell {
+        Some(Value::Number(n)) => (0, n.as_f64().unwrap_or_default(), String::new()),
+        Some(Value::Bool(b)) => (0, f64::from(u8::from(*b)), String::new()),
+        Some(Value::String(s)) => match (is_age, parse_duration(s), s.parse::<f64>()) {
+            (true, Some(seconds), _) => (0, seconds as f64, String::new()),
+            (_, _, Ok(n)) => (0, n, String::new()),
+            _ => (1, 0.0, s.to_lowercase()),
+        },
+        // Empty cells go last
+        _ => (2, 0.0, String::new()),
+    }
+}
+
+/// Sorts rows by a column, keeping equal rows in their original order
+pub(crate) fn sort_table(
+    table: &mut ResourceTable,
+    options: &TableOptions,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(sort_by) = &options.sort_by else {
+        return Ok(());
+    };
+    let index = table
+        .columns
+        .iter()
+        .position(|c| c.name.eq_ignore_ascii_case(sort_by))
+        .ok_or_else(|| format!("Unknown column: {}", sort_by))?;
+    let is_age = table.columns[index].column_type == "date"
+        || table.columns[index].name.eq_ignore_ascii_case("age");
+
+    table.rows.sort_by(|a, b| {
+        let (a, b) = (
+            sort_key(a.cells.get(index), is_age),
+            sort_key(b.cells.get(index), is_age),
+        );
+        let ordering =
+            a.0.cmp(&b.0)
+                .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+                .then_with(|| a.2.cmp(&b.2));
+        // Empty cells stay last either way
+        if options.descending && a.0 != 2 && b.0 != 2 {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    Ok(())
+}
+
+/// Requests a list as a server-side Table. Returns the plain list instead when the server
+/// answered with one, so callers can render it themselves.
+async fn request_table(
+    client: &Client,
+    api_resource: &ApiResource,
+) -> Result<Result<ResourceTable, Vec<Value>>, Box<dyn Error + Send + Sync>> {
+    let api: Api<DynamicObject> = Api::all_with(client.clone(), api_resource);
+    let mut request = Request::new(api.resource_url())
+        .list(&ListParams::default())
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+    request
+        .headers_mut()
+        .insert(ACCEPT, HeaderValue::from_static(TABLE_ACCEPT));
+
+    let response: Value = match client.request(request).await {
+        Ok(response) => response,
+        // Aggregated APIs may refuse the Table media type outright
+        Err(kube::Error::Api(e)) if e.code == 406 => {
+            let list = api
+                .list(&ListParams::default())
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+            return Ok(Err(list
+                .items
+                .into_iter()
+                .map(|item| serde_json::to_value(item).unwrap_or_default())
+                .collect()));
+        }
+        Err(e) => return Err(Box::new(e)),
+    };
+    Ok(match parse_server_table(&response) {
+        Some(table) => Ok(table),
+        None => Err(response
+            .get("items")
+            .and_then(|i| i.as_array())
+            .cloned()
+            .unwrap_or_default()),
+    })
+}
+
+impl K8sClient {
+    /// Helper to list a kind as a table using a provided client, exposed for testing.
+    /// Uses the API server's Table rendering, and computes the same columns when it can't.
+    pub(crate) async fn list_resource_table_with_client(
+        client: Client,
+        resource_type: K8sResourceType,
+        options: TableOptions,
+    ) -> Result<ResourceTable, Box<dyn Error + Send + Sync>> {
+        let mut table = match request_table(&client, &resource_type.get_api_resource()).await? {
+            Ok(table) => table,
+            Err(objects) => computed_table(&resource_type, &objects, Utc::now()),
+        };
+        sort_table(&mut table, &options)?;
+        Ok(table)
+    }
+
+    /// Helper to list the objects of a CustomResourceDefinition, named like
+    /// `certificates.cert-manager.io`, as a table using a provided client, exposed for testing
+    pub(crate) async fn list_custom_resource_table_with_client(
+        client: Client,
+        crd_name: &str,
+        options: TableOptions,
+    ) -> Result<ResourceTable, Box<dyn Error + Send + Sync>> {
+        let crds: Api<CustomResourceDefinition> = Api::all(client.clone());
+        let crd = crds
+            .get(crd_name)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        let version = crd
+            .spec
+            .versions
+            .iter()
+            .find(|v| v.storage)
+            .or_else(|| crd.spec.versions.iter().find(|v| v.served))
+            .ok_or_else(|| format!("{} serves no versions", crd_name))?;
+        let api_resource = ApiResource {
+            group: crd.spec.group.clone(),
+            version: version.name.clone(),
+            api_version: format!("{}/{}", crd.spec.group, version.name),
+            kind: crd.spec.names.kind.clone(),
+            plural: crd.spec.names.plural.clone(),
+        };
+
+        let mut table = match request_table(&client, &api_resource).await? {
+            Ok(table) => table,
+            Err(objects) => {
+                let printer_columns: Vec<Value> = version
+                    .additional_printer_columns
+                    .iter()
+                    .flatten()
+                    .map(|c| serde_json::to_value(c).unwrap_or_default())
+                    .collect();
+                custom_table(&printer_columns, &objects, Utc::now())
+            }
+        };
+        sort_table(&mut table, &options)?;
+        Ok(table)
+    }
+}
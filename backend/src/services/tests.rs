@@ -1,7 +1,18 @@
 #[cfg(test)]
 mod tests {
+    use super::super::diagnose::diagnose;
+    use super::super::graph_export::render_graph;
+    use super::super::health::node_health;
     use super::super::k8s::K8sClient;
-    use crate::models::K8sResourceType;
+    use super::super::nodes::DrainDecision;
+    use super::super::table::computed_table;
+    use crate::models::{
+        ClusterEvent, DanglingReference, DebugContainerSpec, DrainEvent, DrainOptions, EventFilter,
+        EventType, GraphData, GraphDetail, GraphEdge, GraphFormat, GraphNode, GraphOptions,
+        HealthStatus, InvolvedObject, K8sResourceType, NamespaceGraphOptions, PatchType,
+        PodProblem, ReplaceOutcome, ResourcePatch, ServiceProblem, TableOptions,
+        UnusedResourceOptions,
+    };
     use http::{Request, Response};
     use k8s_openapi::api::core::v1::Pod;
     use k8s_openapi::List;
@@ -62,6 +73,45 @@ mod tests {
         assert_eq!(resources[0]["metadata"]["name"], "pod-generic");
     }
 
+    /// Answers mocked API requests by path, in whatever order they arrive, until the client
+    /// is dropped. A `Status` body is sent with its code; unknown paths get a NotFound
+    /// status. Returns the requested paths.
+    async fn serve_by_path<const N: usize>(
+        mut handle: mock::Handle<Request<kube::client::Body>, Response<kube::client::Body>>,
+        responses: [(&str, serde_json::Value); N],
+    ) -> Vec<String> {
+        let responses: std::collections::HashMap<String, serde_json::Value> = responses
+            .into_iter()
+            .map(|(path, body)| (path.to_string(), body))
+            .collect();
+        let mut requested = Vec::new();
+        while let Some((request, send)) = handle.next_request().await {
+            let path = request.uri().path().to_string();
+            let response = match responses.get(&path) {
+                Some(body) => Response::builder()
+                    .status(match body["kind"].as_str() {
+                        Some("Status") => body["code"].as_u64().unwrap_or(500) as u16,
+                        _ => 200,
+                    })
+                    .body(kube::client::Body::from(serde_json::to_vec(body).unwrap()))
+                    .unwrap(),
+                None => Response::builder()
+                    .status(404)
+                    .body(kube::client::Body::from(
+                        serde_json::to_vec(&serde_json::json!({
+                            "kind": "Status", "apiVersion": "v1", "status": "Failure",
+                            "reason": "NotFound", "code": 404
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            };
+            send.send_response(response);
+            requested.push(path);
+        }
+        requested
+    }
+
     #[tokio::test]
     async fn test_get_resource_graph_pod_owner() {
         let (mock_service, mut handle) =
@@ -134,6 +184,7 @@ mod tests {
             K8sResourceType::Pod,
             "my-pod",
             Some("default".to_string()),
+            GraphOptions::default(),
         )
         .await
         .unwrap();
@@ -225,6 +276,7 @@ mod tests {
             K8sResourceType::Pod,
             "target-pod",
             Some("default".to_string()),
+            GraphOptions::default(),
         )
         .await
         .unwrap();
@@ -239,4 +291,2987 @@ mod tests {
         assert_eq!(edge.target, "pod-uid");
         assert_eq!(edge.label, "selects");
     }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_walks_owner_chain_without_cycles() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let owner = |kind: &str, name: &str, uid: &str| serde_json::json!([{ "apiVersion": "v1", "kind": kind, "name": name, "uid": uid }]);
+            let responses = [
+                (
+                    "/api/v1/namespaces/default/pods/web-abc",
+                    serde_json::json!({
+                        "metadata": { "name": "web-abc", "namespace": "default", "uid": "pod-uid",
+                                      "ownerReferences": owner("ReplicaSet", "web-rs", "rs-uid") }
+                    }),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/default/replicasets/web-rs",
+                    serde_json::json!({
+                        "metadata": { "name": "web-rs", "namespace": "default", "uid": "rs-uid",
+                                      "ownerReferences": owner("Deployment", "web", "deploy-uid") }
+                    }),
+                ),
+                (
+                    // A (malformed) reference back to the pod must not be followed again
+                    "/apis/apps/v1/namespaces/default/deployments/web",
+                    serde_json::json!({
+                        "metadata": { "name": "web", "namespace": "default", "uid": "deploy-uid",
+                                      "ownerReferences": owner("Pod", "web-abc", "pod-uid") }
+                    }),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Pod,
+            "web-abc",
+            Some("default".to_string()),
+            GraphOptions {
+                depth: 5,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let ids: Vec<_> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["pod-uid", "rs-uid", "deploy-uid"]);
+        let edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("rs-uid", "pod-uid"),
+                ("deploy-uid", "rs-uid"),
+                ("pod-uid", "deploy-uid")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_statefulset_pods_claims_and_service() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let meta = |name: &str, uid: &str| serde_json::json!({ "name": name, "namespace": "default", "uid": uid });
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let mut pod_meta = meta("db-0", "pod-uid");
+            pod_meta["ownerReferences"] = serde_json::json!([
+                { "apiVersion": "apps/v1", "kind": "StatefulSet", "name": "db", "uid": "sts-uid" }
+            ]);
+
+            let responses = [
+                (
+                    "/apis/apps/v1/namespaces/default/statefulsets/db",
+                    serde_json::json!({
+                        "metadata": meta("db", "sts-uid"),
+                        "spec": {
+                            "serviceName": "db-headless",
+                            "volumeClaimTemplates": [{ "metadata": { "name": "data" } }]
+                        }
+                    }),
+                ),
+                (
+                    "/api/v1/namespaces/default/pods",
+                    list(vec![
+                        serde_json::json!({ "metadata": pod_meta }),
+                        serde_json::json!({ "metadata": meta("unrelated", "other-pod-uid") }),
+                    ]),
+                ),
+                (
+                    "/api/v1/namespaces/default/services/db-headless",
+                    serde_json::json!({ "metadata": meta("db-headless", "svc-uid") }),
+                ),
+                (
+                    "/api/v1/namespaces/default/persistentvolumeclaims",
+                    list(vec![
+                        serde_json::json!({ "metadata": meta("data-db-0", "pvc0-uid") }),
+                        serde_json::json!({ "metadata": meta("data-db-1", "pvc1-uid") }),
+                        serde_json::json!({ "metadata": meta("data-dbx-0", "other-pvc-uid") }),
+                        serde_json::json!({ "metadata": meta("data-db-old", "stale-pvc-uid") }),
+                    ]),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::StatefulSet,
+            "db",
+            Some("default".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.label.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("sts-uid", "pod-uid", "manages"),
+                ("sts-uid", "svc-uid", "governed by"),
+                ("sts-uid", "pvc0-uid", "claims"),
+                ("sts-uid", "pvc1-uid", "claims"),
+            ]
+        );
+        assert_eq!(graph.nodes.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_ingress_routes_tls_and_class() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let object = |name: &str, uid: &str| serde_json::json!({ "metadata": { "name": name, "namespace": "default", "uid": uid } });
+            let backend = |service: &str| serde_json::json!({ "service": { "name": service, "port": { "number": 80 } } });
+            let mut ingress = object("shop", "ing-uid");
+            ingress["spec"] = serde_json::json!({
+                "ingressClassName": "nginx",
+                "defaultBackend": backend("fallback"),
+                "tls": [{ "hosts": ["shop.example.com"], "secretName": "shop-tls" }],
+                "rules": [{
+                    "host": "shop.example.com",
+                    "http": { "paths": [
+                        { "path": "/api", "pathType": "Prefix", "backend": backend("api") },
+                        { "path": "/v2", "pathType": "Prefix", "backend": backend("api") }
+                    ] }
+                }]
+            });
+
+            let responses = [
+                (
+                    "/apis/networking.k8s.io/v1/namespaces/default/ingresses/shop",
+                    ingress,
+                ),
+                (
+                    "/api/v1/namespaces/default/services/fallback",
+                    object("fallback", "fallback-uid"),
+                ),
+                (
+                    "/api/v1/namespaces/default/services/api",
+                    object("api", "api-uid"),
+                ),
+                (
+                    "/api/v1/namespaces/default/secrets/shop-tls",
+                    object("shop-tls", "tls-uid"),
+                ),
+                (
+                    "/apis/networking.k8s.io/v1/ingressclasses/nginx",
+                    object("nginx", "class-uid"),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Ingress,
+            "shop",
+            Some("default".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| (e.target.as_str(), e.label.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("fallback-uid", "default backend"),
+                ("api-uid", "shop.example.com/api, shop.example.com/v2"),
+                ("tls-uid", "tls shop.example.com"),
+                ("class-uid", "class"),
+            ]
+        );
+        assert!(graph.edges.iter().all(|e| e.source == "ing-uid"));
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_pod_service_account_rbac() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let object = |name: &str, namespace: Option<&str>, uid: &str| serde_json::json!({ "metadata": { "name": name, "namespace": namespace, "uid": uid } });
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let mut pod = object("builder-0", Some("ci"), "pod-uid");
+            pod["spec"] = serde_json::json!({ "serviceAccountName": "builder", "containers": [] });
+
+            let mut role_binding = object("builder-edit", Some("ci"), "rb-uid");
+            role_binding["subjects"] =
+                serde_json::json!([{ "kind": "ServiceAccount", "name": "builder" }]);
+            role_binding["roleRef"] = serde_json::json!({
+                "apiGroup": "rbac.authorization.k8s.io", "kind": "Role", "name": "edit-jobs"
+            });
+            // Same account name, other namespace: must not match
+            let mut other_binding = object("builder-edit", Some("prod"), "other-rb-uid");
+            other_binding["subjects"] =
+                serde_json::json!([{ "kind": "ServiceAccount", "name": "builder" }]);
+
+            let mut cluster_binding = object("builder-view", None, "crb-uid");
+            cluster_binding["subjects"] = serde_json::json!([
+                { "kind": "ServiceAccount", "name": "builder", "namespace": "ci" }
+            ]);
+            cluster_binding["roleRef"] = serde_json::json!({
+                "apiGroup": "rbac.authorization.k8s.io", "kind": "ClusterRole", "name": "view"
+            });
+
+            let responses = [
+                ("/api/v1/namespaces/ci/pods/builder-0", pod),
+                (
+                    "/api/v1/namespaces/ci/serviceaccounts/builder",
+                    object("builder", Some("ci"), "sa-uid"),
+                ),
+                (
+                    "/apis/rbac.authorization.k8s.io/v1/rolebindings",
+                    list(vec![role_binding, other_binding]),
+                ),
+                (
+                    "/apis/rbac.authorization.k8s.io/v1/namespaces/ci/roles/edit-jobs",
+                    object("edit-jobs", Some("ci"), "role-uid"),
+                ),
+                (
+                    "/apis/rbac.authorization.k8s.io/v1/clusterrolebindings",
+                    list(vec![cluster_binding]),
+                ),
+                (
+                    "/apis/rbac.authorization.k8s.io/v1/clusterroles/view",
+                    object("view", None, "clusterrole-uid"),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Pod,
+            "builder-0",
+            Some("ci".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.label.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("pod-uid", "sa-uid", "runs as"),
+                ("rb-uid", "sa-uid", "binds"),
+                ("rb-uid", "role-uid", "grants"),
+                ("crb-uid", "sa-uid", "binds"),
+                ("crb-uid", "clusterrole-uid", "grants"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_shares_lists_and_reports_stats() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        let server = tokio::spawn(async move {
+            let owned = |name: &str, uid: &str, kind: &str, owner: &str, owner_uid: &str| {
+                serde_json::json!({
+                    "metadata": { "name": name, "namespace": "default", "uid": uid,
+                                  "ownerReferences": [{ "apiVersion": "apps/v1", "kind": kind, "name": owner, "uid": owner_uid }] }
+                })
+            };
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+
+            let responses = [
+                (
+                    "/apis/apps/v1/namespaces/default/deployments/web",
+                    serde_json::json!({ "metadata": { "name": "web", "namespace": "default", "uid": "deploy-uid" } }),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/default/replicasets",
+                    list(vec![
+                        owned("web-1", "rs1-uid", "Deployment", "web", "deploy-uid"),
+                        owned("web-2", "rs2-uid", "Deployment", "web", "deploy-uid"),
+                    ]),
+                ),
+                (
+                    "/api/v1/namespaces/default/pods",
+                    list(vec![
+                        owned("web-1-a", "pod1-uid", "ReplicaSet", "web-1", "rs1-uid"),
+                        owned("web-2-a", "pod2-uid", "ReplicaSet", "web-2", "rs2-uid"),
+                    ]),
+                ),
+            ];
+            serve_by_path(handle, responses).await
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Deployment,
+            "web",
+            Some("default".to_string()),
+            GraphOptions {
+                depth: 2,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut requested = server.await.unwrap();
+        requested.sort();
+        assert_eq!(
+            requested,
+            vec![
+                "/api/v1/namespaces/default/pods",
+                "/apis/apps/v1/namespaces/default/deployments/web",
+                "/apis/apps/v1/namespaces/default/replicasets",
+            ]
+        );
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.edges.len(), 4);
+        assert_eq!(graph.stats.unwrap().api_calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_fetches_repeated_dependency_once() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        let server = tokio::spawn(async move {
+            let responses = [
+                (
+                    "/api/v1/namespaces/default/pods/web",
+                    serde_json::json!({
+                        "metadata": { "name": "web", "namespace": "default", "uid": "pod-uid" },
+                        "spec": {
+                            "volumes": [{ "name": "cfg", "configMap": { "name": "web-config" } }],
+                            "containers": [{ "name": "web", "envFrom": [{ "configMapRef": { "name": "web-config" } }] }]
+                        }
+                    }),
+                ),
+                (
+                    "/api/v1/namespaces/default/configmaps/web-config",
+                    serde_json::json!({ "metadata": { "name": "web-config", "namespace": "default", "uid": "cm-uid" } }),
+                ),
+            ];
+            serve_by_path(handle, responses).await
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Pod,
+            "web",
+            Some("default".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let requested = server.await.unwrap();
+        let config_gets = requested
+            .iter()
+            .filter(|path| path.ends_with("/configmaps/web-config"))
+            .count();
+        assert_eq!(config_gets, 1);
+        assert_eq!(graph.edges.iter().filter(|e| e.label == "uses").count(), 1);
+        assert_eq!(graph.stats.unwrap().cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_summary_detail_drops_objects() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let responses = [
+                (
+                    "/api/v1/namespaces/default/pods/web",
+                    serde_json::json!({
+                        "metadata": { "name": "web", "namespace": "default", "uid": "pod-uid",
+                                      "managedFields": [{ "manager": "kubectl" }] },
+                        "spec": { "volumes": [{ "name": "cfg", "configMap": { "name": "web-config" } }] },
+                        "status": { "phase": "Running",
+                                    "conditions": [{ "type": "Ready", "status": "True" }] }
+                    }),
+                ),
+                (
+                    "/api/v1/namespaces/default/configmaps/web-config",
+                    serde_json::json!({
+                        "metadata": { "name": "web-config", "namespace": "default", "uid": "cm-uid" },
+                        "data": { "app.yaml": "a very large payload" }
+                    }),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Pod,
+            "web",
+            Some("default".to_string()),
+            GraphOptions {
+                detail: GraphDetail::Summary,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            graph.nodes[0].data,
+            serde_json::json!({
+                "kind": "Pod", "name": "web", "namespace": "default", "uid": "pod-uid",
+                "phase": "Running", "ready": true
+            })
+        );
+        assert_eq!(
+            graph.nodes[1].data,
+            serde_json::json!({
+                "kind": "ConfigMap", "name": "web-config", "namespace": "default",
+                "uid": "cm-uid", "phase": null, "ready": null
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_flags_health_and_broken_links() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let responses = [
+                (
+                    "/api/v1/namespaces/default/pods/api-0",
+                    serde_json::json!({
+                        "metadata": { "name": "api-0", "namespace": "default", "uid": "pod-uid",
+                                      "labels": { "app": "api" } },
+                        "spec": {
+                            "serviceAccountName": "api",
+                            "containers": [{ "name": "api", "env": [{ "name": "PASSWORD", "valueFrom": {
+                                "secretKeyRef": { "name": "db-creds", "key": "password" } } }] }]
+                        },
+                        "status": {
+                            "phase": "Running",
+                            "containerStatuses": [{ "name": "api", "ready": false,
+                                "state": { "waiting": { "reason": "CrashLoopBackOff" } } }]
+                        }
+                    }),
+                ),
+                (
+                    "/api/v1/namespaces/default/services",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "api", "namespace": "default", "uid": "svc-uid" },
+                        "spec": { "selector": { "app": "api" } }
+                    })]),
+                ),
+                (
+                    "/api/v1/namespaces/default/serviceaccounts/api",
+                    serde_json::json!({ "metadata": { "name": "api", "namespace": "default", "uid": "sa-uid" } }),
+                ),
+                (
+                    "/api/v1/namespaces/default/pods",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "api-0", "namespace": "default", "uid": "pod-uid",
+                                      "labels": { "app": "api" } },
+                        "status": { "phase": "Running" }
+                    })]),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Pod,
+            "api-0",
+            Some("default".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let health = |id: &str| {
+            let node = graph.nodes.iter().find(|n| n.id == id).unwrap();
+            let health = node.health.clone().unwrap();
+            (health.status, health.message.unwrap())
+        };
+        assert_eq!(
+            health("pod-uid"),
+            (HealthStatus::Error, "api: CrashLoopBackOff".to_string())
+        );
+        assert_eq!(
+            health("svc-uid"),
+            (HealthStatus::Error, "No ready endpoints".to_string())
+        );
+
+        let broken = graph
+            .edges
+            .iter()
+            .find(|e| e.error.is_some())
+            .expect("missing secret is flagged");
+        assert_eq!(broken.source, "pod-uid");
+        assert_eq!(broken.target, "missing:Secret/default/db-creds");
+        assert_eq!(broken.error.as_deref(), Some("Secret db-creds not found"));
+        assert_eq!(
+            health("missing:Secret/default/db-creds").0,
+            HealthStatus::Error
+        );
+        assert_eq!(graph.edges.iter().filter(|e| e.error.is_some()).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_flags_selector_without_pods() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let responses = [
+                (
+                    "/api/v1/namespaces/default/services/web",
+                    serde_json::json!({
+                        "metadata": { "name": "web", "namespace": "default", "uid": "svc-uid" },
+                        "spec": { "selector": { "app": "web" } }
+                    }),
+                ),
+                (
+                    "/api/v1/namespaces/default/pods",
+                    serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": [] }),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Service,
+            "web",
+            Some("default".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(
+            graph.edges[0].error.as_deref(),
+            Some("selector app=web matches no pods")
+        );
+        let service = &graph.nodes[0];
+        assert_eq!(
+            service.health.as_ref().unwrap().message.as_deref(),
+            Some("Selector matches no pods")
+        );
+    }
+
+    #[test]
+    fn test_node_health_workloads_and_claims() {
+        let deployment = serde_json::json!({
+            "spec": { "replicas": 3 }, "status": { "availableReplicas": 2 }
+        });
+        let health = node_health(&K8sResourceType::Deployment, &deployment, &[]).unwrap();
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.message.as_deref(), Some("2/3 available"));
+
+        let pending_claim = serde_json::json!({ "status": { "phase": "Pending" } });
+        let health =
+            node_health(&K8sResourceType::PersistentVolumeClaim, &pending_claim, &[]).unwrap();
+        assert_eq!(health.status, HealthStatus::Progressing);
+
+        let unschedulable = serde_json::json!({ "status": { "phase": "Pending", "conditions": [
+            { "type": "PodScheduled", "status": "False", "reason": "Unschedulable",
+              "message": "0/3 nodes are available: 3 Insufficient cpu." }
+        ] } });
+        let health = node_health(&K8sResourceType::Pod, &unschedulable, &[]).unwrap();
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(
+            health.message.as_deref(),
+            Some("0/3 nodes are available: 3 Insufficient cpu.")
+        );
+
+        assert!(node_health(&K8sResourceType::ConfigMap, &serde_json::json!({}), &[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_namespace_graph_links_and_groups_unrelated() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let meta = |name: &str, uid: &str| serde_json::json!({ "name": name, "namespace": "shop", "uid": uid });
+            let owned = |name: &str, uid: &str, kind: &str, owner: &str, owner_uid: &str| {
+                let mut m = meta(name, uid);
+                m["ownerReferences"] = serde_json::json!([
+                    { "apiVersion": "apps/v1", "kind": kind, "name": owner, "uid": owner_uid }
+                ]);
+                m
+            };
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let mut pod_meta = owned("web-1-a", "pod-uid", "ReplicaSet", "web-1", "rs-uid");
+            pod_meta["labels"] = serde_json::json!({ "app": "web" });
+
+            let responses = [
+                (
+                    "/api/v1/namespaces/shop/services",
+                    list(vec![serde_json::json!({
+                        "metadata": meta("web", "svc-uid"),
+                        "spec": { "selector": { "app": "web" } }
+                    })]),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/shop/deployments",
+                    list(vec![
+                        serde_json::json!({ "metadata": meta("web", "deploy-uid") }),
+                    ]),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/shop/replicasets",
+                    list(vec![serde_json::json!({
+                        "metadata": owned("web-1", "rs-uid", "Deployment", "web", "deploy-uid")
+                    })]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/pods",
+                    list(vec![serde_json::json!({
+                        "metadata": pod_meta,
+                        "spec": { "volumes": [{ "name": "cfg", "configMap": { "name": "web-config" } }] }
+                    })]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/configmaps",
+                    list(vec![
+                        serde_json::json!({ "metadata": meta("web-config", "cm-uid") }),
+                        serde_json::json!({ "metadata": meta("kube-root-ca.crt", "ca-uid") }),
+                        serde_json::json!({ "metadata": meta("leftover", "leftover-uid") }),
+                    ]),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let options = NamespaceGraphOptions {
+            exclude: vec![
+                K8sResourceType::Ingress,
+                K8sResourceType::CronJob,
+                K8sResourceType::StatefulSet,
+                K8sResourceType::DaemonSet,
+                K8sResourceType::Job,
+                K8sResourceType::Secret,
+                K8sResourceType::PersistentVolumeClaim,
+                K8sResourceType::ServiceAccount,
+            ],
+            collapse_unrelated: true,
+            ..Default::default()
+        };
+        let graph = K8sClient::get_namespace_graph_with_client(client, "shop", options)
+            .await
+            .unwrap();
+
+        let edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.label.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("svc-uid", "pod-uid", "selects"),
+                ("deploy-uid", "rs-uid", "manages"),
+                ("rs-uid", "pod-uid", "manages"),
+                ("pod-uid", "cm-uid", "uses"),
+            ]
+        );
+
+        let ids: Vec<_> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "svc-uid",
+                "deploy-uid",
+                "rs-uid",
+                "pod-uid",
+                "cm-uid",
+                "group-ConfigMap"
+            ]
+        );
+        let group = graph.nodes.last().unwrap();
+        assert_eq!(group.resource_type, "Group");
+        assert_eq!(
+            group.data["members"],
+            serde_json::json!(["kube-root-ca.crt", "leftover"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_dangling_references_reports_field_paths() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let named = |name: &str| serde_json::json!({ "metadata": { "name": name } });
+            let responses = [
+                (
+                    "/api/v1/namespaces/shop/pods",
+                    list(vec![
+                        serde_json::json!({
+                            "metadata": { "name": "debug", "labels": { "app": "web" } },
+                            "spec": { "volumes": [{ "name": "tls", "secret": { "secretName": "web-tls" } }] }
+                        }),
+                        // Covered by its Deployment's template
+                        serde_json::json!({
+                            "metadata": { "name": "web-1-a", "ownerReferences": [{
+                                "apiVersion": "apps/v1", "kind": "ReplicaSet", "name": "web-1",
+                                "uid": "rs-uid", "controller": true }] },
+                            "spec": { "volumes": [{ "name": "cfg", "configMap": { "name": "web-config" } }] }
+                        }),
+                        // A standalone ReplicaSet and an operator have no template scanned
+                        serde_json::json!({
+                            "metadata": { "name": "legacy-a", "ownerReferences": [{
+                                "apiVersion": "apps/v1", "kind": "ReplicaSet", "name": "legacy",
+                                "uid": "legacy-uid", "controller": true }] },
+                            "spec": { "volumes": [{ "name": "cfg", "configMap": { "name": "legacy-config" } }] }
+                        }),
+                        serde_json::json!({
+                            "metadata": { "name": "cache-0", "ownerReferences": [{
+                                "apiVersion": "example.com/v1", "kind": "CacheCluster", "name": "cache",
+                                "uid": "cache-uid", "controller": true }] },
+                            "spec": { "volumes": [{ "name": "auth", "secret": { "secretName": "cache-auth" } }] }
+                        }),
+                    ]),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/shop/replicasets",
+                    list(vec![
+                        serde_json::json!({ "metadata": { "name": "web-1", "ownerReferences": [{
+                            "apiVersion": "apps/v1", "kind": "Deployment", "name": "web",
+                            "uid": "deploy-uid", "controller": true }] } }),
+                        named("legacy"),
+                    ]),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/shop/deployments",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "web" },
+                        "spec": { "template": { "spec": {
+                            "serviceAccountName": "web",
+                            "volumes": [
+                                { "name": "cfg", "configMap": { "name": "web-config" } },
+                                { "name": "extra", "secret": { "secretName": "extra", "optional": true } }
+                            ],
+                            "containers": [{ "name": "web" }]
+                        } } }
+                    })]),
+                ),
+                ("/apis/apps/v1/namespaces/shop/statefulsets", list(vec![])),
+                ("/apis/apps/v1/namespaces/shop/daemonsets", list(vec![])),
+                ("/apis/batch/v1/namespaces/shop/jobs", list(vec![])),
+                ("/apis/batch/v1/namespaces/shop/cronjobs", list(vec![])),
+                (
+                    "/api/v1/namespaces/shop/services",
+                    list(vec![
+                        serde_json::json!({ "metadata": { "name": "web" }, "spec": { "selector": { "app": "web" } } }),
+                        serde_json::json!({ "metadata": { "name": "old" }, "spec": { "selector": { "app": "old" } } }),
+                    ]),
+                ),
+                (
+                    "/apis/networking.k8s.io/v1/namespaces/shop/ingresses",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "shop" },
+                        "spec": { "rules": [{ "http": { "paths": [
+                            { "path": "/", "backend": { "service": { "name": "web" } } },
+                            { "path": "/api", "backend": { "service": { "name": "api" } } }
+                        ] } }] }
+                    })]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/persistentvolumeclaims",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "data" }, "spec": {}, "status": { "phase": "Pending" }
+                    })]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/configmaps",
+                    list(vec![named("kube-root-ca.crt")]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/secrets",
+                    list(vec![named("web-tls")]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/serviceaccounts",
+                    list(vec![named("default")]),
+                ),
+                ("/api/v1/persistentvolumes", list(vec![])),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let findings = K8sClient::find_dangling_references_with_client(client, "shop")
+            .await
+            .unwrap();
+
+        let summary: Vec<_> = findings
+            .iter()
+            .map(|f| {
+                (
+                    format!("{}/{}", f.kind, f.name),
+                    f.field_path.as_str(),
+                    f.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "Pod/legacy-a".to_string(),
+                    "spec.volumes[0].configMap.name",
+                    "ConfigMap legacy-config does not exist"
+                ),
+                (
+                    "Pod/cache-0".to_string(),
+                    "spec.volumes[0].secret.secretName",
+                    "Secret cache-auth does not exist"
+                ),
+                (
+                    "Deployment/web".to_string(),
+                    "spec.template.spec.volumes[0].configMap.name",
+                    "ConfigMap web-config does not exist"
+                ),
+                (
+                    "Deployment/web".to_string(),
+                    "spec.template.spec.serviceAccountName",
+                    "ServiceAccount web does not exist"
+                ),
+                (
+                    "Service/old".to_string(),
+                    "spec.selector",
+                    "selector app=old matches no pods"
+                ),
+                (
+                    "Ingress/shop".to_string(),
+                    "spec.rules[0].http.paths[1].backend.service.name",
+                    "Service api does not exist"
+                ),
+                (
+                    "PersistentVolumeClaim/data".to_string(),
+                    "spec.volumeName",
+                    "claim is Pending and not bound to any volume"
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_dangling_references_without_access_to_volumes() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let responses = [
+                ("/api/v1/namespaces/shop/pods", list(vec![])),
+                ("/apis/apps/v1/namespaces/shop/deployments", list(vec![])),
+                ("/apis/apps/v1/namespaces/shop/statefulsets", list(vec![])),
+                ("/apis/apps/v1/namespaces/shop/daemonsets", list(vec![])),
+                ("/apis/batch/v1/namespaces/shop/jobs", list(vec![])),
+                ("/apis/batch/v1/namespaces/shop/cronjobs", list(vec![])),
+                ("/apis/apps/v1/namespaces/shop/replicasets", list(vec![])),
+                ("/api/v1/namespaces/shop/services", list(vec![])),
+                (
+                    "/apis/networking.k8s.io/v1/namespaces/shop/ingresses",
+                    list(vec![]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/persistentvolumeclaims",
+                    list(vec![
+                        serde_json::json!({
+                            "metadata": { "name": "data" },
+                            "spec": { "volumeName": "pv-data" },
+                            "status": { "phase": "Bound" }
+                        }),
+                        serde_json::json!({
+                            "metadata": { "name": "scratch" }, "spec": {}, "status": { "phase": "Pending" }
+                        }),
+                    ]),
+                ),
+                ("/api/v1/namespaces/shop/secrets", list(vec![])),
+                ("/api/v1/namespaces/shop/configmaps", list(vec![])),
+                ("/api/v1/namespaces/shop/serviceaccounts", list(vec![])),
+                (
+                    "/api/v1/persistentvolumes",
+                    serde_json::json!({
+                        "kind": "Status", "apiVersion": "v1", "status": "Failure", "code": 403,
+                        "reason": "Forbidden",
+                        "message": "persistentvolumes is forbidden: cannot list resource at the cluster scope"
+                    }),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let findings = K8sClient::find_dangling_references_with_client(client, "shop")
+            .await
+            .unwrap();
+
+        // The bound claim's volume can't be checked; the unbound claim still is
+        let claims: Vec<_> = findings.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(claims, vec!["scratch"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_unused_resources_reports_age_and_size() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let created = "2020-01-01T00:00:00Z";
+            let endpoint_slice = |name: &str, service: &str, address: &str| {
+                serde_json::json!({
+                    "metadata": { "name": name, "labels": { "kubernetes.io/service-name": service } },
+                    "addressType": "IPv4",
+                    "endpoints": [{ "addresses": [address], "conditions": { "ready": true } }]
+                })
+            };
+            let replica_set = |name: &str, revision: &str, replicas: i64| {
+                serde_json::json!({
+                    "metadata": {
+                        "name": name, "namespace": "shop", "creationTimestamp": created,
+                        "annotations": { "deployment.kubernetes.io/revision": revision },
+                        "ownerReferences": [{ "apiVersion": "apps/v1", "kind": "Deployment",
+                            "name": "web", "uid": "web-uid", "controller": true }]
+                    },
+                    "spec": { "replicas": replicas }
+                })
+            };
+            let responses = [
+                (
+                    "/api/v1/namespaces/shop/pods",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "web-a", "namespace": "shop", "labels": { "app": "web" } },
+                        "spec": {
+                            "imagePullSecrets": [{ "name": "registry" }],
+                            "volumes": [{ "name": "cfg", "projected": { "sources": [
+                                { "configMap": { "name": "web-config" } }
+                            ] } }]
+                        },
+                        "status": { "phase": "Running", "conditions": [{ "type": "Ready", "status": "True" }] }
+                    })]),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/shop/deployments",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "web", "uid": "web-uid",
+                            "annotations": { "deployment.kubernetes.io/revision": "4" } },
+                        "spec": { "revisionHistoryLimit": 1, "template": { "spec": {} } }
+                    })]),
+                ),
+                ("/apis/apps/v1/namespaces/shop/statefulsets", list(vec![])),
+                ("/apis/apps/v1/namespaces/shop/daemonsets", list(vec![])),
+                (
+                    "/apis/batch/v1/namespaces/shop/jobs",
+                    list(vec![
+                        serde_json::json!({
+                            "metadata": { "name": "migrate", "namespace": "shop", "creationTimestamp": created },
+                            "status": { "completionTime": "2020-01-02T00:00:00Z" }
+                        }),
+                        serde_json::json!({
+                            "metadata": { "name": "running", "namespace": "shop", "creationTimestamp": created },
+                            "status": { "active": 1 }
+                        }),
+                    ]),
+                ),
+                ("/apis/batch/v1/namespaces/shop/cronjobs", list(vec![])),
+                (
+                    "/apis/networking.k8s.io/v1/namespaces/shop/ingresses",
+                    list(vec![]),
+                ),
+                ("/api/v1/namespaces/shop/serviceaccounts", list(vec![])),
+                (
+                    "/api/v1/namespaces/shop/configmaps",
+                    list(vec![
+                        serde_json::json!({ "metadata": { "name": "kube-root-ca.crt", "namespace": "shop" } }),
+                        serde_json::json!({ "metadata": { "name": "web-config", "namespace": "shop" } }),
+                        serde_json::json!({
+                            "metadata": { "name": "stale", "namespace": "shop", "creationTimestamp": created },
+                            "data": { "a": "hello" }
+                        }),
+                    ]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/secrets",
+                    list(vec![
+                        serde_json::json!({ "metadata": { "name": "registry", "namespace": "shop" } }),
+                        serde_json::json!({
+                            "metadata": { "name": "old-token", "namespace": "shop", "creationTimestamp": created },
+                            "type": "Opaque",
+                            "data": { "token": "aGVsbG8=" }
+                        }),
+                    ]),
+                ),
+                (
+                    "/api/v1/persistentvolumes",
+                    list(vec![
+                        serde_json::json!({
+                            "metadata": { "name": "pv-1", "creationTimestamp": created },
+                            "spec": { "capacity": { "storage": "1Gi" },
+                                "persistentVolumeReclaimPolicy": "Retain",
+                                "claimRef": { "namespace": "shop", "name": "data" } },
+                            "status": { "phase": "Released" }
+                        }),
+                        serde_json::json!({
+                            "metadata": { "name": "pv-2" },
+                            "spec": { "claimRef": { "namespace": "other", "name": "data" } },
+                            "status": { "phase": "Released" }
+                        }),
+                    ]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/persistentvolumeclaims",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "scratch", "namespace": "shop", "creationTimestamp": created },
+                        "spec": { "resources": { "requests": { "storage": "500Mi" } } },
+                        "status": { "phase": "Pending" }
+                    })]),
+                ),
+                (
+                    "/apis/apps/v1/namespaces/shop/replicasets",
+                    list(vec![
+                        replica_set("web-4", "4", 2),
+                        replica_set("web-3", "3", 0),
+                        replica_set("web-2", "2", 0),
+                        replica_set("web-1", "1", 0),
+                    ]),
+                ),
+                (
+                    "/api/v1/namespaces/shop/services",
+                    list(vec![
+                        serde_json::json!({ "metadata": { "name": "web", "namespace": "shop" },
+                            "spec": { "selector": { "app": "web" } } }),
+                        serde_json::json!({ "metadata": { "name": "old", "namespace": "shop", "creationTimestamp": created },
+                            "spec": { "selector": { "app": "old" } } }),
+                        // Without selectors, only their EndpointSlices tell them apart
+                        serde_json::json!({ "metadata": { "name": "legacy-db", "namespace": "shop", "creationTimestamp": created },
+                            "spec": { "ports": [{ "port": 5432 }] } }),
+                        serde_json::json!({ "metadata": { "name": "partner-api", "namespace": "shop", "creationTimestamp": created },
+                            "spec": { "ports": [{ "port": 443 }] } }),
+                        serde_json::json!({ "metadata": { "name": "docs", "namespace": "shop" },
+                            "spec": { "type": "ExternalName", "externalName": "docs.example.com" } }),
+                    ]),
+                ),
+                (
+                    "/apis/discovery.k8s.io/v1/namespaces/shop/endpointslices",
+                    list(vec![
+                        endpoint_slice("web-x1", "web", "10.0.0.5"),
+                        endpoint_slice("partner-api-1", "partner-api", "203.0.113.7"),
+                        serde_json::json!({
+                            "metadata": { "name": "legacy-db-1",
+                                "labels": { "kubernetes.io/service-name": "legacy-db" } },
+                            "addressType": "IPv4",
+                            "endpoints": []
+                        }),
+                    ]),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let findings = K8sClient::find_unused_resources_with_client(
+            client,
+            "shop",
+            UnusedResourceOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let summary: Vec<_> = findings
+            .iter()
+            .map(|f| (format!("{}/{}", f.kind, f.name), f.size_bytes))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("ConfigMap/stale".to_string(), Some(5)),
+                ("Secret/old-token".to_string(), Some(5)),
+                ("PersistentVolume/pv-1".to_string(), Some(1_073_741_824)),
+                (
+                    "PersistentVolumeClaim/scratch".to_string(),
+                    Some(524_288_000)
+                ),
+                ("ReplicaSet/web-2".to_string(), None),
+                ("ReplicaSet/web-1".to_string(), None),
+                ("Job/migrate".to_string(), None),
+                ("Service/old".to_string(), None),
+                ("Service/legacy-db".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            findings[8].reason,
+            "No selector and no ready endpoints in its EndpointSlices"
+        );
+        assert!(findings.iter().all(|f| f.age_seconds.unwrap() > 86_400));
+        assert_eq!(findings[2].namespace, None);
+        assert_eq!(
+            findings[4].reason,
+            "Revision 2 of Deployment web is beyond its history limit of 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_events_filters_window_and_sorts_newest_first() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        let spawned = tokio::spawn(async move {
+            let mut handle = handle;
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let uri = request.uri().to_string();
+            let event = |name: &str, last: &str| {
+                serde_json::json!({
+                    "metadata": { "name": name, "namespace": "shop" },
+                    "involvedObject": { "kind": "Pod", "name": "web", "uid": "pod-uid" },
+                    "type": "Warning",
+                    "reason": "BackOff",
+                    "count": 2,
+                    "source": { "component": "kubelet" },
+                    "lastTimestamp": last
+                })
+            };
+            let body = serde_json::json!({
+                "apiVersion": "v1", "kind": "EventList", "metadata": {},
+                "items": [
+                    event("old", "2024-01-01T00:00:00Z"),
+                    event("early", "2024-01-02T08:00:00Z"),
+                    event("late", "2024-01-02T12:00:00Z"),
+                ]
+            });
+            send.send_response(
+                Response::builder()
+                    .body(kube::client::Body::from(serde_json::to_vec(&body).unwrap()))
+                    .unwrap(),
+            );
+            uri
+        });
+
+        let filter = EventFilter {
+            namespace: Some("shop".to_string()),
+            involved_uid: Some("pod-uid".to_string()),
+            event_type: Some(EventType::Warning),
+            since: Some("2024-01-02T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let events = K8sClient::list_events_with_client(client, &filter)
+            .await
+            .unwrap();
+
+        let uri = spawned.await.unwrap();
+        assert!(uri.starts_with("/api/v1/namespaces/shop/events?"));
+        assert!(uri.contains("fieldSelector=involvedObject.uid%3Dpod-uid%2Ctype%3DWarning"));
+        let names: Vec<_> = events.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["late", "early"]);
+        assert_eq!(events[0].source.as_deref(), Some("kubelet"));
+        assert_eq!(events[0].count, 2);
+    }
+
+    #[test]
+    fn test_diagnose_pod_findings_and_remedies() {
+        let event = |reason: &str, container: &str, message: &str| ClusterEvent {
+            name: "web.1".to_string(),
+            namespace: "shop".to_string(),
+            involved_object: InvolvedObject {
+                kind: "Pod".to_string(),
+                name: "web".to_string(),
+                namespace: Some("shop".to_string()),
+                uid: None,
+                field_path: Some(format!("spec.containers{{{}}}", container)),
+            },
+            event_type: "Warning".to_string(),
+            reason: reason.to_string(),
+            message: message.to_string(),
+            count: 4,
+            source: None,
+            first_seen: None,
+            last_seen: None,
+        };
+        let pod = serde_json::json!({
+            "metadata": { "name": "web" },
+            "status": { "phase": "Running", "containerStatuses": [
+                {
+                    "name": "app", "restartCount": 5,
+                    "state": { "waiting": { "reason": "CrashLoopBackOff" } },
+                    "lastState": { "terminated": { "reason": "Error", "exitCode": 127 } }
+                },
+                {
+                    "name": "cache",
+                    "state": { "running": {} },
+                    "lastState": { "terminated": { "reason": "OOMKilled", "exitCode": 137 } }
+                },
+                {
+                    "name": "sidecar", "image": "registry.example.com/sidecar:1",
+                    "state": { "waiting": { "reason": "ImagePullBackOff", "message": "Back-off pulling image" } }
+                }
+            ] }
+        });
+        let events = vec![
+            event(
+                "Failed",
+                "sidecar",
+                "Failed to pull image: 401 Unauthorized",
+            ),
+            event(
+                "Unhealthy",
+                "app",
+                "Liveness probe failed: HTTP probe failed with statuscode: 500",
+            ),
+            event(
+                "Unhealthy",
+                "app",
+                "Liveness probe failed: connection refused",
+            ),
+        ];
+        let node = serde_json::json!({
+            "metadata": { "name": "node-1" },
+            "status": { "conditions": [
+                { "type": "Ready", "status": "True" },
+                { "type": "MemoryPressure", "status": "True", "message": "low memory" }
+            ] }
+        });
+        let findings = diagnose(&pod, &events, Some(&node), &[]);
+
+        let problems: Vec<_> = findings
+            .iter()
+            .map(|f| (f.problem, f.container.as_deref()))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (PodProblem::CrashLoopBackOff, Some("app")),
+                (PodProblem::OomKilled, Some("cache")),
+                (PodProblem::ImagePullBackOff, Some("sidecar")),
+                (PodProblem::ProbeFailure, Some("app")),
+                (PodProblem::NodeCondition, None),
+            ]
+        );
+        assert_eq!(findings[0].exit_code, Some(127));
+        assert!(findings[0].remedy.contains("command wasn't found"));
+        assert_eq!(
+            findings[2].message,
+            "Failed to pull image: 401 Unauthorized"
+        );
+        assert!(findings[2].remedy.contains("imagePullSecrets"));
+        assert!(findings[3].message.ends_with("(4 times)"));
+
+        let pending = serde_json::json!({
+            "status": { "phase": "Pending", "conditions": [{
+                "type": "PodScheduled", "status": "False", "reason": "Unschedulable",
+                "message": "0/3 nodes are available: 1 Insufficient memory, 2 node(s) had untolerated taint {dedicated: gpu}. preemption: 0/3 nodes are available."
+            }] }
+        });
+        let claim = DanglingReference {
+            kind: "PersistentVolumeClaim".to_string(),
+            name: "data".to_string(),
+            field_path: "spec.volumeName".to_string(),
+            target_kind: "PersistentVolume".to_string(),
+            target_name: None,
+            message: "claim is Pending and not bound to any volume".to_string(),
+        };
+        let findings = diagnose(&pending, &[], None, &[claim]);
+        let messages: Vec<_> = findings
+            .iter()
+            .map(|f| (f.problem, f.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (PodProblem::Unschedulable, "1 Insufficient memory"),
+                (
+                    PodProblem::Unschedulable,
+                    "2 node(s) had untolerated taint {dedicated: gpu}"
+                ),
+                (
+                    PodProblem::MissingReference,
+                    "claim is Pending and not bound to any volume"
+                ),
+            ]
+        );
+        assert!(findings[1].remedy.contains("toleration"));
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_service_follows_selector_ports_and_slices() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let pod = |name: &str, ready: &str, message: &str| {
+                serde_json::json!({
+                    "metadata": { "name": name, "namespace": "shop", "labels": { "app": "web" } },
+                    "spec": { "containers": [{ "name": "app", "image": "web",
+                        "ports": [{ "name": "http", "containerPort": 8080 }] }] },
+                    "status": { "phase": "Running", "podIP": "10.0.0.1", "conditions": [
+                        { "type": "Ready", "status": ready, "message": message }
+                    ] }
+                })
+            };
+            let responses = [
+                (
+                    "/api/v1/namespaces/shop/services/web",
+                    serde_json::json!({
+                        "apiVersion": "v1", "kind": "Service",
+                        "metadata": { "name": "web", "namespace": "shop" },
+                        "spec": { "selector": { "app": "web" }, "ports": [
+                            { "name": "http", "port": 80, "targetPort": "http" },
+                            { "name": "metrics", "port": 9090 }
+                        ] }
+                    }),
+                ),
+                (
+                    "/api/v1/namespaces/shop/pods",
+                    list(vec![
+                        pod("web-1", "True", ""),
+                        pod("web-2", "False", "containers with unready status: [app]"),
+                    ]),
+                ),
+                (
+                    "/apis/discovery.k8s.io/v1/namespaces/shop/endpointslices",
+                    list(vec![serde_json::json!({
+                        "metadata": { "name": "web-abc" },
+                        "addressType": "IPv4",
+                        "ports": [{ "name": "http", "port": 8080, "protocol": "TCP" }],
+                        "endpoints": [
+                            { "addresses": ["10.0.0.1"], "conditions": { "ready": true },
+                              "targetRef": { "kind": "Pod", "name": "web-1" } },
+                            { "addresses": ["10.0.0.2"], "conditions": { "ready": false },
+                              "targetRef": { "kind": "Pod", "name": "web-2" } }
+                        ]
+                    })]),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let diagnosis = K8sClient::diagnose_service_with_client(client, "web", "shop")
+            .await
+            .unwrap();
+
+        assert_eq!(diagnosis.selector.as_deref(), Some("app=web"));
+        assert_eq!(
+            diagnosis.pods[1].reason.as_deref(),
+            Some("containers with unready status: [app]")
+        );
+        assert_eq!(diagnosis.ports[0].target_port, "http");
+        assert_eq!(diagnosis.ports[0].container_port, Some(8080));
+        assert_eq!(diagnosis.ports[0].declared_by, 2);
+        assert_eq!(diagnosis.ports[1].target_port, "9090");
+        assert_eq!(diagnosis.endpoint_slices[0].ports, vec!["http:8080/TCP"]);
+        assert_eq!(
+            diagnosis.endpoint_slices[0].endpoints[1].pod.as_deref(),
+            Some("web-2")
+        );
+        let problems: Vec<_> = diagnosis.findings.iter().map(|f| f.problem).collect();
+        assert_eq!(
+            problems,
+            vec![ServiceProblem::PodNotReady, ServiceProblem::PortNotDeclared]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_resource_table_uses_server_table_or_computes_columns() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |items: Vec<serde_json::Value>| serde_json::json!({ "apiVersion": "v1", "kind": "List", "metadata": {}, "items": items });
+            let server_row = |name: &str, restarts: i64, age: &str| {
+                serde_json::json!({
+                    "cells": [name, "1/1", "Running", restarts, age],
+                    "object": { "metadata": { "name": name, "namespace": "shop" } }
+                })
+            };
+            let responses = [
+                (
+                    "/api/v1/pods",
+                    serde_json::json!({
+                        "kind": "Table", "apiVersion": "meta.k8s.io/v1",
+                        "columnDefinitions": [
+                            { "name": "Name", "type": "string", "priority": 0 },
+                            { "name": "Ready", "type": "string", "priority": 0 },
+                            { "name": "Status", "type": "string", "priority": 0 },
+                            { "name": "Restarts", "type": "string", "priority": 0 },
+                            { "name": "Age", "type": "string", "priority": 0 }
+                        ],
+                        "rows": [
+                            server_row("a", 0, "3d"),
+                            server_row("b", 12, "5m"),
+                            server_row("c", 2, "26h")
+                        ]
+                    }),
+                ),
+                // Servers that can't render a Table fall back to the plain list
+                (
+                    "/apis/apps/v1/deployments",
+                    list(vec![
+                        serde_json::json!({
+                            "metadata": { "name": "web", "namespace": "shop",
+                                "creationTimestamp": "2020-01-01T00:00:00Z" },
+                            "spec": { "replicas": 3 },
+                            "status": { "readyReplicas": 2, "updatedReplicas": 3, "availableReplicas": 2 }
+                        }),
+                        serde_json::json!({
+                            "metadata": { "name": "api", "namespace": "shop",
+                                "creationTimestamp": "2021-01-01T00:00:00Z" },
+                            "spec": { "replicas": 1 },
+                            "status": {}
+                        }),
+                    ]),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let options = TableOptions {
+            sort_by: Some("restarts".to_string()),
+            descending: true,
+        };
+        let table = K8sClient::list_resource_table_with_client(
+            client.clone(),
+            K8sResourceType::Pod,
+            options,
+        )
+        .await
+        .unwrap();
+        assert!(table.server_side);
+        let names: Vec<_> = table.rows.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "c", "a"]);
+
+        let options = TableOptions {
+            sort_by: Some("Age".to_string()),
+            descending: false,
+        };
+        let table = K8sClient::list_resource_table_with_client(
+            client.clone(),
+            K8sResourceType::Deployment,
+            options,
+        )
+        .await
+        .unwrap();
+        assert!(!table.server_side);
+        let columns: Vec<_> = table.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            columns,
+            vec!["Name", "Ready", "Up-to-date", "Available", "Age"]
+        );
+        assert_eq!(table.rows[0].name, "api");
+        assert_eq!(
+            table.rows[1].cells[..4],
+            [
+                serde_json::json!("web"),
+                serde_json::json!("2/3"),
+                serde_json::json!(3),
+                serde_json::json!(2)
+            ]
+        );
+
+        let options = TableOptions {
+            sort_by: Some("Widgets".to_string()),
+            descending: false,
+        };
+        let err = K8sClient::list_resource_table_with_client(client, K8sResourceType::Pod, options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Unknown column: Widgets");
+    }
+
+    #[test]
+    fn test_computed_table_pod_status_matches_kubectl() {
+        let now = "2024-01-02T00:00:00Z".parse().unwrap();
+        let pods = vec![
+            serde_json::json!({
+                "metadata": { "name": "crashing", "creationTimestamp": "2024-01-01T21:30:00Z" },
+                "spec": { "containers": [{ "name": "a" }, { "name": "b" }] },
+                "status": { "phase": "Running", "containerStatuses": [
+                    { "name": "a", "ready": true, "restartCount": 1, "state": { "running": {} } },
+                    { "name": "b", "ready": false, "restartCount": 4,
+                      "state": { "waiting": { "reason": "CrashLoopBackOff" } } }
+                ] }
+            }),
+            serde_json::json!({
+                "metadata": { "name": "starting", "creationTimestamp": "2024-01-01T23:59:15Z" },
+                "spec": { "initContainers": [{ "name": "i1" }, { "name": "i2" }], "containers": [{ "name": "a" }] },
+                "status": { "phase": "Pending", "initContainerStatuses": [
+                    { "name": "i1", "state": { "terminated": { "exitCode": 0 } } },
+                    { "name": "i2", "state": { "running": {} } }
+                ] }
+            }),
+        ];
+        let table = computed_table(&K8sResourceType::Pod, &pods, now);
+
+        let cells: Vec<_> = table.rows.iter().map(|r| r.cells.clone()).collect();
+        assert_eq!(
+            cells[0],
+            serde_json::json!([
+                "crashing",
+                "1/2",
+                "CrashLoopBackOff",
+                5,
+                "<none>",
+                "<none>",
+                "150m"
+            ])
+            .as_array()
+            .unwrap()
+            .clone()
+        );
+        assert_eq!(cells[1][2], "Init:1/2");
+        assert_eq!(cells[1][6], "45s");
+        assert_eq!(table.columns[4].priority, 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_custom_resource_table_honours_printer_columns() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let widget = |name: &str, size: i64| {
+                serde_json::json!({
+                    "apiVersion": "example.com/v1", "kind": "Widget",
+                    "metadata": { "name": name, "namespace": "shop" },
+                    "spec": { "size": size },
+                    "status": { "conditions": [{ "type": "Ready", "status": "True" }] }
+                })
+            };
+            let responses = [
+                (
+                    "/apis/apiextensions.k8s.io/v1/customresourcedefinitions/widgets.example.com",
+                    serde_json::json!({
+                        "apiVersion": "apiextensions.k8s.io/v1", "kind": "CustomResourceDefinition",
+                        "metadata": { "name": "widgets.example.com" },
+                        "spec": {
+                            "group": "example.com", "scope": "Namespaced",
+                            "names": { "kind": "Widget", "plural": "widgets" },
+                            "versions": [{
+                                "name": "v1", "served": true, "storage": true,
+                                "additionalPrinterColumns": [
+                                    { "name": "Size", "type": "integer", "jsonPath": ".spec.size" },
+                                    { "name": "Ready", "type": "string", "jsonPath": ".status.conditions[0].status" }
+                                ]
+                            }]
+                        }
+                    }),
+                ),
+                (
+                    "/apis/example.com/v1/widgets",
+                    serde_json::json!({
+                        "apiVersion": "example.com/v1", "kind": "WidgetList", "metadata": {},
+                        "items": [widget("small", 1), widget("big", 10), widget("mid", 5)]
+                    }),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let options = TableOptions {
+            sort_by: Some("Size".to_string()),
+            descending: true,
+        };
+        let table = K8sClient::list_custom_resource_table_with_client(
+            client,
+            "widgets.example.com",
+            options,
+        )
+        .await
+        .unwrap();
+
+        let columns: Vec<_> = table.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(columns, vec!["Name", "Size", "Ready"]);
+        let rows: Vec<_> = table.rows.iter().map(|r| r.cells.clone()).collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    serde_json::json!("big"),
+                    serde_json::json!(10),
+                    serde_json::json!("True")
+                ],
+                vec![
+                    serde_json::json!("mid"),
+                    serde_json::json!(5),
+                    serde_json::json!("True")
+                ],
+                vec![
+                    serde_json::json!("small"),
+                    serde_json::json!(1),
+                    serde_json::json!("True")
+                ],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cluster_overview_aggregates_nodes_pods_and_requests() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |kind: &str, items: serde_json::Value| serde_json::json!({ "kind": kind, "apiVersion": "v1", "metadata": {}, "items": items });
+            let node = |name: &str, ready: &str, unschedulable: bool| {
+                serde_json::json!({
+                    "metadata": { "name": name },
+                    "spec": { "unschedulable": unschedulable },
+                    "status": {
+                        "conditions": [{ "type": "Ready", "status": ready }],
+                        "allocatable": { "cpu": "2", "memory": "4Gi" }
+                    }
+                })
+            };
+            let pod = |name: &str, phase: &str, node: Option<&str>, cpu: &str, init_cpu: &str| {
+                serde_json::json!({
+                    "metadata": { "name": name, "namespace": "shop" },
+                    "spec": {
+                        "nodeName": node,
+                        "initContainers": [{ "name": "init", "resources": { "requests": { "cpu": init_cpu } } }],
+                        "containers": [
+                            { "name": "app", "resources": { "requests": { "cpu": cpu, "memory": "256Mi" } } },
+                            { "name": "sidecar", "resources": { "requests": { "cpu": cpu } } }
+                        ]
+                    },
+                    "status": { "phase": phase }
+                })
+            };
+            let deployment = |name: &str, available: i64| {
+                serde_json::json!({
+                    "metadata": { "name": name, "namespace": "shop" },
+                    "spec": { "replicas": 2 },
+                    "status": { "availableReplicas": available }
+                })
+            };
+            let warning = serde_json::json!({
+                "metadata": { "name": "web.1", "namespace": "shop" },
+                "involvedObject": { "kind": "Pod", "name": "web" },
+                "type": "Warning",
+                "reason": "BackOff",
+                "lastTimestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            });
+            let responses = [
+                (
+                    "/version",
+                    serde_json::json!({
+                        "major": "1", "minor": "31", "gitVersion": "v1.31.2", "gitCommit": "",
+                        "gitTreeState": "", "buildDate": "", "goVersion": "", "compiler": "", "platform": ""
+                    }),
+                ),
+                (
+                    "/api/v1/nodes",
+                    list(
+                        "NodeList",
+                        serde_json::json!([node("a", "True", false), node("b", "False", true),]),
+                    ),
+                ),
+                (
+                    "/api/v1/pods",
+                    list(
+                        "PodList",
+                        serde_json::json!([
+                            pod("web", "Running", Some("a"), "250m", "100m"),
+                            pod("migrate", "Pending", Some("a"), "100m", "1"),
+                            pod("queued", "Pending", None, "1", "0"),
+                            pod("done", "Succeeded", Some("b"), "1", "0"),
+                        ]),
+                    ),
+                ),
+                (
+                    "/api/v1/namespaces",
+                    list(
+                        "NamespaceList",
+                        serde_json::json!([
+                            { "metadata": { "name": "shop" } },
+                            { "metadata": { "name": "default" } },
+                        ]),
+                    ),
+                ),
+                (
+                    "/apis/apps/v1/deployments",
+                    list(
+                        "DeploymentList",
+                        serde_json::json!([deployment("web", 2), deployment("api", 1),]),
+                    ),
+                ),
+                (
+                    "/apis/apps/v1/statefulsets",
+                    list("StatefulSetList", serde_json::json!([])),
+                ),
+                (
+                    "/apis/apps/v1/daemonsets",
+                    list("DaemonSetList", serde_json::json!([])),
+                ),
+                (
+                    "/apis/batch/v1/jobs",
+                    list("JobList", serde_json::json!([])),
+                ),
+                (
+                    "/api/v1/events",
+                    list("EventList", serde_json::json!([warning])),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let overview = K8sClient::get_cluster_overview_with_client(client)
+            .await
+            .unwrap();
+
+        assert_eq!(overview.server_version, "v1.31.2");
+        assert_eq!(overview.nodes.total, 2);
+        assert_eq!(overview.nodes.ready, 1);
+        assert_eq!(overview.nodes.unschedulable, 1);
+        assert_eq!(overview.namespaces, vec!["default", "shop"]);
+        assert_eq!(overview.pods_by_phase.get("Pending"), Some(&2));
+        assert_eq!(overview.pods_by_phase.get("Running"), Some(&1));
+        assert_eq!(overview.failing_workloads.len(), 1);
+        assert_eq!(overview.failing_workloads[0].name, "api");
+        assert_eq!(
+            overview.failing_workloads[0].health.status,
+            HealthStatus::Degraded
+        );
+        assert_eq!(overview.warning_count, 1);
+        assert_eq!(overview.recent_warnings[0].reason, "BackOff");
+        // web: 2 x 250m; migrate: its 1 cpu init container outweighs 2 x 100m. The
+        // unscheduled and finished pods hold nothing.
+        assert_eq!(overview.resources.cpu_requests_millis, 1500);
+        assert_eq!(overview.resources.cpu_allocatable_millis, 4000);
+        assert_eq!(overview.resources.memory_requests_bytes, 512 * 1024 * 1024);
+        assert_eq!(
+            overview.resources.memory_allocatable_bytes,
+            8 * 1024 * 1024 * 1024
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pod_and_node_usage_join_requests_limits_and_allocatable() {
+        let serve = |with_metrics: bool| {
+            let (mock_service, handle) =
+                mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+            tokio::spawn(async move {
+                let list = |items: serde_json::Value| serde_json::json!({ "kind": "List", "apiVersion": "v1", "metadata": {}, "items": items });
+                let metrics = list(serde_json::json!([{
+                    "metadata": { "name": "web", "namespace": "shop" },
+                    "timestamp": "2024-01-02T12:00:00Z",
+                    "window": "15s",
+                    "containers": [
+                        { "name": "app", "usage": { "cpu": "125000000n", "memory": "192Mi" } },
+                        { "name": "sidecar", "usage": { "cpu": "5m", "memory": "64Mi" } }
+                    ]
+                }]));
+                let node_metrics = list(serde_json::json!([{
+                    "metadata": { "name": "node-a" },
+                    "usage": { "cpu": "1500m", "memory": "2Gi" }
+                }]));
+                let pods = list(serde_json::json!([{
+                    "metadata": { "name": "web", "namespace": "shop" },
+                    "spec": {
+                        "nodeName": "node-a",
+                        "containers": [
+                            { "name": "app", "resources": {
+                                "requests": { "cpu": "250m", "memory": "256Mi" },
+                                "limits": { "memory": "512Mi" }
+                            } },
+                            { "name": "sidecar", "resources": { "requests": { "cpu": "10m", "memory": "64Mi" } } }
+                        ]
+                    }
+                }]));
+                let nodes = list(serde_json::json!([{
+                    "metadata": { "name": "node-a" },
+                    "status": { "allocatable": { "cpu": "2", "memory": "8Gi" } }
+                }]));
+                // Without metrics-server nothing serves the metrics.k8s.io paths
+                let metrics_api = if with_metrics {
+                    "/apis/metrics.k8s.io/v1beta1"
+                } else {
+                    "/apis/absent"
+                };
+                let pod_metrics_path = format!("{}/namespaces/shop/pods", metrics_api);
+                let node_metrics_path = format!("{}/nodes", metrics_api);
+                let responses = [
+                    (pod_metrics_path.as_str(), metrics),
+                    (node_metrics_path.as_str(), node_metrics),
+                    ("/api/v1/namespaces/shop/pods", pods),
+                    ("/api/v1/nodes", nodes),
+                ];
+                serve_by_path(handle, responses).await;
+            });
+            Client::new(mock_service, "default")
+        };
+
+        let report = K8sClient::list_pod_usage_with_client(serve(true), Some("shop"))
+            .await
+            .unwrap();
+        assert!(report.metrics_available);
+        let pod = &report.items[0];
+        assert_eq!(pod.node.as_deref(), Some("node-a"));
+        assert_eq!(pod.window.as_deref(), Some("15s"));
+        assert_eq!(pod.usage.cpu.used, 130);
+        assert_eq!(pod.usage.cpu.request, Some(260));
+        assert_eq!(pod.usage.cpu.percent_of_request, Some(50.0));
+        assert_eq!(pod.usage.cpu.percent_of_allocatable, Some(6.5));
+        // The sidecar has no limit, so neither does the pod
+        assert_eq!(pod.usage.memory.limit, None);
+        assert_eq!(pod.usage.memory.percent_of_request, Some(80.0));
+        let app = &pod.containers[0];
+        assert_eq!(app.usage.memory.percent_of_limit, Some(37.5));
+        assert_eq!(app.usage.cpu.percent_of_limit, None);
+
+        let nodes = K8sClient::list_node_usage_with_client(serve(true))
+            .await
+            .unwrap();
+        assert_eq!(nodes.items[0].usage.cpu.percent_of_allocatable, Some(75.0));
+        assert_eq!(
+            nodes.items[0].usage.memory.percent_of_allocatable,
+            Some(25.0)
+        );
+
+        let missing = K8sClient::list_pod_usage_with_client(serve(false), Some("shop"))
+            .await
+            .unwrap();
+        assert!(!missing.metrics_available);
+        assert!(missing.items.is_empty());
+        assert!(missing.message.unwrap().contains("metrics-server"));
+    }
+
+    #[tokio::test]
+    async fn test_graphs_do_not_report_broken_selectors_when_pods_are_unreadable() {
+        let serve = || {
+            let (mock_service, handle) =
+                mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+            tokio::spawn(async move {
+                let service = serde_json::json!({
+                    "apiVersion": "v1", "kind": "Service",
+                    "metadata": { "name": "web", "namespace": "shop", "uid": "svc-uid" },
+                    "spec": { "selector": { "app": "web" } }
+                });
+                let services = serde_json::json!({
+                    "apiVersion": "v1", "kind": "ServiceList", "metadata": {},
+                    "items": [service.clone()]
+                });
+                // Pods aren't served, as for a user RBAC doesn't allow to list them
+                let responses = [
+                    ("/api/v1/namespaces/shop/services/web", service),
+                    ("/api/v1/namespaces/shop/services", services),
+                ];
+                serve_by_path(handle, responses).await;
+            });
+            Client::new(mock_service, "default")
+        };
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            serve(),
+            K8sResourceType::Service,
+            "web",
+            Some("shop".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(graph.edges.iter().all(|e| e.error.is_none()));
+        let health = graph.nodes[0].health.as_ref().unwrap();
+        assert_eq!(health.status, HealthStatus::Unknown);
+        assert!(health
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("could not list Pods"));
+
+        let graph = K8sClient::get_namespace_graph_with_client(
+            serve(),
+            "shop",
+            NamespaceGraphOptions {
+                collapse_unrelated: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let service = graph.nodes.iter().find(|n| n.id == "svc-uid").unwrap();
+        assert_eq!(
+            service.health.as_ref().unwrap().status,
+            HealthStatus::Unknown
+        );
+        assert!(graph.edges.iter().all(|e| e.error.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_graphs_skip_missing_optional_pod_references() {
+        let serve = || {
+            let (mock_service, handle) =
+                mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+            tokio::spawn(async move {
+                let pod = serde_json::json!({
+                    "apiVersion": "v1", "kind": "Pod",
+                    "metadata": { "name": "web", "namespace": "shop", "uid": "pod-uid" },
+                    "spec": {
+                        "serviceAccountName": "web",
+                        "containers": [{
+                            "name": "app",
+                            "envFrom": [
+                                { "configMapRef": { "name": "extra", "optional": true } },
+                                { "configMapRef": { "name": "tuning", "optional": true } },
+                                { "secretRef": { "name": "creds" } }
+                            ]
+                        }]
+                    }
+                });
+                let tuning = serde_json::json!({
+                    "apiVersion": "v1", "kind": "ConfigMap",
+                    "metadata": { "name": "tuning", "namespace": "shop", "uid": "tuning-uid" }
+                });
+                let account = serde_json::json!({
+                    "apiVersion": "v1", "kind": "ServiceAccount",
+                    "metadata": { "name": "web", "namespace": "shop", "uid": "sa-uid" }
+                });
+                let list = |kind: &str, items: serde_json::Value| serde_json::json!({ "apiVersion": "v1", "kind": kind, "metadata": {}, "items": items });
+                let responses = [
+                    ("/api/v1/namespaces/shop/pods/web", pod.clone()),
+                    ("/api/v1/namespaces/shop/configmaps/tuning", tuning.clone()),
+                    (
+                        "/api/v1/namespaces/shop/serviceaccounts/web",
+                        account.clone(),
+                    ),
+                    (
+                        "/api/v1/namespaces/shop/pods",
+                        list("PodList", serde_json::json!([pod])),
+                    ),
+                    (
+                        "/api/v1/namespaces/shop/configmaps",
+                        list("ConfigMapList", serde_json::json!([tuning])),
+                    ),
+                    (
+                        "/api/v1/namespaces/shop/secrets",
+                        list("SecretList", serde_json::json!([])),
+                    ),
+                    (
+                        "/api/v1/namespaces/shop/serviceaccounts",
+                        list("ServiceAccountList", serde_json::json!([account])),
+                    ),
+                ];
+                serve_by_path(handle, responses).await;
+            });
+            Client::new(mock_service, "default")
+        };
+        let check = |graph: &GraphData| {
+            let broken: Vec<&str> = graph
+                .edges
+                .iter()
+                .filter_map(|e| e.error.as_deref())
+                .collect();
+            assert_eq!(broken, vec!["Secret creds not found"]);
+            assert!(graph
+                .edges
+                .iter()
+                .any(|e| e.source == "pod-uid" && e.target == "tuning-uid"));
+        };
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            serve(),
+            K8sResourceType::Pod,
+            "web",
+            Some("shop".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+        check(&graph);
+
+        let graph = K8sClient::get_namespace_graph_with_client(
+            serve(),
+            "shop",
+            NamespaceGraphOptions::default(),
+        )
+        .await
+        .unwrap();
+        check(&graph);
+    }
+
+    #[tokio::test]
+    async fn test_cluster_overview_degrades_unreadable_sections() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let list = |kind: &str, items: serde_json::Value| serde_json::json!({ "apiVersion": "v1", "kind": kind, "metadata": {}, "items": items });
+            let responses = [
+                (
+                    "/version",
+                    serde_json::json!({
+                        "major": "1", "minor": "31", "gitVersion": "v1.31.2", "gitCommit": "",
+                        "gitTreeState": "", "buildDate": "", "goVersion": "", "compiler": "", "platform": ""
+                    }),
+                ),
+                (
+                    "/api/v1/pods",
+                    list(
+                        "PodList",
+                        serde_json::json!([{
+                            "metadata": { "name": "web", "namespace": "shop" },
+                            "spec": { "containers": [] },
+                            "status": { "phase": "Running" }
+                        }]),
+                    ),
+                ),
+                (
+                    "/api/v1/namespaces",
+                    list(
+                        "NamespaceList",
+                        serde_json::json!([{ "metadata": { "name": "shop" } }]),
+                    ),
+                ),
+                (
+                    "/apis/apps/v1/deployments",
+                    list("DeploymentList", serde_json::json!([])),
+                ),
+                (
+                    "/apis/apps/v1/statefulsets",
+                    list("StatefulSetList", serde_json::json!([])),
+                ),
+                (
+                    "/apis/apps/v1/daemonsets",
+                    list("DaemonSetList", serde_json::json!([])),
+                ),
+                (
+                    "/apis/batch/v1/jobs",
+                    list("JobList", serde_json::json!([])),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let overview = K8sClient::get_cluster_overview_with_client(client)
+            .await
+            .unwrap();
+
+        assert_eq!(overview.server_version, "v1.31.2");
+        assert_eq!(overview.namespaces, vec!["shop"]);
+        assert_eq!(overview.pods_by_phase.get("Running"), Some(&1));
+        assert_eq!(overview.nodes.total, 0);
+        assert_eq!(
+            overview.unavailable.keys().collect::<Vec<_>>(),
+            vec!["nodes", "warnings"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_namespace_graph_usage_is_opt_in_and_kept_in_summaries() {
+        let serve = || {
+            let (mock_service, handle) =
+                mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+            let server = tokio::spawn(async move {
+                let list = |items: serde_json::Value| serde_json::json!({ "kind": "List", "apiVersion": "v1", "metadata": {}, "items": items });
+                let responses = [
+                    (
+                        "/apis/metrics.k8s.io/v1beta1/namespaces/shop/pods",
+                        list(serde_json::json!([{
+                            "metadata": { "name": "web", "namespace": "shop" },
+                            "containers": [{ "name": "app", "usage": { "cpu": "130m", "memory": "256Mi" } }]
+                        }])),
+                    ),
+                    (
+                        "/api/v1/namespaces/shop/pods",
+                        list(serde_json::json!([{
+                            "metadata": { "name": "web", "namespace": "shop", "uid": "pod-uid" },
+                            "spec": {
+                                "nodeName": "node-a",
+                                "containers": [{ "name": "app", "resources": {
+                                    "requests": { "cpu": "260m", "memory": "512Mi" }
+                                } }]
+                            }
+                        }])),
+                    ),
+                    (
+                        "/api/v1/nodes",
+                        list(serde_json::json!([{
+                            "metadata": { "name": "node-a" },
+                            "status": { "allocatable": { "cpu": "2", "memory": "8Gi" } }
+                        }])),
+                    ),
+                ];
+                serve_by_path(handle, responses).await
+            });
+            (Client::new(mock_service, "default"), server)
+        };
+        // Summary nodes drop the pod spec, but usage is joined with it before they do
+        let options = |usage: bool| NamespaceGraphOptions {
+            collapse_unrelated: false,
+            detail: GraphDetail::Summary,
+            usage,
+            ..Default::default()
+        };
+
+        let (client, server) = serve();
+        let graph = K8sClient::get_namespace_graph_with_client(client, "shop", options(true))
+            .await
+            .unwrap();
+        let requested = server.await.unwrap();
+        assert!(graph.nodes[0].data.get("spec").is_none());
+        let usage = graph.nodes[0].usage.as_ref().unwrap();
+        assert_eq!(usage.cpu.used, 130);
+        assert_eq!(usage.cpu.percent_of_request, Some(50.0));
+        assert_eq!(usage.cpu.percent_of_allocatable, Some(6.5));
+        assert_eq!(usage.memory.percent_of_request, Some(50.0));
+        let calls = |path: &str| requested.iter().filter(|p| *p == path).count();
+        assert_eq!(calls("/api/v1/namespaces/shop/pods"), 1);
+        assert_eq!(calls("/api/v1/nodes"), 1);
+
+        let (client, server) = serve();
+        let graph = K8sClient::get_namespace_graph_with_client(client, "shop", options(false))
+            .await
+            .unwrap();
+        let requested = server.await.unwrap();
+        assert!(graph.nodes[0].usage.is_none());
+        assert!(!requested
+            .iter()
+            .any(|p| p.starts_with("/apis/metrics.k8s.io")));
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_graph_skips_references_it_cannot_read() {
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let forbidden = |path: &str| {
+                serde_json::json!({
+                    "kind": "Status", "apiVersion": "v1", "status": "Failure", "code": 403,
+                    "reason": "Forbidden",
+                    "message": format!("secrets is forbidden: cannot get {}", path)
+                })
+            };
+            let responses = [
+                (
+                    "/api/v1/namespaces/shop/pods/web",
+                    serde_json::json!({
+                        "apiVersion": "v1", "kind": "Pod",
+                        "metadata": { "name": "web", "namespace": "shop", "uid": "pod-uid" },
+                        "spec": {
+                            "containers": [{
+                                "name": "app",
+                                "envFrom": [
+                                    { "secretRef": { "name": "creds" } },
+                                    { "secretRef": { "name": "gone" } }
+                                ]
+                            }]
+                        }
+                    }),
+                ),
+                ("/api/v1/namespaces/shop/secrets/creds", forbidden("creds")),
+                (
+                    "/api/v1/namespaces/shop/serviceaccounts/default",
+                    forbidden("default"),
+                ),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+
+        let graph = K8sClient::get_resource_graph_with_client(
+            client,
+            K8sResourceType::Pod,
+            "web",
+            Some("shop".to_string()),
+            GraphOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let broken: Vec<&str> = graph
+            .edges
+            .iter()
+            .filter_map(|e| e.error.as_deref())
+            .collect();
+        assert_eq!(broken, vec!["Secret gone not found"]);
+        assert!(!graph.nodes.iter().any(|n| n.id.contains("creds")));
+    }
+
+    fn export_sample_graph() -> GraphData {
+        GraphData {
+            nodes: vec![
+                GraphNode {
+                    id: "ing-uid".to_string(),
+                    label: "shop".to_string(),
+                    resource_type: "Ingress".to_string(),
+                    data: serde_json::json!({ "spec": { "huge": "payload" } }),
+                    health: None,
+                    usage: None,
+                },
+                GraphNode {
+                    id: "svc-uid".to_string(),
+                    label: "api \"v2\" <beta>".to_string(),
+                    resource_type: "Service".to_string(),
+                    data: serde_json::json!({}),
+                    health: None,
+                    usage: None,
+                },
+            ],
+            edges: vec![GraphEdge {
+                id: "ing-uid-svc-uid".to_string(),
+                source: "ing-uid".to_string(),
+                target: "svc-uid".to_string(),
+                label: "shop.example.com/api|v2".to_string(),
+                error: None,
+            }],
+            stats: None,
+        }
+    }
+
+    #[test]
+    fn test_render_graph_dot_and_mermaid() {
+        let graph = export_sample_graph();
+
+        let (content_type, dot) = render_graph(&graph, &GraphFormat::Dot);
+        assert_eq!(content_type, "text/vnd.graphviz");
+        assert!(dot.starts_with("digraph resources {"));
+        assert!(
+            dot.contains(r#""svc-uid" [label="api \"v2\" <beta>\n(Service)", kind="Service"];"#)
+        );
+        assert!(dot.contains(r#""ing-uid" -> "svc-uid" [label="shop.example.com/api|v2"];"#));
+        assert!(!dot.contains("payload"));
+
+        let (_, mermaid) = render_graph(&graph, &GraphFormat::Mermaid);
+        assert_eq!(
+            mermaid,
+            concat!(
+                "flowchart TD\n",
+                "  n0[\"Ingress: shop\"]\n",
+                "  n1[\"Service: api #quot;v2#quot; <beta>\"]\n",
+                "  n0 -->|\"shop.example.com/api#124;v2\"| n1\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_graph_graphml_and_cytoscape() {
+        let graph = export_sample_graph();
+
+        let (content_type, graphml) = render_graph(&graph, &GraphFormat::Graphml);
+        assert_eq!(content_type, "application/graphml+xml");
+        assert!(graphml.contains(
+            r#"<node id="svc-uid"><data key="label">api &quot;v2&quot; &lt;beta&gt;</data><data key="kind">Service</data></node>"#
+        ));
+        assert!(
+            graphml.contains(r#"<edge id="ing-uid-svc-uid" source="ing-uid" target="svc-uid">"#)
+        );
+        assert!(!graphml.contains("payload"));
+
+        let (_, cytoscape) = render_graph(&graph, &GraphFormat::Cytoscape);
+        let cytoscape: serde_json::Value = serde_json::from_str(&cytoscape).unwrap();
+        assert_eq!(
+            cytoscape["elements"]["nodes"][0]["data"],
+            serde_json::json!({ "id": "ing-uid", "label": "shop", "kind": "Ingress" })
+        );
+        assert_eq!(
+            cytoscape["elements"]["edges"][0]["data"]["label"],
+            "shop.example.com/api|v2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_patch_resource_merge_with_precondition() {
+        let (mock_service, mut handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::PATCH);
+            assert_eq!(
+                request.uri().path(),
+                "/apis/apps/v1/namespaces/default/deployments/web"
+            );
+            assert_eq!(request.uri().query(), Some("&dryRun=All"));
+            assert_eq!(
+                request.headers()[http::header::CONTENT_TYPE],
+                "application/merge-patch+json"
+            );
+
+            let body = request.into_body().collect_bytes().await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(patch["metadata"]["resourceVersion"], "42");
+            assert_eq!(patch["metadata"]["annotations"]["team"], "core");
+
+            let deployment = serde_json::json!({
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "metadata": {
+                    "name": "web",
+                    "namespace": "default",
+                    "resourceVersion": "43",
+                    "annotations": { "team": "core" }
+                }
+            });
+            let response = Response::builder()
+                .body(kube::client::Body::from(
+                    serde_json::to_vec(&deployment).unwrap(),
+                ))
+                .unwrap();
+            send.send_response(response);
+        });
+
+        let patched = K8sClient::patch_resource_with_client(
+            client,
+            K8sResourceType::Deployment,
+            "web",
+            Some("default".to_string()),
+            ResourcePatch {
+                patch_type: PatchType::Merge,
+                body: serde_json::json!({ "metadata": { "annotations": { "team": "core" } } }),
+                dry_run: true,
+                resource_version: Some("42".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(patched["metadata"]["resourceVersion"], "43");
+    }
+
+    #[tokio::test]
+    async fn test_patch_resource_json_patch_prepends_test_op() {
+        let (mock_service, mut handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(
+                request.headers()[http::header::CONTENT_TYPE],
+                "application/json-patch+json"
+            );
+
+            let body = request.into_body().collect_bytes().await.unwrap();
+            let ops: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(ops[0]["op"], "test");
+            assert_eq!(ops[0]["path"], "/metadata/resourceVersion");
+            assert_eq!(ops[0]["value"], "7");
+            assert_eq!(ops[1]["op"], "replace");
+
+            let pod = Pod {
+                metadata: k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta {
+                    name: Some("my-pod".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let response = Response::builder()
+                .body(kube::client::Body::from(serde_json::to_vec(&pod).unwrap()))
+                .unwrap();
+            send.send_response(response);
+        });
+
+        let patched = K8sClient::patch_resource_with_client(
+            client,
+            K8sResourceType::Pod,
+            "my-pod",
+            Some("default".to_string()),
+            ResourcePatch {
+                patch_type: PatchType::Json,
+                body: serde_json::json!([
+                    { "op": "replace", "path": "/spec/containers/0/image", "value": "nginx:1.27" }
+                ]),
+                dry_run: false,
+                resource_version: Some("7".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(patched["metadata"]["name"], "my-pod");
+    }
+
+    #[tokio::test]
+    async fn test_replace_resource_stale_version_conflicts() {
+        let (mock_service, mut handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::GET);
+
+            let live = serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": { "name": "settings", "namespace": "default", "resourceVersion": "11" },
+                "data": { "mode": "live" }
+            });
+            let response = Response::builder()
+                .body(kube::client::Body::from(serde_json::to_vec(&live).unwrap()))
+                .unwrap();
+            send.send_response(response);
+        });
+
+        let outcome = K8sClient::replace_resource_with_client(
+            client,
+            K8sResourceType::ConfigMap,
+            "settings",
+            Some("default".to_string()),
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": { "name": "settings", "namespace": "default", "resourceVersion": "10" },
+                "data": { "mode": "edited" }
+            }),
+            false,
+        )
+        .await
+        .unwrap();
+
+        match outcome {
+            ReplaceOutcome::Conflict { current } => assert_eq!(current["data"]["mode"], "live"),
+            other => panic!("expected conflict, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_resource_dry_run_preview() {
+        let (mock_service, mut handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::GET);
+            let live = serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": {
+                    "name": "settings",
+                    "namespace": "default",
+                    "resourceVersion": "11",
+                    "managedFields": [{ "manager": "kubectl" }]
+                },
+                "data": { "mode": "live" }
+            });
+            send.send_response(
+                Response::builder()
+                    .body(kube::client::Body::from(serde_json::to_vec(&live).unwrap()))
+                    .unwrap(),
+            );
+
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::PUT);
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/namespaces/default/configmaps/settings"
+            );
+            assert!(request
+                .uri()
+                .query()
+                .unwrap_or_default()
+                .contains("dryRun=All"));
+            let body = request.into_body().collect_bytes().await.unwrap();
+            let mut replaced: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            replaced["metadata"]["managedFields"] = serde_json::json!([{ "manager": "backend" }]);
+            send.send_response(
+                Response::builder()
+                    .body(kube::client::Body::from(
+                        serde_json::to_vec(&replaced).unwrap(),
+                    ))
+                    .unwrap(),
+            );
+        });
+
+        let outcome = K8sClient::replace_resource_with_client(
+            client,
+            K8sResourceType::ConfigMap,
+            "settings",
+            Some("default".to_string()),
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": { "name": "settings", "namespace": "default", "resourceVersion": "11" },
+                "data": { "mode": "edited" }
+            }),
+            true,
+        )
+        .await
+        .unwrap();
+
+        match outcome {
+            ReplaceOutcome::Preview {
+                before,
+                after,
+                diff,
+            } => {
+                assert!(before["metadata"].get("managedFields").is_none());
+                assert_eq!(after["data"]["mode"], "edited");
+                assert_eq!(
+                    diff,
+                    serde_json::json!([
+                        { "op": "replace", "path": "/data/mode", "value": "edited" }
+                    ])
+                );
+            }
+            other => panic!("expected preview, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_debug_container_waits_for_running() {
+        let (mock_service, mut handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::PATCH);
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/namespaces/default/pods/app/ephemeralcontainers"
+            );
+            assert_eq!(
+                request.headers()[http::header::CONTENT_TYPE],
+                "application/strategic-merge-patch+json"
+            );
+            let body = request.into_body().collect_bytes().await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let container = &patch["spec"]["ephemeralContainers"][0];
+            assert_eq!(container["name"], "debugger-abcde");
+            assert_eq!(container["image"], "busybox:1.36");
+            assert_eq!(container["targetContainerName"], "app");
+            assert_eq!(container["tty"], true);
+
+            let pod = serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Pod",
+                "metadata": { "name": "app", "namespace": "default" }
+            });
+            send.send_response(
+                Response::builder()
+                    .body(kube::client::Body::from(serde_json::to_vec(&pod).unwrap()))
+                    .unwrap(),
+            );
+
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::GET);
+            let pod = serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Pod",
+                "metadata": { "name": "app", "namespace": "default" },
+                "status": {
+                    "ephemeralContainerStatuses": [{
+                        "name": "debugger-abcde",
+                        "image": "busybox:1.36",
+                        "imageID": "",
+                        "ready": false,
+                        "restartCount": 0,
+                        "state": { "running": { "startedAt": "2024-01-01T00:00:00Z" } }
+                    }]
+                }
+            });
+            send.send_response(
+                Response::builder()
+                    .body(kube::client::Body::from(serde_json::to_vec(&pod).unwrap()))
+                    .unwrap(),
+            );
+        });
+
+        K8sClient::create_debug_container_with_client(
+            client,
+            "app",
+            "default",
+            DebugContainerSpec {
+                name: "debugger-abcde".to_string(),
+                image: "busybox:1.36".to_string(),
+                target_container: Some("app".to_string()),
+                command: vec![],
+            },
+            std::time::Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_job_from_cronjob_sets_owner_and_annotation() {
+        let cronjob: k8s_openapi::api::batch::v1::CronJob =
+            serde_json::from_value(serde_json::json!({
+                "metadata": { "name": "nightly-report", "namespace": "batch", "uid": "cj-uid" },
+                "spec": {
+                    "schedule": "0 2 * * *",
+                    "jobTemplate": {
+                        "metadata": { "labels": { "app": "report" } },
+                        "spec": {
+                            "template": {
+                                "spec": {
+                                    "restartPolicy": "Never",
+                                    "containers": [{ "name": "report", "image": "report:1.0" }]
+                                }
+                            }
+                        }
+                    }
+                }
+            }))
+            .unwrap();
+
+        let job = K8sClient::job_from_cronjob(&cronjob, "ab12c").unwrap();
+
+        assert_eq!(
+            job.metadata.name.as_deref(),
+            Some("nightly-report-manual-ab12c")
+        );
+        assert_eq!(job.metadata.namespace.as_deref(), Some("batch"));
+        assert_eq!(job.metadata.labels.unwrap()["app"], "report");
+        assert_eq!(
+            job.metadata.annotations.unwrap()["cronjob.kubernetes.io/instantiate"],
+            "manual"
+        );
+        let owner = &job.metadata.owner_references.unwrap()[0];
+        assert_eq!(owner.kind, "CronJob");
+        assert_eq!(owner.api_version, "batch/v1");
+        assert_eq!(owner.uid, "cj-uid");
+        assert_eq!(owner.controller, Some(true));
+        assert!(job.spec.is_some());
+    }
+
+    #[test]
+    fn test_job_for_rerun_strips_controller_fields() {
+        let controller_labels = serde_json::json!({
+            "app": "migrate",
+            "controller-uid": "old-uid",
+            "batch.kubernetes.io/controller-uid": "old-uid",
+            "job-name": "migrate",
+            "batch.kubernetes.io/job-name": "migrate"
+        });
+        let mut job: k8s_openapi::api::batch::v1::Job = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "migrate", "namespace": "default", "labels": controller_labels },
+            "spec": {
+                "selector": { "matchLabels": { "batch.kubernetes.io/controller-uid": "old-uid" } },
+                "template": {
+                    "metadata": { "labels": controller_labels },
+                    "spec": {
+                        "restartPolicy": "Never",
+                        "containers": [{ "name": "migrate", "image": "migrate:2" }]
+                    }
+                }
+            },
+            "status": {
+                "conditions": [{ "type": "Failed", "status": "True" }]
+            }
+        }))
+        .unwrap();
+
+        let rerun = K8sClient::job_for_rerun(&job, "9f3e1").unwrap();
+
+        assert_eq!(rerun.metadata.name.as_deref(), Some("migrate-rerun-9f3e1"));
+        let spec = rerun.spec.unwrap();
+        assert!(spec.selector.is_none());
+        let pod_labels = spec.template.metadata.unwrap().labels.unwrap();
+        assert_eq!(pod_labels.len(), 1);
+        assert_eq!(pod_labels["app"], "migrate");
+        assert_eq!(rerun.metadata.labels.unwrap().len(), 1);
+
+        job.status = None;
+        assert!(K8sClient::job_for_rerun(&job, "9f3e1").is_err());
+    }
+
+    #[test]
+    fn test_derived_job_name_fits_label_limit() {
+        let name = K8sClient::derived_job_name(&"a".repeat(80), "manual", "ab12c");
+        assert_eq!(name.len(), 63);
+        assert!(name.ends_with("-manual-ab12c"));
+    }
+
+    #[test]
+    fn test_classify_drain_pod() {
+        let pod = |value: serde_json::Value| -> Pod { serde_json::from_value(value).unwrap() };
+        let owned_by = |kind: &str| {
+            serde_json::json!([{
+                "apiVersion": "apps/v1", "kind": kind, "name": "owner", "uid": "u", "controller": true
+            }])
+        };
+        let options = DrainOptions::default();
+
+        let daemon = pod(serde_json::json!({
+            "metadata": { "name": "fluentd", "ownerReferences": owned_by("DaemonSet") }
+        }));
+        assert!(matches!(
+            K8sClient::classify_drain_pod(&daemon, &options),
+            DrainDecision::Skip(_)
+        ));
+
+        let mirror = pod(serde_json::json!({
+            "metadata": { "name": "etcd", "annotations": { "kubernetes.io/config.mirror": "x" } }
+        }));
+        assert!(matches!(
+            K8sClient::classify_drain_pod(&mirror, &options),
+            DrainDecision::Skip(_)
+        ));
+
+        let bare = pod(serde_json::json!({ "metadata": { "name": "scratch" } }));
+        assert!(matches!(
+            K8sClient::classify_drain_pod(&bare, &options),
+            DrainDecision::Block(_)
+        ));
+        let forced = DrainOptions {
+            force: true,
+            ..DrainOptions::default()
+        };
+        assert_eq!(
+            K8sClient::classify_drain_pod(&bare, &forced),
+            DrainDecision::Evict
+        );
+
+        let cache = pod(serde_json::json!({
+            "metadata": { "name": "cache", "ownerReferences": owned_by("ReplicaSet") },
+            "spec": { "containers": [], "volumes": [{ "name": "tmp", "emptyDir": {} }] }
+        }));
+        assert!(matches!(
+            K8sClient::classify_drain_pod(&cache, &options),
+            DrainDecision::Block(_)
+        ));
+
+        let web = pod(serde_json::json!({
+            "metadata": { "name": "web", "ownerReferences": owned_by("ReplicaSet") }
+        }));
+        assert_eq!(
+            K8sClient::classify_drain_pod(&web, &options),
+            DrainDecision::Evict
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drain_node_retries_pdb_blocked_eviction() {
+        let (mock_service, mut handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+
+        tokio::spawn(async move {
+            let json_response = |status: u16, value: serde_json::Value| {
+                Response::builder()
+                    .status(status)
+                    .body(kube::client::Body::from(
+                        serde_json::to_vec(&value).unwrap(),
+                    ))
+                    .unwrap()
+            };
+
+            // 1. Cordon
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::PATCH);
+            assert_eq!(request.uri().path(), "/api/v1/nodes/worker-1");
+            let body = request.into_body().collect_bytes().await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(patch["spec"]["unschedulable"], true);
+            send.send_response(json_response(
+                200,
+                serde_json::json!({ "apiVersion": "v1", "kind": "Node", "metadata": { "name": "worker-1" } }),
+            ));
+
+            // 2. Pods on the node
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.uri().path(), "/api/v1/pods");
+            assert!(request
+                .uri()
+                .query()
+                .unwrap_or_default()
+                .contains("fieldSelector=spec.nodeName%3Dworker-1"));
+            send.send_response(json_response(
+                200,
+                serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "PodList",
+                    "metadata": {},
+                    "items": [
+                        {
+                            "metadata": {
+                                "name": "fluentd", "namespace": "logging",
+                                "ownerReferences": [{ "apiVersion": "apps/v1", "kind": "DaemonSet", "name": "fluentd", "uid": "ds", "controller": true }]
+                            }
+                        },
+                        {
+                            "metadata": {
+                                "name": "web-1", "namespace": "shop", "uid": "web-1-uid",
+                                "ownerReferences": [{ "apiVersion": "apps/v1", "kind": "ReplicaSet", "name": "web", "uid": "rs", "controller": true }]
+                            }
+                        }
+                    ]
+                }),
+            ));
+
+            // 3. First eviction is refused by a PDB, the retry goes through
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(request.method(), http::Method::POST);
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/namespaces/shop/pods/web-1/eviction"
+            );
+            send.send_response(json_response(
+                429,
+                serde_json::json!({
+                    "kind": "Status", "apiVersion": "v1", "status": "Failure", "code": 429,
+                    "reason": "TooManyRequests",
+                    "message": "Cannot evict pod as it would violate the pod's disruption budget."
+                }),
+            ));
+
+            let (request, send) = handle.next_request().await.expect("Service not called");
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/namespaces/shop/pods/web-1/eviction"
+            );
+            send.send_response(json_response(
+                201,
+                serde_json::json!({ "kind": "Status", "apiVersion": "v1", "status": "Success", "code": 201 }),
+            ));
+
+            // 4. The evicted pod is still terminating, then it is gone; a replacement with
+            // the same name but a new uid doesn't count
+            let pods_on_node = |web_uid: &str| {
+                serde_json::json!({
+                    "apiVersion": "v1", "kind": "PodList", "metadata": {},
+                    "items": [
+                        { "metadata": { "name": "fluentd", "namespace": "logging", "uid": "fluentd-uid" } },
+                        { "metadata": { "name": "web-1", "namespace": "shop", "uid": web_uid } }
+                    ]
+                })
+            };
+            for web_uid in ["web-1-uid", "replacement-uid"] {
+                let (request, send) = handle.next_request().await.expect("Service not called");
+                assert_eq!(request.uri().path(), "/api/v1/pods");
+                assert!(request
+                    .uri()
+                    .query()
+                    .unwrap_or_default()
+                    .contains("fieldSelector=spec.nodeName%3Dworker-1"));
+                send.send_response(json_response(200, pods_on_node(web_uid)));
+            }
+        });
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(32);
+        K8sClient::drain_node_with_client(
+            client,
+            "worker-1",
+            DrainOptions::default(),
+            tx,
+            std::time::Duration::from_millis(1),
+        )
+        .await
+        .unwrap();
+
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+
+        assert_eq!(
+            events[0],
+            DrainEvent::Cordoned {
+                node: "worker-1".to_string()
+            }
+        );
+        assert!(matches!(&events[1], DrainEvent::Skipped { name, .. } if name == "fluentd"));
+        assert!(matches!(&events[2], DrainEvent::Blocked { name, .. } if name == "web-1"));
+        assert!(matches!(&events[3], DrainEvent::Evicted { name, .. } if name == "web-1"));
+        assert!(matches!(&events[4], DrainEvent::Deleted { name, .. } if name == "web-1"));
+        assert_eq!(
+            events[5],
+            DrainEvent::Completed {
+                evicted: 1,
+                blocking: vec![]
+            }
+        );
+
+        // A pod still shutting down when the timeout passes keeps the node from being drained
+        let (mock_service, handle) =
+            mock::pair::<Request<kube::client::Body>, Response<kube::client::Body>>();
+        let client = Client::new(mock_service, "default");
+        tokio::spawn(async move {
+            let pods = serde_json::json!({
+                "apiVersion": "v1", "kind": "PodList", "metadata": {},
+                "items": [{
+                    "metadata": {
+                        "name": "web-1", "namespace": "shop", "uid": "web-1-uid",
+                        "deletionTimestamp": "2024-01-01T00:00:00Z",
+                        "ownerReferences": [{ "apiVersion": "apps/v1", "kind": "ReplicaSet", "name": "web", "uid": "rs", "controller": true }]
+                    }
+                }]
+            });
+            let node = serde_json::json!({ "apiVersion": "v1", "kind": "Node", "metadata": { "name": "worker-1" } });
+            let eviction = serde_json::json!({ "kind": "Status", "apiVersion": "v1", "status": "Success", "code": 201 });
+            let responses = [
+                ("/api/v1/nodes/worker-1", node),
+                ("/api/v1/pods", pods),
+                ("/api/v1/namespaces/shop/pods/web-1/eviction", eviction),
+            ];
+            serve_by_path(handle, responses).await;
+        });
+        let (tx, mut rx) = tokio::sync::mpsc::channel(32);
+        let options = DrainOptions {
+            timeout_seconds: 0,
+            ..Default::default()
+        };
+        K8sClient::drain_node_with_client(
+            client,
+            "worker-1",
+            options,
+            tx,
+            std::time::Duration::from_millis(1),
+        )
+        .await
+        .unwrap();
+        let mut last = None;
+        while let Some(event) = rx.recv().await {
+            last = Some(event);
+        }
+        assert_eq!(
+            last,
+            Some(DrainEvent::Completed {
+                evicted: 1,
+                blocking: vec!["shop/web-1".to_string()]
+            })
+        );
+    }
 }
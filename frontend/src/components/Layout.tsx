@@ -31,6 +31,7 @@ export function Layout() {
         K8sResourceType.DaemonSet,
         K8sResourceType.Job,
         K8sResourceType.CronJob,
+        K8sResourceType.Node,
       ]
     },
     {
@@ -39,6 +40,7 @@ export function Layout() {
       resources: [
         K8sResourceType.Service,
         K8sResourceType.Ingress,
+        K8sResourceType.IngressClass,
       ]
     },
     {
@@ -9,7 +9,7 @@ import {
   startPortForward,
   stopPortForward,
 } from '../services/api'
-import type { PortForwardRequest } from '../services/api'
+import type { GraphDetail, PortForwardRequest } from '../services/api'
 import { K8sResourceType } from '../types/k8s'
 
 export function useContexts() {
@@ -36,10 +36,10 @@ export function useResource(context: string, resourceType: K8sResourceType, name
   })
 }
 
-export function useResourceGraph(context: string, resourceType: K8sResourceType, name: string, namespace?: string) {
+export function useResourceGraph(context: string, resourceType: K8sResourceType, name: string, namespace?: string, depth?: number, detail?: GraphDetail, includeUsage = false) {
   return useQuery({
-    queryKey: ['resource-graph', context, resourceType, name, namespace],
-    queryFn: () => getResourceGraph(context, resourceType, name, namespace),
+    queryKey: ['resource-graph', context, resourceType, name, namespace, depth, detail, includeUsage],
+    queryFn: () => getResourceGraph(context, resourceType, name, namespace, depth, detail, includeUsage),
     enabled: !!context && !!resourceType && !!name,
   })
 }
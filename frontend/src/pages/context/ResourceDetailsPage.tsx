@@ -17,6 +17,7 @@ import dagre from 'dagre'
 
 import { useResource, useResourceGraph, usePortForwards, usePortForwardMutations, usePodLogs } from '../../hooks/useK8s'
 import { K8sResourceType } from '../../types/k8s'
+import type { HealthStatus } from '../../types/k8s'
 
 // Layout helper
 const getLayoutedElements = (nodes: Node[], edges: Edge[]) => {
@@ -63,6 +64,16 @@ const getEdgeColor = (label: string) => {
   }
 };
 
+const getHealthColor = (status?: HealthStatus) => {
+  switch (status) {
+    case 'healthy': return '#059669';     // Emerald 600
+    case 'progressing': return '#2563eb'; // Blue 600
+    case 'degraded': return '#d97706';    // Amber 600
+    case 'error': return '#dc2626';       // Red 600
+    default: return '#d1d5db';            // Gray 300
+  }
+};
+
 export function ResourceDetailsPage() {
   const { context, resourceType, name } = useParams<{ context: string, resourceType: string, name: string }>()
   const navigate = useNavigate()
@@ -98,7 +109,10 @@ export function ResourceDetailsPage() {
     context || '',
     resourceType as K8sResourceType,
     name || '',
-    namespace
+    namespace,
+    undefined,
+    'summary',
+    true
   )
 
   const [nodes, setNodes, onNodesChange] = useNodesState([]);
@@ -117,16 +131,27 @@ export function ResourceDetailsPage() {
               <span className="text-[6px] text-stone-400 uppercase tracking-widest mt-1 font-semibold">
                 {node.resource_type}
               </span>
+              {node.health?.message && (
+                <span className="text-[8px] mt-1 truncate w-full" style={{ color: getHealthColor(node.health.status) }} title={node.health.message}>
+                  {node.health.message}
+                </span>
+              )}
+              {node.usage && (
+                <span className="text-[8px] mt-1 text-stone-500">
+                  {node.usage.cpu.used}m cpu · {Math.round(node.usage.memory.used / (1024 * 1024))}Mi mem
+                </span>
+              )}
             </div>
           ),
           originalLabel: node.label,
           resourceType: node.resource_type,
-          namespace: node.data?.metadata?.namespace
+          namespace: node.data?.namespace
         },
         position: { x: 0, y: 0 }, // Layout will fix this
         style: { 
           background: node.id === resource?.metadata?.uid ? '#fffbeb' : '#fff',
           border: node.id === resource?.metadata?.uid ? '2px solid #d97706' : '1px solid #e5e7eb',
+          borderLeft: `4px solid ${getHealthColor(node.health?.status)}`,
           borderRadius: '8px',
           padding: '8px',
           width: 180,
@@ -136,26 +161,31 @@ export function ResourceDetailsPage() {
         },
       }));
 
-      const initialEdges: Edge[] = graphData.edges.map((edge: any) => ({
-        id: edge.id,
-        source: edge.source,
-        target: edge.target,
-        label: edge.label,
-        type: 'smoothstep',
-        markerEnd: {
-          type: MarkerType.ArrowClosed,
-          color: getEdgeColor(edge.label),
-        },
-        style: { 
-          stroke: getEdgeColor(edge.label),
-          strokeWidth: 2,
-        },
-        labelStyle: { 
-          fill: getEdgeColor(edge.label), 
-          fontSize: 10,
-          fontWeight: 500
-        },
-      }));
+      const initialEdges: Edge[] = graphData.edges.map((edge: any) => {
+        // Broken references (missing target, selector without pods) are drawn dashed in red
+        const color = edge.error ? getHealthColor('error') : getEdgeColor(edge.label);
+        return {
+          id: edge.id,
+          source: edge.source,
+          target: edge.target,
+          label: edge.error ? `${edge.label}: ${edge.error}` : edge.label,
+          type: 'smoothstep',
+          markerEnd: {
+            type: MarkerType.ArrowClosed,
+            color,
+          },
+          style: { 
+            stroke: color,
+            strokeWidth: 2,
+            ...(edge.error ? { strokeDasharray: '4 2' } : {}),
+          },
+          labelStyle: { 
+            fill: color, 
+            fontSize: 10,
+            fontWeight: 500
+          },
+        };
+      });
 
       const { nodes: layoutedNodes, edges: layoutedEdges } = getLayoutedElements(initialNodes, initialEdges);
       setNodes(layoutedNodes);
@@ -165,7 +195,8 @@ export function ResourceDetailsPage() {
 
   const onNodeClick = (_: React.MouseEvent, node: Node) => {
     const { resourceType: type, originalLabel: label, namespace: ns } = node.data;
-    if (type && label) {
+    // Placeholders for missing objects have nothing to open
+    if (type && label && !node.id.startsWith('missing:')) {
       const url = `/${context}/${type}/${encodeURIComponent(label)}${ns ? `?namespace=${ns}` : ''}`;
       navigate(url);
     }
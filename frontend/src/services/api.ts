@@ -1,5 +1,5 @@
 import axios from 'axios';
-import type { Pod, K8sResourceType, GraphData } from '../types/k8s';
+import type { Pod, K8sResourceType, GraphData, DanglingReference, UnusedResource, ClusterEvent, EventFilter, PodDiagnosis, ServiceDiagnosis, ResourceTable, ClusterOverview, UsageReport, PodUsage, NodeUsage, UsageHistory } from '../types/k8s';
 
 const api = axios.create({
   baseURL: import.meta.env.VITE_API_URL || 'http://localhost:3000/api',
@@ -10,23 +10,324 @@ export const getContexts = async (): Promise<string[]> => {
   return response.data.contexts;
 };
 
-export const getResources = async (context: string, resourceType: K8sResourceType): Promise<any[]> => {
-  const response = await api.get(`/${context}/resources/${resourceType}`);
+// With `includeUsage`, Pods and Nodes carry live cpu and memory under `usage`
+export const getResources = async (
+  context: string,
+  resourceType: K8sResourceType,
+  includeUsage = false
+): Promise<any[]> => {
+  const params = includeUsage ? { usage: true } : {};
+  const response = await api.get(`/${context}/resources/${resourceType}`, { params });
   return response.data;
 };
 
-export const getResource = async (context: string, resourceType: K8sResourceType, name: string, namespace?: string): Promise<any> => {
-  const params = namespace ? { namespace } : {};
+export const listPodUsage = async (context: string, namespace?: string): Promise<UsageReport<PodUsage>> => {
+  const response = await api.get(`/${context}/metrics/pods`, { params: namespace ? { namespace } : {} });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export const listNodeUsage = async (context: string): Promise<UsageReport<NodeUsage>> => {
+  const response = await api.get(`/${context}/metrics/nodes`);
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export interface HistoryOptions {
+  namespace?: string;
+  name?: string;
+  since?: string;
+  until?: string;
+  // Bucket size in seconds; each bucket keeps its peak
+  step?: number;
+}
+
+// The backend samples its startup contexts from launch, and any other context once its usage or
+// history has been read, until nobody asks about it for the retention period
+export const getUsageHistory = async (
+  context: string,
+  kind: 'pods' | 'nodes',
+  options: HistoryOptions = {}
+): Promise<UsageHistory[]> => {
+  const response = await api.get(`/${context}/metrics/history/${kind}`, { params: options });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export const getResource = async (
+  context: string,
+  resourceType: K8sResourceType,
+  name: string,
+  namespace?: string,
+  includeEvents = false
+): Promise<any> => {
+  const params = { ...(namespace ? { namespace } : {}), ...(includeEvents ? { events: true } : {}) };
   const response = await api.get(`/${context}/resources/${resourceType}/${name}`, { params });
   return response.data;
 };
 
-export const getResourceGraph = async (context: string, resourceType: K8sResourceType, name: string, namespace?: string): Promise<GraphData> => {
-  const params = namespace ? { namespace } : {};
+export interface TableOptions {
+  sortBy?: string;
+  descending?: boolean;
+}
+
+const tableParams = (options: TableOptions) => ({
+  ...(options.sortBy ? { sort_by: options.sortBy } : {}),
+  ...(options.descending ? { descending: true } : {}),
+});
+
+// Served from a short-lived backend cache unless `refresh` is set
+export const getClusterOverview = async (context: string, refresh = false): Promise<ClusterOverview> => {
+  const response = await api.get(`/${context}/overview`, { params: refresh ? { refresh } : {} });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export const listResourceTable = async (
+  context: string,
+  resourceType: K8sResourceType,
+  options: TableOptions = {}
+): Promise<ResourceTable> => {
+  const response = await api.get(`/${context}/tables/${resourceType}`, { params: tableParams(options) });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+// `crd` is the CustomResourceDefinition's name, e.g. `certificates.cert-manager.io`
+export const listCustomResourceTable = async (context: string, crd: string, options: TableOptions = {}): Promise<ResourceTable> => {
+  const response = await api.get(`/${context}/tables/crd/${crd}`, { params: tableParams(options) });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export type PatchType = 'json' | 'merge' | 'strategic';
+
+export interface PatchOptions {
+  namespace?: string;
+  patchType?: PatchType;
+  dryRun?: boolean;
+  resourceVersion?: string;
+}
+
+export const patchResource = async (context: string, resourceType: K8sResourceType, name: string, patch: unknown, options: PatchOptions = {}): Promise<any> => {
+  const params = {
+    namespace: options.namespace,
+    patch_type: options.patchType,
+    dry_run: options.dryRun,
+    resource_version: options.resourceVersion,
+  };
+  const response = await api.patch(`/${context}/resources/${resourceType}/${name}`, patch, { params });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export type ReplaceOutcome =
+  | { outcome: 'preview'; before: any; after: any; diff: any[] }
+  | { outcome: 'applied'; resource: any }
+  | { outcome: 'conflict'; current: any };
+
+export const replaceResource = async (context: string, resourceType: K8sResourceType, name: string, object: unknown, options: { namespace?: string; dryRun?: boolean } = {}): Promise<ReplaceOutcome> => {
+  const params = { namespace: options.namespace, dry_run: options.dryRun };
+  // A 409 still carries the live object, so don't let axios treat it as a failure
+  const response = await api.put(`/${context}/resources/${resourceType}/${name}`, object, {
+    params,
+    validateStatus: (status) => status < 400 || status === 409,
+  });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+const unwrap = (data: any) => {
+  if (data?.error) {
+    throw new Error(data.error);
+  }
+  return data;
+};
+
+export const diagnosePod = async (context: string, name: string, namespace: string): Promise<PodDiagnosis> => {
+  const response = await api.get(`/${context}/pods/${name}/diagnose`, { params: { namespace } });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export const diagnoseService = async (context: string, name: string, namespace: string): Promise<ServiceDiagnosis> => {
+  const response = await api.get(`/${context}/services/${name}/diagnose`, { params: { namespace } });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export const triggerCronJob = async (context: string, name: string, namespace?: string): Promise<any> => {
+  const response = await api.post(`/${context}/cronjobs/${name}/trigger`, null, { params: { namespace } });
+  return unwrap(response.data);
+};
+
+export const setCronJobSuspended = async (context: string, name: string, suspend: boolean, namespace?: string): Promise<any> => {
+  const action = suspend ? 'suspend' : 'resume';
+  const response = await api.post(`/${context}/cronjobs/${name}/${action}`, null, { params: { namespace } });
+  return unwrap(response.data);
+};
+
+export const rerunJob = async (context: string, name: string, namespace?: string): Promise<any> => {
+  const response = await api.post(`/${context}/jobs/${name}/rerun`, null, { params: { namespace } });
+  return unwrap(response.data);
+};
+
+export const setNodeCordoned = async (context: string, name: string, cordon: boolean): Promise<any> => {
+  const action = cordon ? 'cordon' : 'uncordon';
+  const response = await api.post(`/${context}/nodes/${name}/${action}`);
+  return unwrap(response.data);
+};
+
+export interface DrainOptions {
+  timeout_seconds?: number;
+  grace_period_seconds?: number;
+  force?: boolean;
+  delete_emptydir_data?: boolean;
+}
+
+export type DrainEvent =
+  | { type: 'cordoned'; node: string }
+  | { type: 'skipped'; namespace: string; name: string; reason: string }
+  | { type: 'evicted'; namespace: string; name: string }
+  | { type: 'deleted'; namespace: string; name: string }
+  | { type: 'blocked'; namespace: string; name: string; reason: string }
+  | { type: 'failed'; namespace: string; name: string; reason: string }
+  | { type: 'completed'; evicted: number; blocking: string[] }
+  | { type: 'error'; message: string };
+
+// Drain progress arrives as server-sent events on a POST, so EventSource can't be used
+export const drainNode = async (context: string, name: string, options: DrainOptions, onEvent: (event: DrainEvent) => void): Promise<void> => {
+  const url = api.getUri({ url: `/${context}/nodes/${name}/drain`, params: options });
+  const response = await fetch(url, { method: 'POST' });
+  if (!response.body) {
+    throw new Error('Drain response has no body');
+  }
+  const reader = response.body.pipeThrough(new TextDecoderStream()).getReader();
+  let buffer = '';
+  for (;;) {
+    const { value, done } = await reader.read();
+    if (done) break;
+    buffer += value;
+    const frames = buffer.split('\n\n');
+    buffer = frames.pop() ?? '';
+    for (const frame of frames) {
+      const data = frame
+        .split('\n')
+        .filter((line) => line.startsWith('data:'))
+        .map((line) => line.slice(5).trim())
+        .join('\n');
+      if (data) onEvent(JSON.parse(data));
+    }
+  }
+};
+
+// 'summary' nodes carry only kind, name, namespace, uid, phase and readiness; fetch the object itself with getResource
+export type GraphDetail = 'summary' | 'full';
+
+// With `includeUsage`, full-detail Pod and Node nodes carry live cpu and memory under `usage`
+export const getResourceGraph = async (context: string, resourceType: K8sResourceType, name: string, namespace?: string, depth?: number, detail?: GraphDetail, includeUsage = false): Promise<GraphData> => {
+  const params = { ...(namespace ? { namespace } : {}), ...(depth ? { depth } : {}), ...(detail ? { detail } : {}), ...(includeUsage ? { usage: true } : {}) };
   const response = await api.get(`/${context}/resources/${resourceType}/${name}/graph`, { params });
   return response.data;
 };
 
+export type GraphExportFormat = 'dot' | 'mermaid' | 'graphml' | 'cytoscape';
+
+// Export documents keep kinds and labels only, for pasting into docs or loading into other tools
+export const getGraphExportUrl = (context: string, resourceType: K8sResourceType, name: string, format: GraphExportFormat, namespace?: string, depth?: number): string => {
+  return api.getUri({
+    url: `/${context}/resources/${resourceType}/${name}/graph`,
+    params: { format, namespace, depth },
+  });
+};
+
+export interface NamespaceGraphOptions {
+  exclude?: K8sResourceType[];
+  collapse?: boolean;
+  detail?: GraphDetail;
+  usage?: boolean;
+}
+
+export const getNamespaceGraph = async (context: string, namespace: string, options: NamespaceGraphOptions = {}): Promise<GraphData> => {
+  const params = {
+    ...(options.exclude?.length ? { exclude: options.exclude.join(',') } : {}),
+    ...(options.collapse === undefined ? {} : { collapse: options.collapse }),
+    ...(options.detail ? { detail: options.detail } : {}),
+    ...(options.usage ? { usage: true } : {}),
+  };
+  const response = await api.get(`/${context}/namespaces/${namespace}/graph`, { params });
+  return response.data;
+};
+
+// References in a namespace that don't resolve: missing ConfigMaps, Secrets, Services and
+// ServiceAccounts, unbound PVCs and Service selectors matching no pods
+export const findDanglingReferences = async (context: string, namespace: string): Promise<DanglingReference[]> => {
+  const response = await api.get(`/${context}/namespaces/${namespace}/dangling-references`);
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export const findUnusedResources = async (
+  context: string,
+  namespace: string,
+  jobAgeDays?: number
+): Promise<UnusedResource[]> => {
+  const params = jobAgeDays !== undefined ? { job_age_days: jobAgeDays } : {};
+  const response = await api.get(`/${context}/namespaces/${namespace}/unused`, { params });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+export const listEvents = async (context: string, filter: EventFilter = {}): Promise<ClusterEvent[]> => {
+  const response = await api.get(`/${context}/events`, { params: filter });
+  if (response.data.error) {
+    throw new Error(response.data.error);
+  }
+  return response.data;
+};
+
+// Only events reported after the stream opens are sent. Returns a function that closes the stream.
+export const streamEvents = (
+  context: string,
+  filter: EventFilter,
+  onEvent: (event: ClusterEvent) => void,
+  onError?: (message: string) => void
+): (() => void) => {
+  const source = new EventSource(api.getUri({ url: `/${context}/events/stream`, params: filter }));
+  source.onmessage = (message) => onEvent(JSON.parse(message.data));
+  source.addEventListener('error', (message) => {
+    if (message instanceof MessageEvent) {
+      onError?.(JSON.parse(message.data).error);
+      source.close();
+    }
+  });
+  return () => source.close();
+};
+
 export const getPodLogs = async (context: string, name: string, namespace?: string): Promise<string> => {
   const params = namespace ? { namespace } : {};
   const response = await api.get(`/${context}/pods/${name}/logs`, { params });
@@ -36,6 +337,102 @@ export const getPodLogs = async (context: string, name: string, namespace?: stri
   return response.data.logs ?? '';
 };
 
+export interface ExecOptions {
+  namespace?: string;
+  container?: string;
+  command?: string;
+  tty?: boolean;
+  idleTimeout?: number;
+}
+
+// Binary frames use the Kubernetes channel protocol: first byte is the stream id
+export const ExecChannel = {
+  Stdin: 0,
+  Stdout: 1,
+  Stderr: 2,
+  Status: 3,
+  Resize: 4,
+} as const;
+
+export const getExecSocketUrl = (context: string, name: string, options: ExecOptions = {}): string => {
+  const base = new URL(api.defaults.baseURL ?? '', window.location.href);
+  base.protocol = base.protocol === 'https:' ? 'wss:' : 'ws:';
+  const url = new URL(`${base.pathname.replace(/\/$/, '')}/${context}/pods/${name}/exec`, base);
+  const params: Record<string, string | undefined> = {
+    namespace: options.namespace,
+    container: options.container,
+    command: options.command,
+    tty: options.tty === undefined ? undefined : String(options.tty),
+    idle_timeout: options.idleTimeout === undefined ? undefined : String(options.idleTimeout),
+  };
+  Object.entries(params).forEach(([key, value]) => {
+    if (value !== undefined) url.searchParams.set(key, value);
+  });
+  return url.toString();
+};
+
+export interface DebugOptions {
+  namespace?: string;
+  image?: string;
+  target?: string;
+  command?: string;
+  idleTimeout?: number;
+}
+
+export const getDebugSocketUrl = (context: string, name: string, options: DebugOptions = {}): string => {
+  const base = new URL(api.defaults.baseURL ?? '', window.location.href);
+  base.protocol = base.protocol === 'https:' ? 'wss:' : 'ws:';
+  const url = new URL(`${base.pathname.replace(/\/$/, '')}/${context}/pods/${name}/debug`, base);
+  const params: Record<string, string | undefined> = {
+    namespace: options.namespace,
+    image: options.image,
+    target: options.target,
+    command: options.command,
+    idle_timeout: options.idleTimeout === undefined ? undefined : String(options.idleTimeout),
+  };
+  Object.entries(params).forEach(([key, value]) => {
+    if (value !== undefined) url.searchParams.set(key, value);
+  });
+  return url.toString();
+};
+
+export interface FileCopyOptions {
+  namespace?: string;
+  container?: string;
+}
+
+export const getDownloadUrl = (context: string, name: string, path: string, options: FileCopyOptions = {}): string => {
+  return api.getUri({
+    url: `/${context}/pods/${name}/files`,
+    params: { path, namespace: options.namespace, container: options.container },
+  });
+};
+
+// Tar archives are unpacked into `path`; any other body is written to `path` as a single file
+export const uploadFiles = async (context: string, name: string, path: string, data: Blob, options: FileCopyOptions = {}): Promise<void> => {
+  await api.put(`/${context}/pods/${name}/files`, data, {
+    params: { path, namespace: options.namespace, container: options.container },
+    headers: { 'Content-Type': data.type || 'application/octet-stream' },
+  });
+};
+
+export interface AuditEntry {
+  id: string;
+  session_id: string;
+  timestamp: string;
+  context: string;
+  namespace: string;
+  pod: string;
+  container?: string;
+  command: string[];
+  event: { type: 'session_start' } | { type: 'session_end'; reason: string };
+}
+
+export const listAuditEntries = async (): Promise<AuditEntry[]> => {
+  const response = await api.get('/audit');
+  return response.data;
+};
+
 export interface PortForwardRequest {
   context: string;
   namespace: string;
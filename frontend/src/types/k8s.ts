@@ -7,10 +7,12 @@ export const K8sResourceType = {
   DaemonSet: "DaemonSet",
   Job: "Job",
   CronJob: "CronJob",
+  Node: "Node",
 
   // Service & Networking Objects
   Service: "Service",
   Ingress: "Ingress",
+  IngressClass: "IngressClass",
 
   // Storage Objects
   PersistentVolume: "PersistentVolume",
@@ -36,11 +38,21 @@ export interface Pod {
   node?: string;
 }
 
+export type HealthStatus = 'healthy' | 'progressing' | 'degraded' | 'error' | 'unknown';
+
+export interface NodeHealth {
+  status: HealthStatus;
+  message?: string;
+}
+
 export interface GraphNode {
   id: string;
   label: string;
   resource_type: string;
   data: any;
+  health?: NodeHealth;
+  // Pods and Nodes, when metrics-server is installed
+  usage?: ResourceUsage;
 }
 
 export interface GraphEdge {
@@ -48,9 +60,228 @@ export interface GraphEdge {
   source: string;
   target: string;
   label: string;
+  // Set when the reference is broken, e.g. the target does not exist
+  error?: string;
+}
+
+export interface GraphStats {
+  duration_ms: number;
+  api_calls: number;
+  cache_hits: number;
 }
 
 export interface GraphData {
   nodes: GraphNode[];
   edges: GraphEdge[];
+  stats?: GraphStats;
+}
+
+export interface DanglingReference {
+  kind: string;
+  name: string;
+  field_path: string;
+  target_kind: string;
+  // Unset when the reference is a selector rather than a name
+  target_name?: string;
+  message: string;
+}
+
+export interface UnusedResource {
+  kind: string;
+  name: string;
+  // Unset for cluster-scoped kinds such as PersistentVolumes
+  namespace?: string;
+  reason: string;
+  age_seconds?: number;
+  size_bytes?: number;
+}
+
+export type EventType = 'Normal' | 'Warning';
+
+export interface EventFilter {
+  namespace?: string;
+  involved_kind?: string;
+  involved_name?: string;
+  involved_uid?: string;
+  type?: EventType;
+  // RFC 3339 timestamps bounding when the event was last seen
+  since?: string;
+  until?: string;
+}
+
+export interface ClusterEvent {
+  name: string;
+  namespace: string;
+  involved_object: {
+    kind: string;
+    name: string;
+    namespace?: string;
+    uid?: string;
+    field_path?: string;
+  };
+  type: string;
+  reason: string;
+  message: string;
+  count: number;
+  source?: string;
+  first_seen?: string;
+  last_seen?: string;
+}
+
+export type PodProblem =
+  | 'crash_loop_back_off'
+  | 'image_pull_back_off'
+  | 'unschedulable'
+  | 'oom_killed'
+  | 'probe_failure'
+  | 'container_config_error'
+  | 'missing_reference'
+  | 'node_condition';
+
+export interface DiagnosisFinding {
+  problem: PodProblem;
+  container?: string;
+  message: string;
+  exit_code?: number;
+  log_tail?: string[];
+  remedy: string;
+}
+
+export interface PodDiagnosis {
+  name: string;
+  namespace: string;
+  phase: string;
+  node?: string;
+  health: NodeHealth;
+  // Empty when nothing looks wrong
+  findings: DiagnosisFinding[];
+}
+
+export type ServiceProblem =
+  | 'no_selector'
+  | 'no_matching_pods'
+  | 'pod_not_ready'
+  | 'target_port_not_found'
+  | 'port_not_declared'
+  | 'no_ready_endpoints';
+
+export interface ServiceDiagnosis {
+  name: string;
+  namespace: string;
+  service_type: string;
+  selector?: string;
+  pods: {
+    name: string;
+    ready: boolean;
+    phase: string;
+    ip?: string;
+    reason?: string;
+  }[];
+  ports: {
+    name?: string;
+    port: number;
+    protocol: string;
+    target_port: string;
+    container_port?: number;
+    declared_by: number;
+  }[];
+  endpoint_slices: {
+    name: string;
+    address_type: string;
+    ports: string[];
+    endpoints: { addresses: string[]; ready: boolean; pod?: string; node?: string }[];
+  }[];
+  // Empty when the Service's endpoints look right
+  findings: { problem: ServiceProblem; message: string; remedy: string }[];
+}
+
+export interface TableColumn {
+  name: string;
+  type: 'string' | 'integer' | 'number' | 'boolean' | 'date' | string;
+  description?: string;
+  // Columns above 0 are only shown in wide output
+  priority: number;
+}
+
+export interface ResourceTable {
+  columns: TableColumn[];
+  rows: { name: string; namespace?: string; cells: unknown[] }[];
+  server_side: boolean;
+}
+
+export interface ClusterOverview {
+  server_version: string;
+  nodes: { total: number; ready: number; unschedulable: number };
+  namespaces: string[];
+  pods_by_phase: Record<string, number>;
+  failing_workloads: { kind: string; name: string; namespace: string; health: NodeHealth }[];
+  // Warning Events in the last hour; only the newest are included in recent_warnings
+  warning_count: number;
+  recent_warnings: ClusterEvent[];
+  // Requests of scheduled pods that haven't finished, against node allocatable
+  resources: {
+    cpu_requests_millis: number;
+    cpu_allocatable_millis: number;
+    memory_requests_bytes: number;
+    memory_allocatable_bytes: number;
+  };
+  generated_at: string;
+  // Sections that could not be read (e.g. nodes, warnings) and why; their fields are empty
+  unavailable: Record<string, string>;
+}
+
+// cpu in millicores, memory in bytes; percentages are left out when there is nothing to compare with
+export interface UsageMeasure {
+  used: number;
+  request?: number;
+  limit?: number;
+  allocatable?: number;
+  percent_of_request?: number;
+  percent_of_limit?: number;
+  percent_of_allocatable?: number;
+}
+
+export interface ResourceUsage {
+  cpu: UsageMeasure;
+  memory: UsageMeasure;
+}
+
+export interface PodUsage {
+  name: string;
+  namespace: string;
+  node?: string;
+  timestamp?: string;
+  window?: string;
+  usage: ResourceUsage;
+  containers: { name: string; usage: ResourceUsage }[];
+}
+
+export interface NodeUsage {
+  name: string;
+  timestamp?: string;
+  window?: string;
+  usage: ResourceUsage;
+}
+
+// metrics_available is false, with the reason in message, when metrics-server isn't installed
+export interface UsageReport<T> {
+  metrics_available: boolean;
+  message?: string;
+  items: T[];
+}
+
+export interface UsageSample {
+  timestamp: string;
+  cpu_millis: number;
+  memory_bytes: number;
+  // Limits when sampled; allocatable for Nodes
+  cpu_limit_millis?: number;
+  memory_limit_bytes?: number;
+}
+
+export interface UsageHistory {
+  kind: 'Pod' | 'Node' | string;
+  name: string;
+  namespace?: string;
+  samples: UsageSample[];
 }